@@ -0,0 +1,289 @@
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::errors::{AppError, ErrorKind};
+use crate::wikilinks::{split_target, wikilink_regex};
+use crate::{atomic_write, vault_config, vault_ignore, vault_notes, vault_relative};
+
+/// Carpeta de adjuntos por defecto, relativa al vault. Si la configurada
+/// empieza por `./` se resuelve desde la carpeta de la nota.
+pub const DEFAULT_FOLDER: &str = "attachments";
+
+#[derive(Serialize, Clone)]
+pub struct SavedAttachment {
+    path: String,
+    rel: String,   // relativa al vault, lista para git_stage_files
+    embed: String, // Markdown a insertar en la nota
+}
+
+#[derive(Serialize, Clone)]
+pub struct UnusedAttachment {
+    path: String,
+    rel: String,
+    kind: String,
+    size: u64,
+}
+
+/// Tipo de adjunto segun la extension. None si no es un adjunto reconocido
+/// (las notas `.md` tampoco lo son).
+pub fn attachment_kind(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_string_lossy().to_lowercase();
+    let kind = match ext.as_str() {
+        "png" | "jpg" | "jpeg" | "gif" | "webp" | "svg" | "bmp" | "avif" => "image",
+        "pdf" => "pdf",
+        "mp3" | "wav" | "ogg" | "m4a" | "flac" => "audio",
+        "mp4" | "webm" | "mov" | "mkv" => "video",
+        "zip" | "csv" | "txt" | "json" | "docx" | "xlsx" | "pptx" | "odt" | "ods" => "file",
+        _ => return None,
+    };
+    Some(kind)
+}
+
+/// Nombre de archivo seguro: sin separadores, caracteres de control ni
+/// caracteres invalidos en Windows.
+fn sanitize_name(name: &str) -> String {
+    let clean: String = name
+        .trim()
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
+            c if c.is_control() => '-',
+            c => c,
+        })
+        .collect();
+    clean.trim_start_matches('.').to_string()
+}
+
+/// `nombre.ext`, `nombre 1.ext`, `nombre 2.ext`... el primero que no exista.
+fn unique_path(dir: &Path, name: &str) -> PathBuf {
+    let candidate = dir.join(name);
+    if !candidate.exists() {
+        return candidate;
+    }
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((s, e)) if !s.is_empty() => (s, format!(".{}", e)),
+        _ => (name, String::new()),
+    };
+    (1..)
+        .map(|i| dir.join(format!("{} {}{}", stem, i, ext)))
+        .find(|p| !p.exists())
+        .unwrap_or(candidate)
+}
+
+/// Ruta de `target` relativa a la carpeta `from`, con `/`.
+fn relative_to(from: &Path, target: &Path) -> String {
+    let from: Vec<Component> = from.components().collect();
+    let target: Vec<Component> = target.components().collect();
+    let common = from.iter().zip(&target).take_while(|(a, b)| a == b).count();
+
+    let mut parts: Vec<String> = vec!["..".to_string(); from.len() - common];
+    parts.extend(target[common..].iter().map(|c| c.as_os_str().to_string_lossy().to_string()));
+    parts.join("/")
+}
+
+/// Markdown para insertar el adjunto en la nota: imagen o link segun el tipo.
+fn embed_for(link: &str, name: &str, kind: Option<&str>) -> String {
+    let url = link.replace(' ', "%20");
+    let label = name.rsplit_once('.').map_or(name, |(stem, _)| stem);
+    match kind {
+        Some("image") => format!("![{}]({})", label, url),
+        _ => format!("[{}]({})", name, url),
+    }
+}
+
+// -- Referencias -------------------------------------------------------------
+
+/// `[texto](destino)`, `![alt](destino "titulo")` y `[x](<con espacios>)`;
+/// captura el destino. Tambien acepta espacios sin `<>`, como Obsidian.
+fn markdown_link_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r#"\]\(\s*(?:<([^>\n]+)>|([^)\n]+?))(?:\s+"[^"]*")?\s*\)"#).unwrap()
+    })
+}
+
+/// `<img src="...">` y similares (`video`, `audio`, `source`, `a href`);
+/// captura el destino.
+fn html_link_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r#"(?i)<(?:img|video|audio|source|embed|a)\b[^>]*?\s(?:src|href)\s*=\s*(?:"([^"]+)"|'([^']+)')"#)
+            .unwrap()
+    })
+}
+
+/// Decodifica los `%XX` de una URL (solo lo necesario para rutas locales).
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or_default();
+            if let Ok(b) = u8::from_str_radix(hex, 16) {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+/// Normaliza `a/./b/../c` sin tocar el disco.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for c in path.components() {
+        match c {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Adjuntos referenciados desde las notas: rutas absolutas (links Markdown
+/// o HTML) y nombres o rutas relativas al vault (wikilinks `![[x.png]]`).
+struct References {
+    paths: HashSet<PathBuf>,
+    names: HashSet<String>,
+}
+
+fn collect_references(vault: &Path) -> References {
+    let mut refs = References {
+        paths: HashSet::new(),
+        names: HashSet::new(),
+    };
+    for note in vault_notes(vault) {
+        let Ok(content) = fs::read_to_string(&note) else { continue };
+        let dir = note.parent().unwrap_or(vault);
+
+        let destinations = markdown_link_regex()
+            .captures_iter(&content)
+            .chain(html_link_regex().captures_iter(&content))
+            .filter_map(|cap| cap.get(1).or_else(|| cap.get(2)));
+        for dest in destinations {
+            let dest = percent_decode(dest.as_str().trim());
+            if dest.contains("://") || dest.starts_with('#') || dest.starts_with("mailto:") {
+                continue;
+            }
+            let dest = dest.split('#').next().unwrap_or_default();
+            let path = match dest.strip_prefix('/') {
+                Some(from_root) => vault.join(from_root),
+                None => dir.join(dest),
+            };
+            refs.paths.insert(normalize(&path));
+        }
+
+        for cap in wikilink_regex().captures_iter(&content) {
+            let (target, _) = split_target(&cap[1]);
+            refs.names.insert(target.trim().to_lowercase());
+        }
+    }
+    refs
+}
+
+impl References {
+    fn contains(&self, vault: &Path, path: &Path) -> bool {
+        if self.paths.contains(path) {
+            return true;
+        }
+        let name = path.file_name().unwrap_or_default().to_string_lossy().to_lowercase();
+        let rel = vault_relative(vault, path).to_lowercase();
+        self.names.contains(&name) || self.names.contains(&rel)
+    }
+}
+
+// -- Comandos ----------------------------------------------------------------
+
+/// Guarda un archivo pegado o arrastrado al editor en la carpeta de adjuntos
+/// (`folder`; si no, la de `.potato/config`, la de los ajustes o
+/// `attachments/`) sin pisar ninguno existente, y
+/// devuelve el Markdown para insertarlo en `note_path`.
+#[tauri::command]
+pub fn save_attachment(
+    vault_path: String,
+    note_path: Option<String>,
+    file_name: String,
+    data: Vec<u8>,
+    folder: Option<String>,
+) -> Result<SavedAttachment, AppError> {
+    let vault = PathBuf::from(&vault_path);
+    let note_dir = note_path
+        .as_deref()
+        .and_then(|p| Path::new(p).parent())
+        .unwrap_or(&vault)
+        .to_path_buf();
+
+    let folder = folder
+        .or_else(|| vault_config::attachments_folder(&vault))
+        .filter(|f| !f.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_FOLDER.to_string());
+    let dir = match folder.trim().strip_prefix("./") {
+        Some(sub) => note_dir.join(sub),
+        None if folder.trim() == "." => note_dir.clone(),
+        None => vault.join(folder.trim().trim_matches('/')),
+    };
+    let dir = normalize(&dir);
+    if !dir.starts_with(&vault) {
+        return Err(AppError::new(ErrorKind::InvalidInput, "attachment.outside").param("path", &folder));
+    }
+
+    let name = sanitize_name(&file_name);
+    if name.is_empty() {
+        return Err(AppError::invalid("file.invalid_name", &file_name));
+    }
+
+    fs::create_dir_all(&dir)?;
+    let path = unique_path(&dir, &name);
+    atomic_write(&path, &data)?;
+
+    let saved_name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let link = relative_to(&note_dir, &path);
+    Ok(SavedAttachment {
+        embed: embed_for(&link, &saved_name, attachment_kind(&path)),
+        rel: vault_relative(&vault, &path),
+        path: path.to_string_lossy().to_string(),
+    })
+}
+
+/// Adjuntos del vault que ninguna nota referencia, ni con link Markdown, ni
+/// con HTML (`<img src>`), ni con wikilink.
+#[tauri::command]
+pub async fn find_unused_attachments(vault_path: String) -> Result<Vec<UnusedAttachment>, AppError> {
+    tokio::task::spawn_blocking(move || {
+        let vault = PathBuf::from(&vault_path);
+        if !vault.is_dir() {
+            return Err(AppError::not_a_dir(&vault_path));
+        }
+        let refs = collect_references(&vault);
+
+        let mut unused: Vec<UnusedAttachment> = vault_ignore::walk(&vault)
+            .filter(|e| e.file_type().is_file())
+            .filter_map(|e| {
+                let kind = attachment_kind(e.path())?;
+                if refs.contains(&vault, e.path()) {
+                    return None;
+                }
+                Some(UnusedAttachment {
+                    path: e.path().to_string_lossy().to_string(),
+                    rel: vault_relative(&vault, e.path()),
+                    kind: kind.to_string(),
+                    size: e.metadata().map(|m| m.len()).unwrap_or(0),
+                })
+            })
+            .collect();
+        unused.sort_by(|a, b| a.rel.cmp(&b.rel));
+        Ok(unused)
+    })
+    .await?
+}
@@ -0,0 +1,202 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+use tauri::Emitter;
+
+use crate::errors::{AppError, ErrorKind};
+use crate::{changed_files, commit_message, conflicts, git_backend, git_cmd, pull_rebase, push_head, PullOutcome};
+
+const DEFAULT_INTERVAL_SECS: u64 = 300;
+const MIN_INTERVAL_SECS: u64 = 30;
+/// Tope del backoff tras errores seguidos (p. ej. sin credenciales).
+const MAX_BACKOFF_SECS: u64 = 3600;
+
+#[derive(Serialize, Clone)]
+pub struct AutoSyncEvent {
+    vault: String,
+    state: String, // "syncing", "synced", "paused", "auth_error", "error", "stopped"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>, // mensaje del commit creado, o el error
+    next_run_secs: u64,
+}
+
+// -- Registry ----------------------------------------------------------------
+
+/// Scheduler activo de un vault: enviar por `wake` lo despierta para
+/// sincronizar ya; soltarlo lo detiene.
+struct Scheduler {
+    wake: Sender<()>,
+    id: u64,
+}
+
+static AUTO_SYNC: Mutex<Option<HashMap<String, Scheduler>>> = Mutex::new(None);
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+fn auto_sync<F, R>(f: F) -> R
+where
+    F: FnOnce(&mut HashMap<String, Scheduler>) -> R,
+{
+    let mut guard = AUTO_SYNC.lock().unwrap();
+    let map = guard.get_or_insert_with(HashMap::new);
+    f(map)
+}
+
+/// Un lock por vault para las operaciones que tocan el remoto: el ciclo
+/// automatico y los pull/commit/push manuales nunca se pisan.
+static GIT_LOCKS: Mutex<Option<HashMap<String, Arc<Mutex<()>>>>> = Mutex::new(None);
+
+/// Ejecuta `f` con el lock de git del vault, esperando si hay otra
+/// sincronizacion en curso.
+pub fn exclusive<F, R>(path: &str, f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    let lock = {
+        let mut guard = GIT_LOCKS.lock().unwrap();
+        guard.get_or_insert_with(HashMap::new).entry(path.to_string()).or_default().clone()
+    };
+    // Si una sincronizacion anterior hizo panic el repo sigue ahi: seguir
+    let _held = lock.lock().unwrap_or_else(PoisonError::into_inner);
+    f()
+}
+
+// -- Ciclo -------------------------------------------------------------------
+
+fn git_ok(path: &str, args: &[&str]) -> Result<String, AppError> {
+    let output = git_cmd(path)
+        .args(args)
+        .output()
+        .map_err(|e| AppError::spawn("git", e))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(AppError::git(String::from_utf8_lossy(&output.stderr).trim()))
+    }
+}
+
+/// Commitea lo guardado en disco, trae lo remoto con rebase y sube.
+/// Devuelve el mensaje del commit si se creo uno. Los errores son los mismos
+/// que los de `git_pull`/`git_push`.
+fn sync_once(path: &str) -> Result<Option<String>, AppError> {
+    if conflicts::operation_in_progress(path).is_some() {
+        return Err(AppError::new(ErrorKind::Conflict, "git.conflict"));
+    }
+    if git_ok(path, &["remote", "get-url", "origin"]).is_err() {
+        return Err(AppError::new(ErrorKind::Git, "git.no_origin"));
+    }
+
+    let changes = changed_files(path)?;
+    let mut committed = None;
+    if !changes.is_empty() {
+        let message = commit_message::generate(Path::new(path), &changes);
+        let git = git_backend::backend();
+        git.stage_all(path)?;
+        git.commit(path, &message)?;
+        committed = Some(message);
+    }
+
+    let pulled = pull_rebase(path)?;
+    // Sin upstream (rama nueva) rev-list falla: hay que subirla igual
+    let ahead = git_ok(path, &["rev-list", "--count", "@{upstream}..HEAD"])
+        .map(|n| n.parse::<u32>().unwrap_or(0))
+        .unwrap_or(1);
+    if ahead > 0 || pulled == PullOutcome::NoRemoteBranch {
+        push_head(path)?;
+    }
+    Ok(committed)
+}
+
+/// Espera hasta el siguiente ciclo: el intervalo, duplicado por cada error
+/// seguido hasta `MAX_BACKOFF_SECS`.
+fn next_delay(interval: u64, failures: u32) -> u64 {
+    interval
+        .saturating_mul(1 << failures.min(6))
+        .min(MAX_BACKOFF_SECS.max(interval))
+}
+
+fn run(app: tauri::AppHandle, vault: String, interval: u64, id: u64, wake: mpsc::Receiver<()>) {
+    let emit = |state: &str, message: Option<String>, next_run_secs: u64| {
+        let _ = app.emit("auto-sync", AutoSyncEvent {
+            vault: vault.clone(),
+            state: state.to_string(),
+            message,
+            next_run_secs,
+        });
+    };
+
+    let mut failures = 0;
+    loop {
+        emit("syncing", None, 0);
+        let (state, message) = match exclusive(&vault, || sync_once(&vault)) {
+            Ok(committed) => {
+                failures = 0;
+                ("synced", committed)
+            }
+            // Con un rebase o merge a medio resolver no se toca nada hasta
+            // que el usuario lo termine; se vuelve a mirar en el proximo ciclo
+            Err(e) if e.kind == ErrorKind::Conflict => ("paused", None),
+            Err(e) if e.kind == ErrorKind::Auth => {
+                failures += 1;
+                ("auth_error", None)
+            }
+            Err(e) => {
+                failures += 1;
+                ("error", Some(e.to_string()))
+            }
+        };
+
+        let delay = next_delay(interval, failures);
+        emit(state, message, delay);
+        match wake.recv_timeout(Duration::from_secs(delay)) {
+            Ok(()) | Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    // Si lo reemplazo otro scheduler, el vault sigue sincronizandose
+    let replaced = auto_sync(|map| map.get(&vault).is_some_and(|s| s.id != id));
+    if !replaced {
+        emit("stopped", None, 0);
+    }
+}
+
+// -- Comandos ----------------------------------------------------------------
+
+/// Empieza a sincronizar el vault en segundo plano cada `interval_secs`
+/// (5 minutos por defecto). Llamarlo de nuevo reinicia el scheduler.
+#[tauri::command]
+pub fn start_auto_sync(app: tauri::AppHandle, vault_path: String, interval_secs: Option<u64>) -> Result<(), AppError> {
+    if !Path::new(&vault_path).is_dir() {
+        return Err(AppError::not_a_dir(&vault_path));
+    }
+    let interval = interval_secs.unwrap_or(DEFAULT_INTERVAL_SECS).max(MIN_INTERVAL_SECS);
+
+    let (tx, rx) = mpsc::channel();
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    // Al reemplazarlo, el scheduler anterior termina al soltar su Sender; si
+    // estaba sincronizando, el nuevo espera al lock del vault
+    auto_sync(|map| {
+        map.insert(vault_path.clone(), Scheduler { wake: tx, id });
+    });
+    std::thread::spawn(move || run(app, vault_path, interval, id, rx));
+    Ok(())
+}
+
+#[tauri::command]
+pub fn stop_auto_sync(vault_path: String) {
+    auto_sync(|map| {
+        map.remove(&vault_path);
+    });
+}
+
+/// Adelanta el proximo ciclo del scheduler, si esta activo.
+#[tauri::command]
+pub fn auto_sync_now(vault_path: String) -> Result<(), AppError> {
+    auto_sync(|map| match map.get(&vault_path) {
+        Some(scheduler) => scheduler.wake.send(()).map_err(|_| AppError::new(ErrorKind::NotFound, "autosync.inactive")),
+        None => Err(AppError::new(ErrorKind::NotFound, "autosync.inactive")),
+    })
+}
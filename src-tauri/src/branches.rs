@@ -0,0 +1,329 @@
+use serde::Serialize;
+use std::path::Path;
+
+use crate::errors::{AppError, ErrorKind};
+use crate::git_backend::GitError;
+use crate::{autosync, changed_files, conflicts, git_cmd, index_vault_reset, vault_ignore};
+
+const FIELD_SEP: char = '\x1f';
+
+#[derive(Serialize, Clone)]
+pub struct BranchInfo {
+    name: String, // "main" o "origin/main"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    remote: Option<String>, // remoto de las ramas remotas
+    current: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    upstream: Option<String>,
+    ahead: u32,
+    behind: u32,
+    gone: bool, // el upstream ya no existe en el remoto
+    date: String, // ultimo commit, ISO 8601
+    subject: String,
+}
+
+/// Resultado de `git_merge_branch`.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeOutcome {
+    UpToDate,
+    FastForward,
+    /// Se creo un commit de merge.
+    Merged,
+}
+
+fn git_ok(path: &str, args: &[&str]) -> Result<String, AppError> {
+    let output = git_cmd(path)
+        .env("GIT_EDITOR", "true")
+        .args(args)
+        .output()
+        .map_err(|e| AppError::spawn("git", e))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(AppError::git(String::from_utf8_lossy(&output.stderr).trim()))
+    }
+}
+
+/// Rechaza nombres que git no acepta como rama o que tomaria como opcion.
+fn check_branch_name(path: &str, name: &str) -> Result<(), AppError> {
+    if name.is_empty() || name.starts_with('-') || git_ok(path, &["check-ref-format", "--branch", name]).is_err() {
+        return Err(AppError::invalid("git.invalid_branch", name));
+    }
+    Ok(())
+}
+
+fn current_branch(path: &str) -> Option<String> {
+    git_ok(path, &["symbolic-ref", "--quiet", "--short", "HEAD"]).ok()
+}
+
+/// Cambiar de rama o mezclar con cambios sin commitear los arrastraria (o
+/// los perderia en un conflicto): hay que sincronizarlos antes.
+fn ensure_clean(path: &str) -> Result<(), AppError> {
+    if conflicts::operation_in_progress(path).is_some() {
+        return Err(AppError::new(ErrorKind::Conflict, "git.conflict"));
+    }
+    if !changed_files(path)?.is_empty() {
+        return Err(AppError::new(ErrorKind::Dirty, "git.dirty"));
+    }
+    Ok(())
+}
+
+/// Tras cambiar los archivos del working tree, los indices del vault ya no
+/// corresponden: se recargan en el siguiente uso.
+fn tree_changed(path: &str) {
+    let root = Path::new(path);
+    vault_ignore::reload(root, None);
+    index_vault_reset(root);
+}
+
+/// "ahead 2, behind 1", "ahead 2", "gone" o vacio.
+fn parse_track(track: &str) -> (u32, u32, bool) {
+    let mut ahead = 0;
+    let mut behind = 0;
+    for part in track.split(", ") {
+        match part.split_once(' ') {
+            Some(("ahead", n)) => ahead = n.parse().unwrap_or(0),
+            Some(("behind", n)) => behind = n.parse().unwrap_or(0),
+            _ => {}
+        }
+    }
+    (ahead, behind, track == "gone")
+}
+
+fn list_branches(path: &str) -> Result<Vec<BranchInfo>, AppError> {
+    let format = [
+        "%(refname)",
+        "%(refname:lstrip=2)",
+        "%(HEAD)",
+        "%(upstream:short)",
+        "%(upstream:track,nobracket)",
+        "%(symref)",
+        "%(committerdate:iso-strict)",
+        "%(contents:subject)",
+    ]
+    .join(&FIELD_SEP.to_string());
+    let stdout = git_ok(
+        path,
+        &["for-each-ref", &format!("--format={}", format), "refs/heads", "refs/remotes"],
+    )?;
+
+    // Los nombres de remoto pueden llevar `/`: gana el mas largo que encaje
+    let mut remotes: Vec<String> = git_ok(path, &["remote"])?.lines().map(str::to_string).collect();
+    remotes.sort_by_key(|r| std::cmp::Reverse(r.len()));
+
+    let branches = stdout
+        .lines()
+        .filter_map(|line| {
+            let f: Vec<&str> = line.splitn(8, FIELD_SEP).collect();
+            if f.len() < 8 || !f[5].is_empty() {
+                // origin/HEAD es solo un alias de la rama por defecto
+                return None;
+            }
+            let remote = match f[0].strip_prefix("refs/remotes/") {
+                Some(short) => Some(
+                    remotes
+                        .iter()
+                        .find(|r| short.strip_prefix(r.as_str()).is_some_and(|b| b.starts_with('/')))?
+                        .clone(),
+                ),
+                None => None,
+            };
+            let (ahead, behind, gone) = parse_track(f[4]);
+            Some(BranchInfo {
+                name: f[1].to_string(),
+                remote,
+                current: f[2] == "*",
+                upstream: Some(f[3].to_string()).filter(|u| !u.is_empty()),
+                ahead,
+                behind,
+                gone,
+                date: f[6].to_string(),
+                subject: f[7].to_string(),
+            })
+        })
+        .collect();
+    Ok(branches)
+}
+
+// -- Comandos ----------------------------------------------------------------
+
+/// Ramas locales y remotas del vault; la actual marcada con `current`.
+#[tauri::command]
+pub async fn git_branches(vault_path: String) -> Result<Vec<BranchInfo>, AppError> {
+    tokio::task::spawn_blocking(move || list_branches(&vault_path))
+        .await?
+}
+
+/// Crea la rama `name` desde `start_point` (HEAD si no se da) y, con
+/// `checkout`, se cambia a ella.
+#[tauri::command]
+pub async fn git_create_branch(
+    vault_path: String,
+    name: String,
+    start_point: Option<String>,
+    checkout: Option<bool>,
+) -> Result<(), AppError> {
+    tokio::task::spawn_blocking(move || {
+        autosync::exclusive(&vault_path, || {
+            check_branch_name(&vault_path, &name)?;
+            let start = start_point.as_deref().filter(|s| !s.is_empty());
+            if start.is_some_and(|s| s.starts_with('-')) {
+                return Err(AppError::invalid("git.invalid_rev", start.unwrap_or_default()));
+            }
+
+            if !checkout.unwrap_or(false) {
+                let mut args = vec!["branch", "--", &name];
+                args.extend(start);
+                return git_ok(&vault_path, &args).map(|_| ());
+            }
+
+            // Desde HEAD los cambios sin commitear pasan tal cual a la rama
+            // nueva; desde otro punto se cambiarian los archivos
+            if start.is_some() {
+                ensure_clean(&vault_path)?;
+            }
+            let mut args = vec!["switch", "-c", &name];
+            args.extend(start);
+            git_ok(&vault_path, &args)?;
+            if start.is_some() {
+                tree_changed(&vault_path);
+            }
+            Ok(())
+        })
+    })
+    .await?
+}
+
+/// Cambia a la rama `name`. Una rama remota (`origin/x`) sin rama local se
+/// crea siguiendola. Falla con `ErrorKind::Dirty` si hay cambios sin
+/// commitear y con `ErrorKind::Conflict` si hay un rebase o merge a medio
+/// resolver.
+#[tauri::command]
+pub async fn git_switch_branch(vault_path: String, name: String) -> Result<String, AppError> {
+    tokio::task::spawn_blocking(move || {
+        autosync::exclusive(&vault_path, || {
+            if name.starts_with('-') {
+                return Err(AppError::invalid("git.invalid_branch", &name));
+            }
+            ensure_clean(&vault_path)?;
+
+            let branches = list_branches(&vault_path)?;
+            let Some(branch) = branches.iter().find(|b| b.name == name) else {
+                return Err(AppError::new(ErrorKind::NotFound, "git.branch_not_found").param("name", &name));
+            };
+            if branch.current {
+                return Ok(name);
+            }
+
+            match &branch.remote {
+                Some(remote) => {
+                    let local = name[remote.len() + 1..].to_string();
+                    if branches.iter().any(|b| b.remote.is_none() && b.name == local) {
+                        git_ok(&vault_path, &["switch", &local])?;
+                    } else {
+                        git_ok(&vault_path, &["switch", "--track", &name])?;
+                    }
+                    tree_changed(&vault_path);
+                    Ok(local)
+                }
+                None => {
+                    git_ok(&vault_path, &["switch", &name])?;
+                    tree_changed(&vault_path);
+                    Ok(name)
+                }
+            }
+        })
+    })
+    .await?
+}
+
+/// Borra la rama local `name`. Si tiene commits que no estan en la rama
+/// actual falla con `ErrorKind::NotMerged` salvo con `force`. Con `remote` tambien se
+/// borra la rama que sigue en su remoto.
+#[tauri::command]
+pub async fn git_delete_branch(
+    vault_path: String,
+    name: String,
+    force: Option<bool>,
+    remote: Option<bool>,
+) -> Result<(), AppError> {
+    tokio::task::spawn_blocking(move || {
+        autosync::exclusive(&vault_path, || {
+            check_branch_name(&vault_path, &name)?;
+            if current_branch(&vault_path).as_deref() == Some(name.as_str()) {
+                return Err(AppError::new(ErrorKind::InvalidInput, "git.delete_current"));
+            }
+
+            let local_ref = format!("refs/heads/{}", name);
+            if git_ok(&vault_path, &["show-ref", "--verify", "--quiet", &local_ref]).is_err() {
+                return Err(AppError::new(ErrorKind::NotFound, "git.branch_not_found").param("name", &name));
+            }
+
+            // El upstream se lee antes: al borrar la rama se borra su config
+            let upstream = if remote.unwrap_or(false) {
+                let remote = git_ok(&vault_path, &["config", &format!("branch.{}.remote", name)]);
+                let merge = git_ok(&vault_path, &["config", &format!("branch.{}.merge", name)]);
+                match (remote, merge) {
+                    (Ok(remote), Ok(merge)) if remote != "." => Some((remote, merge)),
+                    _ => return Err(GitError::NoUpstream.into()),
+                }
+            } else {
+                None
+            };
+
+            let flag = if force.unwrap_or(false) { "-D" } else { "-d" };
+            if let Err(e) = git_ok(&vault_path, &["branch", flag, "--", &name]) {
+                let merged = git_ok(&vault_path, &["merge-base", "--is-ancestor", &local_ref, "HEAD"]).is_ok();
+                return Err(if merged {
+                    e
+                } else {
+                    AppError::new(ErrorKind::NotMerged, "git.not_merged").param("name", &name)
+                });
+            }
+
+            if let Some((remote, merge)) = upstream {
+                git_ok(&vault_path, &["push", &remote, "--delete", &merge])
+                    .map_err(|e| AppError::from(GitError::from_cli(e.detail.as_deref().unwrap_or_default())))?;
+            }
+            Ok(())
+        })
+    })
+    .await?
+}
+
+/// Mezcla la rama `name` (local o remota) en la actual. Si se detiene por
+/// conflictos falla con `ErrorKind::Conflict` y se resuelven con
+/// `conflicts::*`.
+#[tauri::command]
+pub async fn git_merge_branch(vault_path: String, name: String) -> Result<MergeOutcome, AppError> {
+    tokio::task::spawn_blocking(move || {
+        autosync::exclusive(&vault_path, || {
+            if name.is_empty() || name.starts_with('-') {
+                return Err(AppError::invalid("git.invalid_branch", &name));
+            }
+            ensure_clean(&vault_path)?;
+
+            let before = git_ok(&vault_path, &["rev-parse", "HEAD"])?;
+            let result = git_ok(&vault_path, &["merge", "--no-edit", &name]);
+            let after = git_ok(&vault_path, &["rev-parse", "HEAD"])?;
+            if after != before || conflicts::operation_in_progress(&vault_path).is_some() {
+                tree_changed(&vault_path);
+            }
+
+            match result {
+                Err(_) if !conflicts::conflicted_paths(&vault_path).is_empty() => {
+                    Err(AppError::new(ErrorKind::Conflict, "git.conflict"))
+                }
+                Err(e) => Err(e),
+                Ok(_) if after == before => Ok(MergeOutcome::UpToDate),
+                // Un commit de merge tiene segundo padre; un fast-forward no
+                Ok(_) if git_ok(&vault_path, &["rev-parse", "--verify", "--quiet", "HEAD^2"]).is_ok() => {
+                    Ok(MergeOutcome::Merged)
+                }
+                Ok(_) => Ok(MergeOutcome::FastForward),
+            }
+        })
+    })
+    .await?
+}
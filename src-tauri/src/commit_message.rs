@@ -0,0 +1,184 @@
+use chrono::Local;
+use regex::Regex;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::errors::AppError;
+use crate::templates::format_date;
+use crate::{changed_files, frontmatter, GitFileChange};
+
+/// Plantilla del mensaje por vault, p. ej. `docs: {{summary}}` para usar
+/// conventional commits. Variables: `{{summary}}`, `{{added}}`,
+/// `{{updated}}`, `{{removed}}`, `{{renamed}}`, `{{count}}` y
+/// `{{date:FORMATO}}`.
+pub const TEMPLATE_FILE: &str = ".potato/commit-template";
+const DEFAULT_TEMPLATE: &str = "[DOC] {{summary}}";
+
+/// Notas que se nombran por grupo antes de resumir con "and N more".
+const MAX_TOPICS: usize = 5;
+
+/// Titulo de una nota: `title`/`titulo` del frontmatter o el primer
+/// encabezado. None si no tiene ninguno.
+fn note_title(content: &str) -> Option<String> {
+    let from_frontmatter = frontmatter::parse(content).and_then(|fm| {
+        fm.as_object()?
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case("title") || k.eq_ignore_ascii_case("titulo"))
+            .map(|(_, v)| frontmatter::value_text(v).trim().to_string())
+            .filter(|t| !t.is_empty())
+    });
+    from_frontmatter.or_else(|| {
+        let (_, body) = frontmatter::split(content);
+        body.lines().find_map(|l| {
+            let hashes = l.len() - l.trim_start_matches('#').len();
+            let text = l[hashes..].strip_prefix(' ')?.trim();
+            ((1..=6).contains(&hashes) && !text.is_empty()).then(|| text.to_string())
+        })
+    })
+}
+
+fn file_stem(path: &str) -> &str {
+    // Las carpetas sin seguimiento vienen como "carpeta/"
+    let path = path.trim_end_matches('/');
+    let name = path.rsplit('/').next().unwrap_or(path);
+    name.strip_suffix(".md").unwrap_or(name)
+}
+
+/// Como se nombra la nota en el mensaje: su titulo o, si no tiene (o ya no
+/// existe), el nombre del archivo. Primera letra en minuscula para que se
+/// lea de corrido.
+fn note_name(vault: &Path, path: &str) -> String {
+    let name = fs::read_to_string(vault.join(path))
+        .ok()
+        .and_then(|content| note_title(&content))
+        .unwrap_or_else(|| file_stem(path).to_string());
+
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => name,
+    }
+}
+
+#[derive(Default)]
+struct Groups {
+    added: Vec<String>,
+    updated: Vec<String>,
+    removed: Vec<String>,
+    renamed: Vec<String>,
+}
+
+impl Groups {
+    fn from_changes(vault: &Path, files: &[GitFileChange]) -> Self {
+        let mut groups = Groups::default();
+        for file in files {
+            match file.status_code.as_str() {
+                "?" | "A" => groups.added.push(note_name(vault, &file.path)),
+                "D" => groups.removed.push(note_name(vault, &file.path)),
+                "R" => {
+                    // `git status --porcelain` da los renombres como "vieja -> nueva"
+                    let (from, to) = file.path.split_once(" -> ").unwrap_or(("", &file.path));
+                    let to_name = note_name(vault, to);
+                    groups.renamed.push(match file_stem(from) {
+                        "" => to_name,
+                        from => format!("{} -> {}", from, to_name),
+                    });
+                }
+                _ => groups.updated.push(note_name(vault, &file.path)),
+            }
+        }
+        groups
+    }
+
+    fn count(&self) -> usize {
+        self.added.len() + self.updated.len() + self.removed.len() + self.renamed.len()
+    }
+
+    /// `add x; update y, z; remove w; rename a -> b`.
+    fn summary(&self) -> String {
+        let parts: Vec<String> = [
+            ("add", &self.added),
+            ("update", &self.updated),
+            ("remove", &self.removed),
+            ("rename", &self.renamed),
+        ]
+        .into_iter()
+        .filter(|(_, names)| !names.is_empty())
+        .map(|(verb, names)| format!("{} {}", verb, list(names)))
+        .collect();
+
+        if parts.is_empty() {
+            "vault sync".to_string()
+        } else {
+            parts.join("; ")
+        }
+    }
+}
+
+fn list(names: &[String]) -> String {
+    if names.len() <= MAX_TOPICS {
+        return names.join(", ");
+    }
+    format!("{} and {} more", names[..MAX_TOPICS].join(", "), names.len() - MAX_TOPICS)
+}
+
+fn variable_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\{\{\s*([a-z]+)(?::([^}]*))?\s*\}\}").unwrap())
+}
+
+fn render(template: &str, groups: &Groups) -> String {
+    let now = Local::now().naive_local();
+    let rendered = variable_regex().replace_all(template, |caps: &regex::Captures| {
+        match &caps[1] {
+            "summary" => groups.summary(),
+            "added" => list(&groups.added),
+            "updated" => list(&groups.updated),
+            "removed" => list(&groups.removed),
+            "renamed" => list(&groups.renamed),
+            "count" => groups.count().to_string(),
+            "date" => format_date(&now, caps.get(2).map_or("YYYY-MM-DD", |m| m.as_str().trim())),
+            // Variable desconocida: se deja tal cual
+            _ => caps[0].to_string(),
+        }
+    });
+    let rendered = rendered.trim();
+    if !rendered.is_empty() {
+        rendered.to_string()
+    } else if template != DEFAULT_TEMPLATE {
+        // Una plantilla que queda vacia (solo espacios o variables vacias)
+        // no sirve como mensaje: se usa la de por defecto
+        render(DEFAULT_TEMPLATE, groups)
+    } else {
+        groups.summary()
+    }
+}
+
+/// Mensaje de commit para los archivos cambiados con la plantilla del vault
+/// (`[DOC] {{summary}}` si no tiene).
+pub fn generate(vault: &Path, files: &[GitFileChange]) -> String {
+    let template = fs::read_to_string(vault.join(TEMPLATE_FILE))
+        .ok()
+        .filter(|t| !t.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_TEMPLATE.to_string());
+    render(&template, &Groups::from_changes(vault, files))
+}
+
+/// Mensaje para los cambios actuales del repo; con `files` solo para esas
+/// rutas (las que se van a commitear).
+pub fn for_vault(vault_path: &str, files: Option<&[String]>) -> Result<String, AppError> {
+    let mut changes = changed_files(vault_path)?;
+    if let Some(files) = files {
+        changes.retain(|c| files.contains(&c.path));
+    }
+    Ok(generate(Path::new(vault_path), &changes))
+}
+
+// -- Comandos ----------------------------------------------------------------
+
+#[tauri::command]
+pub async fn git_commit_message(vault_path: String, files: Option<Vec<String>>) -> Result<String, AppError> {
+    tokio::task::spawn_blocking(move || for_vault(&vault_path, files.as_deref()))
+        .await?
+}
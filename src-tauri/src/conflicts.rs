@@ -0,0 +1,397 @@
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+use crate::errors::{AppError, ErrorKind};
+use crate::{atomic_write, autosync, git_cmd, git_dir, index_path_removed, index_path_updated};
+
+#[derive(Serialize, Clone)]
+pub struct ConflictFile {
+    path: String, // relativa al vault
+    // Contenido de cada etapa; None si el archivo no existe en ella
+    // (borrado de un lado y editado del otro). En un rebase `ours` es la
+    // rama remota y `theirs` el commit local que se esta aplicando.
+    base: Option<String>,
+    ours: Option<String>,
+    theirs: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    merged: Option<String>, // resultado del auto-merge, si no hay solapamientos
+}
+
+#[derive(Serialize, Clone)]
+pub struct ConflictState {
+    operation: String, // "rebase", "merge" o "none"
+    files: Vec<ConflictFile>,
+}
+
+/// Rebase o merge a medio hacer en el repo, segun los archivos de control
+/// que git deja en su directorio.
+pub fn operation_in_progress(repo_path: &str) -> Option<&'static str> {
+    let git_dir = git_dir(repo_path)?;
+    if git_dir.join("rebase-merge").is_dir() || git_dir.join("rebase-apply").is_dir() {
+        Some("rebase")
+    } else if git_dir.join("MERGE_HEAD").is_file() {
+        Some("merge")
+    } else {
+        None
+    }
+}
+
+/// Archivos sin resolver (con entradas en varias etapas del index).
+pub fn conflicted_paths(repo_path: &str) -> Vec<String> {
+    git_cmd(repo_path)
+        .args(["-c", "core.quotePath=false", "diff", "--name-only", "--diff-filter=U", "-z"])
+        .output()
+        .map(|o| {
+            String::from_utf8_lossy(&o.stdout)
+                .split('\0')
+                .filter(|p| !p.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Contenido de `path` en la etapa `n` del index (1 base, 2 ours, 3 theirs).
+fn stage(repo_path: &str, n: u8, path: &str) -> Option<String> {
+    let output = git_cmd(repo_path)
+        .args(["show", &format!(":{}:{}", n, path)])
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+pub fn conflict_state(repo_path: &str) -> ConflictState {
+    let Some(operation) = operation_in_progress(repo_path) else {
+        return ConflictState {
+            operation: "none".to_string(),
+            files: vec![],
+        };
+    };
+    let files = conflicted_paths(repo_path)
+        .into_iter()
+        .map(|path| {
+            let base = stage(repo_path, 1, &path);
+            let ours = stage(repo_path, 2, &path);
+            let theirs = stage(repo_path, 3, &path);
+            let merged = match (&base, &ours, &theirs) {
+                (Some(b), Some(o), Some(t)) => merge_markdown(b, o, t),
+                _ => None,
+            };
+            ConflictFile { path, base, ours, theirs, merged }
+        })
+        .collect();
+    ConflictState {
+        operation: operation.to_string(),
+        files,
+    }
+}
+
+// -- Auto-merge --------------------------------------------------------------
+
+/// Cambio de un lado respecto a la base: reemplaza las lineas
+/// `base[start..end]` por `lines` (si `start == end` es una insercion).
+#[derive(PartialEq)]
+struct Hunk<'a> {
+    start: usize,
+    end: usize,
+    lines: Vec<&'a str>,
+}
+
+fn hunks<'a>(base: &[&'a str], other: &[&'a str]) -> Vec<Hunk<'a>> {
+    let mut hunks = Vec::new();
+    let mut current: Option<Hunk> = None;
+    let mut pos = 0;
+    for change in diff::slice(base, other) {
+        match change {
+            diff::Result::Both(..) => {
+                hunks.extend(current.take());
+                pos += 1;
+            }
+            diff::Result::Left(_) => {
+                current.get_or_insert(Hunk { start: pos, end: pos, lines: vec![] }).end = pos + 1;
+                pos += 1;
+            }
+            diff::Result::Right(line) => {
+                current
+                    .get_or_insert(Hunk { start: pos, end: pos, lines: vec![] })
+                    .lines
+                    .push(line);
+            }
+        }
+    }
+    hunks.extend(current);
+    hunks
+}
+
+fn is_fence(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with("```") || line.starts_with("~~~")
+}
+
+/// True si insertar antes de `base[at]` cae dentro de un bloque de codigo.
+fn inside_fence(base: &[&str], at: usize) -> bool {
+    base[..at].iter().filter(|l| is_fence(l)).count() % 2 == 1
+}
+
+/// Linea de cierre del frontmatter de la base, si lo tiene.
+fn frontmatter_end(base: &[&str]) -> Option<usize> {
+    if base.first()?.trim_end() != "---" {
+        return None;
+    }
+    base.iter().skip(1).position(|l| l.trim_end() == "---").map(|i| i + 1)
+}
+
+fn yaml_keys<'a>(lines: &[&'a str]) -> Vec<&'a str> {
+    lines
+        .iter()
+        .filter(|l| !l.starts_with(&[' ', '\t', '-', '#'][..]))
+        .filter_map(|l| l.split_once(':').map(|(k, _)| k.trim()))
+        .collect()
+}
+
+fn is_block_item(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with("- ")
+        || line.starts_with("* ")
+        || line.starts_with("+ ")
+        || line.starts_with('|')
+        || line.starts_with('>')
+        || line.split_once(". ").is_some_and(|(n, _)| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Ambos lados insertaron texto en el mismo punto: se conservan los dos
+/// (primero `ours`) salvo que sea dentro de un bloque de codigo o que ambos
+/// agreguen la misma clave al frontmatter. Si ambos agregan parrafos nuevos
+/// se deja una linea en blanco entre ellos para que no se fundan en uno.
+fn union<'a>(base: &[&str], at: usize, ours: &[&'a str], theirs: &[&'a str]) -> Option<Vec<&'a str>> {
+    if theirs.starts_with(ours) {
+        return Some(theirs.to_vec());
+    }
+    if ours.starts_with(theirs) {
+        return Some(ours.to_vec());
+    }
+    if inside_fence(base, at) {
+        return None;
+    }
+    if frontmatter_end(base).is_some_and(|end| at > 0 && at <= end) {
+        let keys = yaml_keys(ours);
+        if yaml_keys(theirs).iter().any(|k| keys.contains(k)) {
+            return None;
+        }
+        return Some(ours.iter().chain(theirs).copied().collect());
+    }
+
+    let last = ours.last().copied().unwrap_or_default();
+    let first = theirs.first().copied().unwrap_or_default();
+    // Lineas que continuan el parrafo anterior de la base siguen juntas
+    let continues = at > 0 && !base[at - 1].trim().is_empty() && !ours[0].trim().is_empty();
+    let separated = continues
+        || last.trim().is_empty()
+        || first.trim().is_empty()
+        || (is_block_item(last) && is_block_item(first));
+
+    let mut merged = ours.to_vec();
+    if !separated {
+        merged.push("\n");
+    }
+    merged.extend_from_slice(theirs);
+    Some(merged)
+}
+
+/// Merge de tres vias por lineas. Los cambios de cada lado se aplican si no
+/// tocan las mismas lineas de la base (a diferencia de git, dos cambios
+/// contiguos no son conflicto) y las inserciones en el mismo punto se
+/// combinan con `union`. None si algun cambio se solapa.
+pub fn merge_markdown(base: &str, ours: &str, theirs: &str) -> Option<String> {
+    let base: Vec<&str> = base.split_inclusive('\n').collect();
+    let ours_lines: Vec<&str> = ours.split_inclusive('\n').collect();
+    let theirs_lines: Vec<&str> = theirs.split_inclusive('\n').collect();
+    // El ultimo renglon sin salto de linea no se compara bien con uno que si lo tiene
+    if [&ours_lines, &theirs_lines, &base].iter().any(|l| l.last().is_some_and(|x| !x.ends_with('\n'))) {
+        let fix = |s: &str| if s.ends_with('\n') || s.is_empty() { s.to_string() } else { format!("{}\n", s) };
+        return merge_markdown(&fix(&base.concat()), &fix(ours), &fix(theirs));
+    }
+
+    let ours_hunks = hunks(&base, &ours_lines);
+    let theirs_hunks = hunks(&base, &theirs_lines);
+    let (mut a, mut b) = (ours_hunks.iter().peekable(), theirs_hunks.iter().peekable());
+
+    let mut out = String::new();
+    let mut pos = 0;
+    let mut apply = |out: &mut String, start: usize, end: usize, lines: &[&str]| {
+        out.extend(base[pos..start].iter().copied());
+        out.extend(lines.iter().copied());
+        pos = end;
+    };
+
+    loop {
+        match (a.peek().copied(), b.peek().copied()) {
+            (None, None) => break,
+            (Some(x), None) => {
+                apply(&mut out, x.start, x.end, &x.lines);
+                a.next();
+            }
+            (None, Some(y)) => {
+                apply(&mut out, y.start, y.end, &y.lines);
+                b.next();
+            }
+            (Some(x), Some(y)) if x == y => {
+                apply(&mut out, x.start, x.end, &x.lines);
+                a.next();
+                b.next();
+            }
+            (Some(x), Some(y)) if x.start == x.end && y.start == y.end && x.start == y.start => {
+                let merged = union(&base, x.start, &x.lines, &y.lines)?;
+                apply(&mut out, x.start, x.end, &merged);
+                a.next();
+                b.next();
+            }
+            (Some(x), Some(y)) if x.start < y.end && y.start < x.end => return None,
+            (Some(x), Some(y)) if x.end <= y.start => {
+                apply(&mut out, x.start, x.end, &x.lines);
+                a.next();
+            }
+            (Some(_), Some(y)) => {
+                apply(&mut out, y.start, y.end, &y.lines);
+                b.next();
+            }
+        }
+    }
+    out.extend(base[pos..].iter().copied());
+    Some(out)
+}
+
+// -- Comandos ----------------------------------------------------------------
+
+fn git_run(repo_path: &str, args: &[&str]) -> Result<String, AppError> {
+    let output = git_cmd(repo_path)
+        .env("GIT_EDITOR", "true")
+        .args(args)
+        .output()
+        .map_err(|e| AppError::spawn("git", e))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(AppError::git(String::from_utf8_lossy(&output.stderr).trim()))
+    }
+}
+
+/// Marca `path` como resuelto: `content` se escribe y se agrega al index;
+/// con None el archivo se elimina (p. ej. aceptar el borrado de un lado).
+fn resolve(vault_path: &str, path: &str, content: Option<&str>) -> Result<(), AppError> {
+    let full = Path::new(vault_path).join(path);
+    match content {
+        Some(content) => {
+            if let Some(dir) = full.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            atomic_write(&full, content.as_bytes())?;
+            git_run(vault_path, &["add", "--", path])?;
+            index_path_updated(&full);
+        }
+        None => {
+            git_run(vault_path, &["rm", "--quiet", "--ignore-unmatch", "--", path])?;
+            index_path_removed(&full);
+        }
+    }
+    Ok(())
+}
+
+/// Rebase o merge en curso y sus archivos en conflicto, con el resultado
+/// del auto-merge de cada uno cuando es posible.
+#[tauri::command]
+pub async fn git_conflicts(vault_path: String) -> Result<ConflictState, AppError> {
+    tokio::task::spawn_blocking(move || Ok(conflict_state(&vault_path)))
+        .await?
+}
+
+/// Acepta el contenido resuelto de un archivo en conflicto.
+#[tauri::command]
+pub async fn git_resolve_conflict(
+    vault_path: String,
+    path: String,
+    content: Option<String>,
+) -> Result<ConflictState, AppError> {
+    tokio::task::spawn_blocking(move || {
+        autosync::exclusive(&vault_path, || {
+            if !conflicted_paths(&vault_path).contains(&path) {
+                return Err(AppError::new(ErrorKind::InvalidInput, "git.no_conflicts").param("path", &path));
+            }
+            resolve(&vault_path, &path, content.as_deref())?;
+            Ok(conflict_state(&vault_path))
+        })
+    })
+    .await?
+}
+
+/// Resuelve todos los archivos cuyo auto-merge no tiene solapamientos y
+/// devuelve los que quedan.
+#[tauri::command]
+pub async fn git_auto_merge_conflicts(vault_path: String) -> Result<ConflictState, AppError> {
+    tokio::task::spawn_blocking(move || {
+        autosync::exclusive(&vault_path, || {
+            for file in conflict_state(&vault_path).files {
+                if let Some(merged) = &file.merged {
+                    resolve(&vault_path, &file.path, Some(merged))?;
+                }
+            }
+            Ok(conflict_state(&vault_path))
+        })
+    })
+    .await?
+}
+
+/// Sigue con el rebase (o cierra el merge) una vez resueltos los conflictos.
+/// Si el rebase se detiene en otro commit con conflictos, se devuelven.
+#[tauri::command]
+pub async fn git_conflict_continue(vault_path: String) -> Result<ConflictState, AppError> {
+    tokio::task::spawn_blocking(move || {
+        autosync::exclusive(&vault_path, || {
+            let pending = conflicted_paths(&vault_path);
+            if !pending.is_empty() {
+                return Err(AppError::new(ErrorKind::Conflict, "git.unresolved").param("count", pending.len()));
+            }
+            match operation_in_progress(&vault_path) {
+                Some("rebase") => {
+                    if let Err(e) = git_run(&vault_path, &["rebase", "--continue"]) {
+                        // El commit quedo vacio tras resolver: no aporta nada, se salta
+                        let stderr = e.detail.as_deref().unwrap_or_default();
+                        if stderr.contains("No changes") || stderr.contains("nothing to commit") {
+                            git_run(&vault_path, &["rebase", "--skip"]).or_else(|e| {
+                                if conflicted_paths(&vault_path).is_empty() { Err(e) } else { Ok(String::new()) }
+                            })?;
+                        } else if conflicted_paths(&vault_path).is_empty() {
+                            return Err(e);
+                        }
+                    }
+                }
+                Some(_) => {
+                    git_run(&vault_path, &["commit", "--no-edit"])?;
+                }
+                None => return Err(AppError::new(ErrorKind::InvalidInput, "git.no_operation")),
+            }
+            Ok(conflict_state(&vault_path))
+        })
+    })
+    .await?
+}
+
+/// Cancela el rebase o merge y deja el repo como estaba antes del pull.
+#[tauri::command]
+pub async fn git_conflict_abort(vault_path: String) -> Result<(), AppError> {
+    tokio::task::spawn_blocking(move || {
+        autosync::exclusive(&vault_path, || {
+            match operation_in_progress(&vault_path) {
+                Some("rebase") => git_run(&vault_path, &["rebase", "--abort"])?,
+                Some(_) => git_run(&vault_path, &["merge", "--abort"])?,
+                None => return Err(AppError::new(ErrorKind::InvalidInput, "git.no_operation")),
+            };
+            Ok(())
+        })
+    })
+    .await?
+}
@@ -0,0 +1,139 @@
+use serde::ser::{SerializeStruct, Serializer};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::i18n;
+
+/// Tipo de error: el frontend decide que hacer segun esto (pedir
+/// credenciales, abrir los conflictos, ofrecer sincronizar...), nunca segun
+/// el texto del mensaje.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    NotFound,
+    InvalidInput,
+    AlreadyExists,
+    Stale, // el archivo cambio en disco desde que se leyo
+    Io,
+    NotARepo,
+    Auth,
+    Conflict, // rebase o merge detenido por conflictos
+    Dirty,    // hay cambios sin commitear
+    NotMerged,
+    NothingToCommit,
+    Git,
+    Claude,
+    Mcp,
+    Update,
+    Cancelled,
+    Internal,
+}
+
+/// Error de los comandos. Se serializa como
+/// `{ kind, key, params, detail, message }`: `key` y `params` permiten
+/// traducir el mensaje en el frontend y `detail` lleva la salida original
+/// (stderr de git, error del sistema) cuando la hay.
+#[derive(Debug, Clone)]
+pub struct AppError {
+    pub kind: ErrorKind,
+    pub key: &'static str,
+    pub params: BTreeMap<&'static str, String>,
+    pub detail: Option<String>,
+}
+
+impl AppError {
+    pub fn new(kind: ErrorKind, key: &'static str) -> Self {
+        AppError {
+            kind,
+            key,
+            params: BTreeMap::new(),
+            detail: None,
+        }
+    }
+
+    pub fn param(mut self, name: &'static str, value: impl fmt::Display) -> Self {
+        self.params.insert(name, value.to_string());
+        self
+    }
+
+    pub fn detail(mut self, detail: impl fmt::Display) -> Self {
+        let detail = detail.to_string();
+        self.detail = Some(detail).filter(|d| !d.trim().is_empty());
+        self
+    }
+
+    /// Mensaje listo para mostrar en el idioma actual, sin el detalle.
+    pub fn message(&self) -> String {
+        i18n::render(self.key, self.params.iter().map(|(k, v)| (*k, v.as_str())))
+    }
+
+    // Atajos para los casos mas repetidos
+
+    pub fn not_a_dir(path: impl fmt::Display) -> Self {
+        AppError::new(ErrorKind::NotFound, "vault.not_a_dir").param("path", path)
+    }
+
+    pub fn file_not_found(path: impl fmt::Display) -> Self {
+        AppError::new(ErrorKind::NotFound, "file.not_found").param("path", path)
+    }
+
+    pub fn invalid(key: &'static str, value: impl fmt::Display) -> Self {
+        AppError::new(ErrorKind::InvalidInput, key).param("value", value)
+    }
+
+    /// No se pudo lanzar un programa externo (git, claude, curl...).
+    pub fn spawn(program: &str, e: impl fmt::Display) -> Self {
+        AppError::new(ErrorKind::Io, "process.spawn").param("program", program).detail(e)
+    }
+
+    /// Un comando de git fallo; `stderr` va como detalle.
+    pub fn git(stderr: impl fmt::Display) -> Self {
+        AppError::new(ErrorKind::Git, "git.failed").detail(stderr)
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.detail {
+            Some(detail) => write!(f, "{}: {}", self.message(), detail),
+            None => write!(f, "{}", self.message()),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("AppError", 5)?;
+        s.serialize_field("kind", &self.kind)?;
+        s.serialize_field("key", self.key)?;
+        s.serialize_field("params", &self.params)?;
+        s.serialize_field("detail", &self.detail)?;
+        s.serialize_field("message", &self.message())?;
+        s.end()
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        let kind = match e.kind() {
+            std::io::ErrorKind::NotFound => ErrorKind::NotFound,
+            _ => ErrorKind::Io,
+        };
+        AppError::new(kind, "io.failed").detail(e)
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(e: serde_json::Error) -> Self {
+        AppError::new(ErrorKind::Internal, "internal.json").detail(e)
+    }
+}
+
+impl From<tokio::task::JoinError> for AppError {
+    fn from(e: tokio::task::JoinError) -> Self {
+        AppError::new(ErrorKind::Internal, "internal.task").detail(e)
+    }
+}
@@ -0,0 +1,296 @@
+use serde_json::{Map, Number, Value};
+use std::fs;
+use std::path::Path;
+use yaml_rust2::yaml::Hash;
+use yaml_rust2::{Yaml, YamlEmitter, YamlLoader};
+
+use crate::errors::{AppError, ErrorKind};
+use crate::{atomic_write, content_hash, index_path_updated, note_revision, NoteContent};
+
+/// Posicion del bloque YAML inicial (`---` ... `---`): (inicio del yaml,
+/// fin del yaml, inicio del cuerpo). None si la nota no tiene frontmatter.
+fn locate(content: &str) -> Option<(usize, usize, usize)> {
+    let start = if content.starts_with("---\r\n") {
+        5
+    } else if content.starts_with("---\n") {
+        4
+    } else {
+        return None;
+    };
+
+    let mut offset = start;
+    for line in content[start..].split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return Some((start, offset, offset + line.len()));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// Separa el bloque YAML inicial (`---` ... `---`) del cuerpo de la nota.
+/// Devuelve (yaml, cuerpo); yaml es None si la nota no tiene frontmatter.
+pub fn split(content: &str) -> (Option<&str>, &str) {
+    match locate(content) {
+        Some((start, end, body)) => (Some(&content[start..end]), &content[body..]),
+        None => (None, content),
+    }
+}
+
+// -- YAML --------------------------------------------------------------------
+
+/// Primer documento YAML de `text` como JSON. `Ok(None)` si esta vacio; error
+/// si no es YAML valido o usa algo que JSON no representa (claves que no son
+/// texto o numero).
+pub fn parse_yaml(text: &str) -> Result<Option<Value>, String> {
+    let docs = YamlLoader::load_from_str(text).map_err(|e| e.to_string())?;
+    match docs.first() {
+        None | Some(Yaml::Null) => Ok(None),
+        Some(doc) => yaml_to_json(doc).map(Some).ok_or_else(|| "valor YAML no soportado".to_string()),
+    }
+}
+
+fn yaml_to_json(yaml: &Yaml) -> Option<Value> {
+    Some(match yaml {
+        Yaml::Null => Value::Null,
+        Yaml::Boolean(b) => Value::Bool(*b),
+        Yaml::Integer(i) => Value::from(*i),
+        Yaml::Real(r) => r.parse::<f64>().ok().and_then(Number::from_f64).map_or(Value::String(r.clone()), Value::Number),
+        Yaml::String(s) => Value::String(s.clone()),
+        Yaml::Array(items) => Value::Array(items.iter().map(yaml_to_json).collect::<Option<_>>()?),
+        Yaml::Hash(hash) => {
+            let mut map = Map::new();
+            for (key, value) in hash {
+                let key = match key {
+                    Yaml::String(s) | Yaml::Real(s) => s.clone(),
+                    Yaml::Integer(i) => i.to_string(),
+                    Yaml::Boolean(b) => b.to_string(),
+                    _ => return None,
+                };
+                map.insert(key, yaml_to_json(value)?);
+            }
+            Value::Object(map)
+        }
+        Yaml::Alias(_) | Yaml::BadValue => return None,
+    })
+}
+
+fn json_to_yaml(value: &Value) -> Yaml {
+    match value {
+        Value::Null => Yaml::Null,
+        Value::Bool(b) => Yaml::Boolean(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => Yaml::Integer(i),
+            None => Yaml::Real(n.to_string()),
+        },
+        Value::String(s) => Yaml::String(s.clone()),
+        Value::Array(items) => Yaml::Array(items.iter().map(json_to_yaml).collect()),
+        Value::Object(map) => Yaml::Hash(
+            map.iter()
+                .map(|(k, v)| (Yaml::String(k.clone()), json_to_yaml(v)))
+                .collect(),
+        ),
+    }
+}
+
+/// Frontmatter como objeto JSON. None si no hay, si el YAML es invalido o
+/// si no es un mapa (`key: value`).
+pub fn parse(content: &str) -> Option<Value> {
+    let (yaml, _) = split(content);
+    let value = parse_yaml(yaml?).ok()??;
+    value.is_object().then_some(value)
+}
+
+/// Primera linea util del cuerpo (ni vacia ni titulo), para descripciones.
+pub fn first_text_line(content: &str) -> Option<String> {
+    let (_, body) = split(content);
+    body.lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(str::to_string)
+}
+
+/// Valor del frontmatter como texto plano (listas separadas por coma).
+pub fn value_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items.iter().map(value_text).collect::<Vec<_>>().join(", "),
+        other => other.to_string(),
+    }
+}
+
+// -- Edicion de propiedades --------------------------------------------------
+
+/// Clave de una linea de primer nivel del YAML (`clave: ...`), sin comillas.
+fn top_level_key(line: &str) -> Option<&str> {
+    if line.starts_with([' ', '\t', '-', '#']) {
+        return None;
+    }
+    let (key, _) = line.split_once(':')?;
+    Some(key.trim().trim_matches('"').trim_matches('\''))
+}
+
+/// Rango de bytes (dentro del yaml) que ocupa una propiedad de primer nivel,
+/// incluyendo sus lineas de continuacion (listas, mapas, textos multilinea).
+fn property_span(yaml: &str, key: &str) -> Option<(usize, usize)> {
+    let mut offset = 0;
+    let mut start = None;
+    let mut end = 0;
+
+    for line in yaml.split_inclusive('\n') {
+        let content_line = line.trim_end();
+        match start {
+            None => {
+                if top_level_key(content_line) == Some(key) {
+                    start = Some(offset);
+                    end = offset + line.len();
+                }
+            }
+            Some(_) => {
+                let continues = content_line.is_empty()
+                    || content_line.starts_with([' ', '\t'])
+                    || content_line.starts_with("- ")
+                    || content_line == "-";
+                if !continues {
+                    break;
+                }
+                // Las lineas vacias al final pertenecen a lo que sigue
+                if !content_line.is_empty() {
+                    end = offset + line.len();
+                }
+            }
+        }
+        offset += line.len();
+    }
+
+    start.map(|s| (s, end))
+}
+
+/// `clave: valor` serializado como YAML, terminado en salto de linea.
+fn render_property(key: &str, value: &Value) -> Result<String, AppError> {
+    let mut hash = Hash::new();
+    hash.insert(Yaml::String(key.to_string()), json_to_yaml(value));
+    let mut out = String::new();
+    YamlEmitter::new(&mut out)
+        .dump(&Yaml::Hash(hash))
+        .map_err(|e| AppError::new(ErrorKind::InvalidInput, "frontmatter.invalid_value").param("key", key).detail(e))?;
+    // El emisor empieza el documento con `---`
+    let body = out.strip_prefix("---").unwrap_or(&out).trim_start_matches('\n');
+    Ok(format!("{}\n", body))
+}
+
+/// Asigna una propiedad. Solo se reescriben las lineas de esa clave: el resto
+/// del YAML conserva su formato y el cuerpo no se toca.
+pub fn set_property(content: &str, key: &str, value: &Value) -> Result<String, AppError> {
+    let rendered = render_property(key, value)?;
+
+    let Some((start, end, _)) = locate(content) else {
+        return Ok(format!("---\n{}---\n{}", rendered, content));
+    };
+    if parse_yaml(&content[start..end]).is_err() {
+        return Err(AppError::new(ErrorKind::InvalidInput, "frontmatter.invalid_yaml"));
+    }
+
+    let yaml = &content[start..end];
+    let mut updated = String::with_capacity(content.len() + rendered.len());
+    updated.push_str(&content[..start]);
+    match property_span(yaml, key) {
+        Some((s, e)) => {
+            updated.push_str(&yaml[..s]);
+            updated.push_str(&rendered);
+            updated.push_str(&yaml[e..]);
+        }
+        None => {
+            updated.push_str(yaml);
+            if !yaml.is_empty() && !yaml.ends_with('\n') {
+                updated.push('\n');
+            }
+            updated.push_str(&rendered);
+        }
+    }
+    updated.push_str(&content[end..]);
+    Ok(updated)
+}
+
+/// Elimina una propiedad. Si el frontmatter queda vacio se quita el bloque.
+/// Devuelve None si la propiedad no existia.
+pub fn delete_property(content: &str, key: &str) -> Option<String> {
+    let (start, end, body) = locate(content)?;
+    let yaml = &content[start..end];
+    let (s, e) = property_span(yaml, key)?;
+
+    let remaining = format!("{}{}", &yaml[..s], &yaml[e..]);
+    if remaining.trim().is_empty() {
+        return Some(content[body..].to_string());
+    }
+    Some(format!("{}{}{}", &content[..start], remaining, &content[end..]))
+}
+
+fn check_revision(current: &str, expected: Option<&str>) -> Result<(), AppError> {
+    let Some(expected) = expected else { return Ok(()) };
+    let expected_hash = expected.split_once('-').map_or(expected, |(_, h)| h);
+    if expected_hash != content_hash(current.as_bytes()) {
+        return Err(AppError::new(ErrorKind::Stale, "frontmatter.stale"));
+    }
+    Ok(())
+}
+
+fn write_note(path: &Path, content: String) -> Result<NoteContent, AppError> {
+    atomic_write(path, content.as_bytes())?;
+    index_path_updated(path);
+    let revision = note_revision(path, content.as_bytes());
+    Ok(NoteContent { content, revision })
+}
+
+// -- Comandos ----------------------------------------------------------------
+
+/// Frontmatter de una nota como objeto (vacio si no tiene).
+#[tauri::command]
+pub fn get_frontmatter(path: String) -> Result<Value, AppError> {
+    let content = fs::read_to_string(&path)?;
+    if let (Some(yaml), _) = split(&content) {
+        if !yaml.trim().is_empty() && parse(&content).is_none() {
+            return Err(AppError::new(ErrorKind::InvalidInput, "frontmatter.invalid_yaml"));
+        }
+    }
+    Ok(parse(&content).unwrap_or_else(|| Value::Object(Map::new())))
+}
+
+/// Crea o reemplaza una propiedad del frontmatter. Devuelve la nota
+/// resultante con su nueva revision.
+#[tauri::command]
+pub fn set_frontmatter_property(
+    path: String,
+    key: String,
+    value: Value,
+    expected_revision: Option<String>,
+) -> Result<NoteContent, AppError> {
+    let key = key.trim();
+    if key.is_empty() || key.contains('\n') {
+        return Err(AppError::new(ErrorKind::InvalidInput, "frontmatter.invalid_key"));
+    }
+    let file_path = Path::new(&path);
+    let content = fs::read_to_string(file_path)?;
+    check_revision(&content, expected_revision.as_deref())?;
+
+    let updated = set_property(&content, key, &value)?;
+    write_note(file_path, updated)
+}
+
+/// Elimina una propiedad del frontmatter.
+#[tauri::command]
+pub fn delete_frontmatter_property(
+    path: String,
+    key: String,
+    expected_revision: Option<String>,
+) -> Result<NoteContent, AppError> {
+    let file_path = Path::new(&path);
+    let content = fs::read_to_string(file_path)?;
+    check_revision(&content, expected_revision.as_deref())?;
+
+    match delete_property(&content, key.trim()) {
+        Some(updated) => write_note(file_path, updated),
+        None => Err(AppError::new(ErrorKind::NotFound, "frontmatter.missing").param("key", &key)),
+    }
+}
@@ -0,0 +1,902 @@
+use chrono::{DateTime, FixedOffset};
+use git2::{
+    BranchType, Delta, DiffFindOptions, DiffFormat, DiffOptions, ErrorCode, IndexAddOption, Repository, RepositoryState, Status,
+    StatusOptions,
+};
+use std::fs;
+use std::sync::OnceLock;
+
+use crate::errors::{AppError, ErrorKind};
+use crate::{conflicts, file_change, git_cmd, git_dir, GitFileChange, GitStatus};
+
+const RECORD_SEP: char = '\x1e';
+const FIELD_SEP: char = '\x1f';
+
+/// Hooks que git ejecuta al commitear; libgit2 no los corre.
+const COMMIT_HOOKS: [&str; 4] = ["pre-commit", "prepare-commit-msg", "commit-msg", "post-commit"];
+
+// -- Errores -----------------------------------------------------------------
+
+/// Error de una operacion git, para decidir sin mirar el texto del error.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GitError {
+    NotARepo,
+    Auth,
+    Conflict,
+    NoUpstream,
+    NothingToCommit,
+    EmptyMessage,
+    /// No se pudo lanzar el proceso de git.
+    Spawn(String),
+    /// El backend en proceso no puede hacer la operacion en este repo
+    /// (hooks, firma, un merge a medio hacer...): se repite con el CLI.
+    Unsupported(String),
+    Failed(String),
+}
+
+impl GitError {
+    /// Clasifica la salida de error del CLI (que corre con `LC_ALL=C`).
+    pub fn from_cli(stderr: &str) -> Self {
+        let s = stderr.trim();
+        if s.contains("Authentication failed")
+            || s.contains("Permission denied")
+            || s.contains("terminal prompts disabled")
+            || s.contains("could not read Username")
+        {
+            GitError::Auth
+        } else if s.contains("not a git repository") {
+            GitError::NotARepo
+        } else if s.contains("nothing to commit") || s.contains("nothing added to commit") {
+            GitError::NothingToCommit
+        } else if s.contains("no tracking information") || s.contains("no such ref") {
+            GitError::NoUpstream
+        } else if s.contains("CONFLICT") {
+            GitError::Conflict
+        } else {
+            GitError::Failed(s.to_string())
+        }
+    }
+}
+
+impl From<git2::Error> for GitError {
+    fn from(e: git2::Error) -> Self {
+        match e.code() {
+            ErrorCode::Auth => GitError::Auth,
+            ErrorCode::Conflict | ErrorCode::MergeConflict => GitError::Conflict,
+            // Cualquier otra cosa se reintenta con el CLI, que da su propio error
+            _ => GitError::Unsupported(e.message().to_string()),
+        }
+    }
+}
+
+impl From<GitError> for AppError {
+    fn from(e: GitError) -> Self {
+        match e {
+            GitError::NotARepo => AppError::new(ErrorKind::NotARepo, "git.not_a_repo"),
+            GitError::Auth => AppError::new(ErrorKind::Auth, "git.auth"),
+            GitError::Conflict => AppError::new(ErrorKind::Conflict, "git.conflict"),
+            GitError::NoUpstream => AppError::new(ErrorKind::Git, "git.no_upstream"),
+            GitError::NothingToCommit => AppError::new(ErrorKind::NothingToCommit, "git.nothing_to_commit"),
+            GitError::EmptyMessage => AppError::new(ErrorKind::InvalidInput, "git.empty_message"),
+            GitError::Spawn(e) => AppError::spawn("git", e),
+            GitError::Unsupported(msg) | GitError::Failed(msg) => AppError::git(msg),
+        }
+    }
+}
+
+// -- Trait -------------------------------------------------------------------
+
+/// Un commit de `log`, con los archivos que cambio respecto a su primer
+/// padre (vacio en los merges, como `git log --name-status`).
+#[derive(Clone, Debug)]
+pub struct LogEntry {
+    pub commit: String,
+    pub short: String,
+    pub author: String,
+    pub email: String,
+    pub date: String, // ISO 8601 con zona horaria
+    pub subject: String,
+    pub files: Vec<LogFile>,
+}
+
+#[derive(Clone, Debug)]
+pub struct LogFile {
+    pub status: char,         // 'A', 'M', 'D', 'R', ...
+    pub path: String,         // la ruta nueva en los renombres
+    pub from: Option<String>, // la ruta anterior en los renombres
+}
+
+/// Operaciones git locales. Lo que toca la red (clone, pull, push) sigue
+/// yendo siempre por el CLI.
+pub trait GitBackend: Send + Sync {
+    fn status(&self, repo: &str) -> Result<GitStatus, GitError>;
+    fn changed_files(&self, repo: &str) -> Result<Vec<GitFileChange>, GitError>;
+    fn stage(&self, repo: &str, files: &[String]) -> Result<(), GitError>;
+    /// Todo, incluidos archivos nuevos y borrados (`git add -A`).
+    fn stage_all(&self, repo: &str) -> Result<(), GitError>;
+    /// Commitea el index; devuelve "[rama abc1234] asunto".
+    fn commit(&self, repo: &str, message: &str) -> Result<String, GitError>;
+    /// Commits del mas reciente al mas antiguo desde HEAD (o desde todas las
+    /// refs con `all_refs`), solo los que tocan `pathspec` si se da.
+    fn log(
+        &self,
+        repo: &str,
+        all_refs: bool,
+        pathspec: Option<&str>,
+        limit: Option<usize>,
+    ) -> Result<Vec<LogEntry>, GitError>;
+    /// Commits que tocaron `path`, con `follow` siguiendo sus renombres.
+    /// `repo` puede ser una subcarpeta del working tree: `path` y las rutas
+    /// devueltas son relativas a ella.
+    fn file_log(&self, repo: &str, path: &str, follow: bool, limit: Option<usize>)
+        -> Result<Vec<LogEntry>, GitError>;
+    /// Contenido de `path` (relativa a `repo`) en `rev`.
+    fn blob_at(&self, repo: &str, rev: &str, path: &str) -> Result<Vec<u8>, GitError>;
+    /// Unified diff del working tree contra el index, o del index contra
+    /// HEAD con `staged`; solo de `path` si se da.
+    fn diff(&self, repo: &str, path: Option<&str>, staged: bool) -> Result<String, GitError>;
+    /// Unified diff de `paths` (relativas a `repo`) entre `from` y `to`, o
+    /// el working tree si `to` es None. Cualquier parecido entre las rutas
+    /// se toma como renombre.
+    fn diff_revs(&self, repo: &str, from: &str, to: Option<&str>, paths: &[&str]) -> Result<String, GitError>;
+}
+
+/// El backend a usar: libgit2 con el CLI como respaldo, o solo el CLI con
+/// `POTATO_GIT_BACKEND=cli`.
+pub fn backend() -> &'static dyn GitBackend {
+    static BACKEND: OnceLock<Box<dyn GitBackend>> = OnceLock::new();
+    BACKEND
+        .get_or_init(|| match std::env::var("POTATO_GIT_BACKEND").as_deref() {
+            Ok("cli") => Box::new(Cli),
+            _ => Box::new(WithFallback),
+        })
+        .as_ref()
+}
+
+// -- libgit2 con respaldo ----------------------------------------------------
+
+/// libgit2 y, si devuelve `Unsupported`, el CLI.
+struct WithFallback;
+
+fn or_cli<T>(result: Result<T, GitError>, cli: impl FnOnce() -> Result<T, GitError>) -> Result<T, GitError> {
+    match result {
+        Err(GitError::Unsupported(_)) => cli(),
+        other => other,
+    }
+}
+
+impl GitBackend for WithFallback {
+    fn status(&self, repo: &str) -> Result<GitStatus, GitError> {
+        or_cli(Libgit2.status(repo), || Cli.status(repo))
+    }
+
+    fn changed_files(&self, repo: &str) -> Result<Vec<GitFileChange>, GitError> {
+        or_cli(Libgit2.changed_files(repo), || Cli.changed_files(repo))
+    }
+
+    fn stage(&self, repo: &str, files: &[String]) -> Result<(), GitError> {
+        or_cli(Libgit2.stage(repo, files), || Cli.stage(repo, files))
+    }
+
+    fn stage_all(&self, repo: &str) -> Result<(), GitError> {
+        or_cli(Libgit2.stage_all(repo), || Cli.stage_all(repo))
+    }
+
+    fn commit(&self, repo: &str, message: &str) -> Result<String, GitError> {
+        or_cli(Libgit2.commit(repo, message), || Cli.commit(repo, message))
+    }
+
+    fn log(
+        &self,
+        repo: &str,
+        all_refs: bool,
+        pathspec: Option<&str>,
+        limit: Option<usize>,
+    ) -> Result<Vec<LogEntry>, GitError> {
+        or_cli(Libgit2.log(repo, all_refs, pathspec, limit), || {
+            Cli.log(repo, all_refs, pathspec, limit)
+        })
+    }
+
+    fn file_log(
+        &self,
+        repo: &str,
+        path: &str,
+        follow: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<LogEntry>, GitError> {
+        or_cli(Libgit2.file_log(repo, path, follow, limit), || {
+            Cli.file_log(repo, path, follow, limit)
+        })
+    }
+
+    fn blob_at(&self, repo: &str, rev: &str, path: &str) -> Result<Vec<u8>, GitError> {
+        or_cli(Libgit2.blob_at(repo, rev, path), || Cli.blob_at(repo, rev, path))
+    }
+
+    fn diff(&self, repo: &str, path: Option<&str>, staged: bool) -> Result<String, GitError> {
+        or_cli(Libgit2.diff(repo, path, staged), || Cli.diff(repo, path, staged))
+    }
+
+    fn diff_revs(&self, repo: &str, from: &str, to: Option<&str>, paths: &[&str]) -> Result<String, GitError> {
+        or_cli(Libgit2.diff_revs(repo, from, to, paths), || Cli.diff_revs(repo, from, to, paths))
+    }
+}
+
+// -- libgit2 -----------------------------------------------------------------
+
+struct Libgit2;
+
+fn open(repo: &str) -> Result<Repository, GitError> {
+    Repository::discover(repo).map_err(|e| match e.code() {
+        ErrorCode::NotFound => GitError::NotARepo,
+        // p. ej. extensiones del repo que libgit2 no conoce
+        _ => GitError::Unsupported(e.message().to_string()),
+    })
+}
+
+/// Rama a la que apunta HEAD, aunque todavia no tenga commits.
+fn head_branch(repo: &Repository) -> Option<String> {
+    let head = repo.find_reference("HEAD").ok()?;
+    head.symbolic_target()?.strip_prefix("refs/heads/").map(str::to_string)
+}
+
+/// Letras `XY` de `git status --porcelain` para una entrada.
+fn porcelain_code(s: Status) -> String {
+    if s.is_conflicted() {
+        return "UU".to_string();
+    }
+    if s.is_wt_new() && !s.intersects(Status::INDEX_NEW | Status::INDEX_MODIFIED) {
+        return "??".to_string();
+    }
+    let x = if s.is_index_new() {
+        'A'
+    } else if s.is_index_modified() {
+        'M'
+    } else if s.is_index_deleted() {
+        'D'
+    } else if s.is_index_renamed() {
+        'R'
+    } else if s.is_index_typechange() {
+        'T'
+    } else {
+        ' '
+    };
+    let y = if s.is_wt_modified() {
+        'M'
+    } else if s.is_wt_deleted() {
+        'D'
+    } else if s.is_wt_typechange() {
+        'T'
+    } else {
+        ' '
+    };
+    format!("{}{}", x, y)
+}
+
+fn status_options() -> StatusOptions {
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(true)
+        .recurse_untracked_dirs(false)
+        .exclude_submodules(true)
+        .renames_head_to_index(true);
+    opts
+}
+
+/// libgit2 no ejecuta hooks ni firma commits: en esos repos se commitea con
+/// el CLI.
+fn needs_cli_commit(repo: &Repository) -> bool {
+    let config = repo.config().ok();
+    if config
+        .as_ref()
+        .and_then(|c| c.get_bool("commit.gpgsign").ok())
+        .unwrap_or(false)
+    {
+        return true;
+    }
+    let hooks_dir = config
+        .as_ref()
+        .and_then(|c| c.get_path("core.hooksPath").ok())
+        .map(|p| match (p.is_absolute(), repo.workdir()) {
+            (false, Some(workdir)) => workdir.join(p),
+            _ => p,
+        })
+        .unwrap_or_else(|| repo.path().join("hooks"));
+    COMMIT_HOOKS.iter().any(|hook| hooks_dir.join(hook).is_file())
+}
+
+fn iso_date(time: git2::Time) -> String {
+    FixedOffset::east_opt(time.offset_minutes() * 60)
+        .and_then(|tz| DateTime::from_timestamp(time.seconds(), 0).map(|t| t.with_timezone(&tz)))
+        .map(|t| t.to_rfc3339())
+        .unwrap_or_default()
+}
+
+/// Carpeta de `path` dentro del working tree con `/` al final ("" en la
+/// raiz), para pasar de rutas relativas al vault a rutas del repo.
+fn workdir_prefix(repo: &Repository, path: &str) -> Result<String, GitError> {
+    let outside = || GitError::Unsupported(format!("{} fuera del working tree", path));
+    let workdir = repo.workdir().and_then(|w| fs::canonicalize(w).ok()).ok_or_else(outside)?;
+    let dir = fs::canonicalize(path).map_err(|_| outside())?;
+    let rel = dir.strip_prefix(&workdir).map_err(|_| outside())?;
+    Ok(rel.iter().map(|part| format!("{}/", part.to_string_lossy())).collect())
+}
+
+/// Texto del diff como lo imprime `git diff`.
+fn patch_text(diff: &git2::Diff) -> Result<String, GitError> {
+    let mut out = Vec::new();
+    diff.print(DiffFormat::Patch, |_, _, line| {
+        if matches!(line.origin(), '+' | '-' | ' ') {
+            out.push(line.origin() as u8);
+        }
+        out.extend_from_slice(line.content());
+        true
+    })?;
+    Ok(String::from_utf8_lossy(&out).to_string())
+}
+
+fn delta_status(delta: Delta) -> char {
+    match delta {
+        Delta::Added => 'A',
+        Delta::Deleted => 'D',
+        Delta::Renamed => 'R',
+        Delta::Copied => 'C',
+        Delta::Typechange => 'T',
+        _ => 'M',
+    }
+}
+
+impl GitBackend for Libgit2 {
+    fn status(&self, path: &str) -> Result<GitStatus, GitError> {
+        let mut repo = open(path)?;
+        let mut status = GitStatus {
+            is_repo: true,
+            operation: conflicts::operation_in_progress(path).unwrap_or("none").to_string(),
+            ..Default::default()
+        };
+
+        for entry in repo.statuses(Some(&mut status_options()))?.iter() {
+            let s = entry.status();
+            if s.is_ignored() {
+                continue;
+            }
+            if s.is_conflicted() {
+                status.conflicted += 1;
+            } else if porcelain_code(s) == "??" {
+                status.untracked += 1;
+            } else {
+                if s.intersects(
+                    Status::INDEX_NEW
+                        | Status::INDEX_MODIFIED
+                        | Status::INDEX_DELETED
+                        | Status::INDEX_RENAMED
+                        | Status::INDEX_TYPECHANGE,
+                ) {
+                    status.staged += 1;
+                }
+                if s.intersects(Status::WT_MODIFIED | Status::WT_DELETED | Status::WT_TYPECHANGE | Status::WT_RENAMED) {
+                    status.unstaged += 1;
+                }
+            }
+            status.has_changes = true;
+        }
+
+        status.detached = repo.head_detached().unwrap_or(false);
+        if !status.detached {
+            status.branch = head_branch(&repo).unwrap_or_default();
+        }
+        if let Ok(local) = repo.find_branch(&status.branch, BranchType::Local) {
+            if let Ok(upstream) = local.upstream() {
+                status.upstream = upstream.name().ok().flatten().map(str::to_string);
+                if let (Some(l), Some(u)) = (local.get().target(), upstream.get().target()) {
+                    let (ahead, behind) = repo.graph_ahead_behind(l, u)?;
+                    status.ahead = ahead as u32;
+                    status.behind = behind as u32;
+                }
+            }
+        }
+
+        status.remote = repo
+            .find_remote("origin")
+            .ok()
+            .and_then(|r| r.url().map(str::to_string))
+            .unwrap_or_default();
+
+        // FETCH_HEAD se reescribe en cada fetch/pull
+        status.last_fetch = fs::metadata(repo.path().join("FETCH_HEAD"))
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_millis() as u64);
+
+        let mut stashes = 0;
+        repo.stash_foreach(|_, _, _| {
+            stashes += 1;
+            true
+        })?;
+        status.stash_count = stashes;
+
+        Ok(status)
+    }
+
+    fn changed_files(&self, path: &str) -> Result<Vec<GitFileChange>, GitError> {
+        let repo = open(path)?;
+        let statuses = repo.statuses(Some(&mut status_options()))?;
+        let files = statuses
+            .iter()
+            .filter(|e| !e.status().is_ignored())
+            .map(|entry| {
+                let code = porcelain_code(entry.status());
+                let renamed = entry.head_to_index().filter(|d| d.status() == Delta::Renamed);
+                let path = match renamed.as_ref().and_then(|d| Some((d.old_file().path()?, d.new_file().path()?))) {
+                    Some((from, to)) => format!("{} -> {}", from.to_string_lossy(), to.to_string_lossy()),
+                    None => entry.path().unwrap_or_default().to_string(),
+                };
+                file_change(code.trim(), path)
+            })
+            .collect();
+        Ok(files)
+    }
+
+    fn stage(&self, path: &str, files: &[String]) -> Result<(), GitError> {
+        let repo = open(path)?;
+        let mut index = repo.index()?;
+        // add_all agrega nuevos y modificados; update_all saca los borrados
+        index.add_all(files, IndexAddOption::DEFAULT, None)?;
+        index.update_all(files, None)?;
+        index.write()?;
+        Ok(())
+    }
+
+    fn stage_all(&self, path: &str) -> Result<(), GitError> {
+        self.stage(path, &["*".to_string()])
+    }
+
+    fn commit(&self, path: &str, message: &str) -> Result<String, GitError> {
+        if message.trim().is_empty() {
+            return Err(GitError::EmptyMessage);
+        }
+        let repo = open(path)?;
+        if repo.state() != RepositoryState::Clean || needs_cli_commit(&repo) {
+            return Err(GitError::Unsupported("commit con el CLI".to_string()));
+        }
+
+        let tree = repo.find_tree(repo.index()?.write_tree()?)?;
+        let parent = match repo.head() {
+            Ok(head) => Some(head.peel_to_commit()?),
+            Err(e) if e.code() == ErrorCode::UnbornBranch => None,
+            Err(e) => return Err(e.into()),
+        };
+        if parent.as_ref().is_some_and(|p| p.tree_id() == tree.id()) {
+            return Err(GitError::NothingToCommit);
+        }
+
+        let signature = repo.signature()?;
+        let message = git2::message_prettify(message, None)?;
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        let oid = repo.commit(Some("HEAD"), &signature, &signature, &message, &tree, &parents)?;
+
+        let short = repo.find_object(oid, None)?.short_id()?;
+        Ok(format!(
+            "[{} {}] {}",
+            head_branch(&repo).unwrap_or_else(|| "HEAD".to_string()),
+            short.as_str().unwrap_or_default(),
+            message.lines().next().unwrap_or_default()
+        ))
+    }
+
+    fn log(
+        &self,
+        path: &str,
+        all_refs: bool,
+        pathspec: Option<&str>,
+        limit: Option<usize>,
+    ) -> Result<Vec<LogEntry>, GitError> {
+        let repo = open(path)?;
+        let mut walk = repo.revwalk()?;
+        walk.set_sorting(git2::Sort::TIME)?;
+        if all_refs {
+            walk.push_glob("*")?;
+        }
+        // Un repo sin commits no tiene HEAD que recorrer
+        if walk.push_head().is_err() && !all_refs {
+            return Ok(Vec::new());
+        }
+
+        let mut entries = Vec::new();
+        for oid in walk {
+            if limit.is_some_and(|max| entries.len() >= max) {
+                break;
+            }
+            let commit = repo.find_commit(oid?)?;
+
+            let mut files = Vec::new();
+            if commit.parent_count() <= 1 {
+                let parent_tree = match commit.parent_count() {
+                    0 => None,
+                    _ => Some(commit.parent(0)?.tree()?),
+                };
+                let mut opts = DiffOptions::new();
+                if let Some(spec) = pathspec {
+                    opts.pathspec(spec);
+                }
+                let mut diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), Some(&mut opts))?;
+                diff.find_similar(None)?;
+                for delta in diff.deltas() {
+                    if let Some(p) = delta.new_file().path().or_else(|| delta.old_file().path()) {
+                        let from = match delta.status() {
+                            Delta::Renamed | Delta::Copied => delta.old_file().path(),
+                            _ => None,
+                        };
+                        files.push(LogFile {
+                            status: delta_status(delta.status()),
+                            path: p.to_string_lossy().to_string(),
+                            from: from.map(|f| f.to_string_lossy().to_string()),
+                        });
+                    }
+                }
+            }
+            if pathspec.is_some() && files.is_empty() {
+                continue;
+            }
+
+            let author = commit.author();
+            let short = commit.as_object().short_id()?;
+            entries.push(LogEntry {
+                commit: commit.id().to_string(),
+                short: short.as_str().unwrap_or_default().to_string(),
+                author: author.name().unwrap_or_default().to_string(),
+                email: author.email().unwrap_or_default().to_string(),
+                date: iso_date(author.when()),
+                subject: commit.summary().unwrap_or_default().to_string(),
+                files,
+            });
+        }
+        Ok(entries)
+    }
+
+    fn diff(&self, path: &str, file: Option<&str>, staged: bool) -> Result<String, GitError> {
+        let repo = open(path)?;
+        let mut opts = DiffOptions::new();
+        if let Some(file) = file {
+            opts.pathspec(file).disable_pathspec_match(true);
+        }
+        let diff = if staged {
+            let head = repo.head().ok().and_then(|h| h.peel_to_tree().ok());
+            repo.diff_tree_to_index(head.as_ref(), None, Some(&mut opts))?
+        } else {
+            repo.diff_index_to_workdir(None, Some(&mut opts))?
+        };
+
+        patch_text(&diff)
+    }
+
+    fn file_log(
+        &self,
+        path: &str,
+        file: &str,
+        follow: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<LogEntry>, GitError> {
+        if follow {
+            // libgit2 no sigue renombres
+            return Err(GitError::Unsupported("log --follow".to_string()));
+        }
+        let repo = open(path)?;
+        let prefix = workdir_prefix(&repo, path)?;
+        let mut entries = self.log(path, false, Some(&format!("{}{}", prefix, file)), limit)?;
+        let relative = |p: &mut String| {
+            if let Some(rest) = p.strip_prefix(&prefix) {
+                *p = rest.to_string();
+            }
+        };
+        for file in entries.iter_mut().flat_map(|e| e.files.iter_mut()) {
+            relative(&mut file.path);
+            if let Some(from) = file.from.as_mut() {
+                relative(from);
+            }
+        }
+        Ok(entries)
+    }
+
+    fn blob_at(&self, path: &str, rev: &str, file: &str) -> Result<Vec<u8>, GitError> {
+        let repo = open(path)?;
+        let prefix = workdir_prefix(&repo, path)?;
+        let tree = repo.revparse_single(rev)?.peel_to_tree()?;
+        let entry = tree.get_path(std::path::Path::new(&format!("{}{}", prefix, file)))?;
+        let blob = entry.to_object(&repo)?.peel_to_blob()?;
+        Ok(blob.content().to_vec())
+    }
+
+    fn diff_revs(&self, path: &str, from: &str, to: Option<&str>, files: &[&str]) -> Result<String, GitError> {
+        let repo = open(path)?;
+        let prefix = workdir_prefix(&repo, path)?;
+        let mut opts = DiffOptions::new();
+        for file in files {
+            opts.pathspec(format!("{}{}", prefix, file));
+        }
+        opts.disable_pathspec_match(true);
+
+        let old = repo.revparse_single(from)?.peel_to_tree()?;
+        let mut diff = match to {
+            Some(to) => {
+                let new = repo.revparse_single(to)?.peel_to_tree()?;
+                repo.diff_tree_to_tree(Some(&old), Some(&new), Some(&mut opts))?
+            }
+            None => repo.diff_tree_to_workdir_with_index(Some(&old), Some(&mut opts))?,
+        };
+        // Como `-M1%`
+        let mut find = DiffFindOptions::new();
+        find.renames(true).rename_threshold(1);
+        diff.find_similar(Some(&mut find))?;
+        patch_text(&diff)
+    }
+}
+
+// -- CLI ---------------------------------------------------------------------
+
+struct Cli;
+
+fn cli(repo: &str, args: &[&str]) -> Result<String, GitError> {
+    cli_bytes(repo, args).map(|out| String::from_utf8_lossy(&out).to_string())
+}
+
+/// Como `cli`, con la salida tal cual (p. ej. el contenido de un blob).
+fn cli_bytes(repo: &str, args: &[&str]) -> Result<Vec<u8>, GitError> {
+    let output = git_cmd(repo)
+        .args(["-c", "core.quotePath=false"])
+        .args(args)
+        .output()
+        .map_err(|e| GitError::Spawn(e.to_string()))?;
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        // "nothing to commit" sale por stdout
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Err(GitError::from_cli(if stderr.trim().is_empty() { &stdout } else { &stderr }))
+    }
+}
+
+/// `remote.origin.url` leido del config del repo, sin lanzar git.
+fn origin_url(repo: &str) -> Option<String> {
+    let dir = git_dir(repo)?;
+    // En un worktree el config esta en el directorio comun
+    let common = match fs::read_to_string(dir.join("commondir")) {
+        Ok(common) => dir.join(common.trim()),
+        Err(_) => dir,
+    };
+    git2::Config::open(&common.join("config"))
+        .ok()?
+        .get_string("remote.origin.url")
+        .ok()
+}
+
+/// Parsea `git status --porcelain=v2 --branch --show-stash`.
+fn parse_status_v2(stdout: &str, status: &mut GitStatus) {
+    for line in stdout.lines() {
+        if let Some(header) = line.strip_prefix("# ") {
+            let (key, value) = header.split_once(' ').unwrap_or((header, ""));
+            match key {
+                "branch.head" if value == "(detached)" => status.detached = true,
+                "branch.head" => status.branch = value.to_string(),
+                "branch.upstream" => status.upstream = Some(value.to_string()),
+                "branch.ab" => {
+                    for part in value.split_whitespace() {
+                        if let Some(n) = part.strip_prefix('+') {
+                            status.ahead = n.parse().unwrap_or(0);
+                        } else if let Some(n) = part.strip_prefix('-') {
+                            status.behind = n.parse().unwrap_or(0);
+                        }
+                    }
+                }
+                "stash" => status.stash_count = value.parse().unwrap_or(0),
+                _ => {}
+            }
+            continue;
+        }
+
+        // "1 XY ...", "2 XY ..." (renombres), "u XY ..." (sin resolver), "? ruta"
+        let mut fields = line.splitn(3, ' ');
+        match (fields.next(), fields.next()) {
+            (Some("1" | "2"), Some(xy)) => {
+                let mut xy = xy.chars();
+                if xy.next().is_some_and(|x| x != '.') {
+                    status.staged += 1;
+                }
+                if xy.next().is_some_and(|y| y != '.') {
+                    status.unstaged += 1;
+                }
+            }
+            (Some("u"), _) => status.conflicted += 1,
+            (Some("?"), _) => status.untracked += 1,
+            _ => continue,
+        }
+        status.has_changes = true;
+    }
+}
+
+/// `--format` de `git log` que entiende `parse_log`.
+fn log_format() -> String {
+    format!("--format={}%H{f}%h{f}%an{f}%ae{f}%aI{f}%s", RECORD_SEP, f = FIELD_SEP)
+}
+
+/// Parsea `git log --name-status` con el formato de `log_format`.
+fn parse_log(stdout: &str) -> Vec<LogEntry> {
+    stdout
+        .split(RECORD_SEP)
+        .filter_map(|record| {
+            let mut lines = record.lines();
+            let header: Vec<&str> = lines.next()?.splitn(6, FIELD_SEP).collect();
+            let [commit, short, author, email, date, subject] = header[..] else {
+                return None;
+            };
+            // "M\truta" o "R087\tvieja\tnueva"
+            let files = lines
+                .filter_map(|l| {
+                    let parts: Vec<&str> = l.split('\t').collect();
+                    let (path, from) = match parts[..] {
+                        [_, path] => (path, None),
+                        [_, from, to] => (to, Some(from.to_string())),
+                        _ => return None,
+                    };
+                    Some(LogFile {
+                        status: parts[0].chars().next()?,
+                        path: path.to_string(),
+                        from,
+                    })
+                })
+                .collect();
+            Some(LogEntry {
+                commit: commit.to_string(),
+                short: short.to_string(),
+                author: author.to_string(),
+                email: email.to_string(),
+                date: date.to_string(),
+                subject: subject.to_string(),
+                files,
+            })
+        })
+        .collect()
+}
+
+/// Un repo sin commits no tiene HEAD que recorrer.
+fn has_head(repo: &str) -> bool {
+    cli(repo, &["rev-parse", "--verify", "--quiet", "HEAD"]).is_ok()
+}
+
+impl GitBackend for Cli {
+    fn status(&self, repo: &str) -> Result<GitStatus, GitError> {
+        let run = |extra: &[&str]| {
+            let mut args = vec!["status", "--porcelain=v2", "--branch"];
+            args.extend_from_slice(extra);
+            cli(repo, &args)
+        };
+        // --show-stash es de git 2.35; en versiones anteriores no se cuentan
+        let stdout = run(&["--show-stash"]).or_else(|e| match e {
+            GitError::Failed(msg) if msg.contains("show-stash") => run(&[]),
+            e => Err(e),
+        })?;
+
+        let mut status = GitStatus {
+            is_repo: true,
+            operation: conflicts::operation_in_progress(repo).unwrap_or("none").to_string(),
+            ..Default::default()
+        };
+        parse_status_v2(&stdout, &mut status);
+
+        status.remote = origin_url(repo).unwrap_or_default();
+
+        // FETCH_HEAD se reescribe en cada fetch/pull
+        status.last_fetch = git_dir(repo)
+            .and_then(|dir| fs::metadata(dir.join("FETCH_HEAD")).ok())
+            .and_then(|m| m.modified().ok())
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_millis() as u64);
+
+        Ok(status)
+    }
+
+    fn changed_files(&self, repo: &str) -> Result<Vec<GitFileChange>, GitError> {
+        // Con -z las rutas no vienen entre comillas y un renombre es
+        // "XY nueva\0vieja\0"
+        let stdout = cli(repo, &["status", "--porcelain", "-z"])?;
+        let mut entries = stdout.split('\0');
+        let mut files = Vec::new();
+        while let Some(entry) = entries.next() {
+            if entry.len() < 4 {
+                continue;
+            }
+            let code = &entry[..2];
+            let mut path = entry[3..].to_string();
+            if code.starts_with(&['R', 'C'][..]) {
+                if let Some(from) = entries.next() {
+                    path = format!("{} -> {}", from, path);
+                }
+            }
+            files.push(file_change(code.trim(), path));
+        }
+        Ok(files)
+    }
+
+    fn stage(&self, repo: &str, files: &[String]) -> Result<(), GitError> {
+        let mut args = vec!["add", "--"];
+        args.extend(files.iter().map(String::as_str));
+        cli(repo, &args).map(|_| ())
+    }
+
+    fn stage_all(&self, repo: &str) -> Result<(), GitError> {
+        cli(repo, &["add", "-A"]).map(|_| ())
+    }
+
+    fn commit(&self, repo: &str, message: &str) -> Result<String, GitError> {
+        cli(repo, &["commit", "-m", message]).map(|out| out.lines().next().unwrap_or_default().to_string())
+    }
+
+    fn log(
+        &self,
+        repo: &str,
+        all_refs: bool,
+        pathspec: Option<&str>,
+        limit: Option<usize>,
+    ) -> Result<Vec<LogEntry>, GitError> {
+        if !all_refs && !has_head(repo) {
+            return Ok(Vec::new());
+        }
+        let format = log_format();
+        let max = limit.map(|n| format!("-n{}", n));
+        let mut args = vec!["log", "--name-status", format.as_str()];
+        if all_refs {
+            args.push("--all");
+        }
+        args.extend(max.as_deref());
+        args.push("--");
+        args.extend(pathspec);
+        Ok(parse_log(&cli(repo, &args)?))
+    }
+
+    fn diff(&self, repo: &str, path: Option<&str>, staged: bool) -> Result<String, GitError> {
+        let mut args = vec!["-c", "diff.noprefix=false", "diff", "--no-color", "--no-ext-diff"];
+        if staged {
+            args.push("--cached");
+        }
+        args.push("--");
+        args.extend(path);
+        cli(repo, &args)
+    }
+
+    fn file_log(
+        &self,
+        repo: &str,
+        path: &str,
+        follow: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<LogEntry>, GitError> {
+        if !has_head(repo) {
+            return Ok(Vec::new());
+        }
+        let format = log_format();
+        let max = limit.map(|n| format!("-n{}", n));
+        // --relative: rutas relativas a `repo` aunque sea una subcarpeta
+        let mut args = vec!["log", "--name-status", "--relative", format.as_str()];
+        if follow {
+            args.push("--follow");
+        }
+        args.extend(max.as_deref());
+        args.extend(["--", path]);
+        Ok(parse_log(&cli(repo, &args)?))
+    }
+
+    fn blob_at(&self, repo: &str, rev: &str, path: &str) -> Result<Vec<u8>, GitError> {
+        // `./` resuelve la ruta desde `repo` y no desde la raiz del repo
+        cli_bytes(repo, &["show", &format!("{}:./{}", rev, path)])
+    }
+
+    fn diff_revs(&self, repo: &str, from: &str, to: Option<&str>, paths: &[&str]) -> Result<String, GitError> {
+        let mut args = vec!["-c", "diff.noprefix=false", "diff", "--no-color", "--no-ext-diff", "-M1%", from];
+        args.extend(to);
+        args.push("--");
+        args.extend_from_slice(paths);
+        cli(repo, &args)
+    }
+}
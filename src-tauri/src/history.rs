@@ -0,0 +1,193 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::errors::{AppError, ErrorKind};
+use crate::git_backend::{self, GitError, LogEntry};
+use crate::{atomic_write, autosync, content_hash, index_path_updated, note_revision, vault_relative, NoteContent};
+
+/// Commits que se listan por defecto en el historial de una nota.
+const DEFAULT_LIMIT: usize = 100;
+
+#[derive(Serialize, Clone)]
+pub struct FileRevision {
+    commit: String,
+    short: String,
+    author: String,
+    email: String,
+    date: String, // ISO 8601 con zona horaria
+    subject: String,
+    path: String,   // ruta de la nota en ese commit, relativa al vault
+    status: String, // "A", "M", "R", "D"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    from: Option<String>, // ruta anterior si el commit la renombro
+}
+
+impl FileRevision {
+    /// Un commit de `file_log`: la nota es el unico archivo que lista.
+    fn from_log(entry: LogEntry) -> Option<Self> {
+        let file = entry.files.into_iter().next()?;
+        Some(FileRevision {
+            commit: entry.commit,
+            short: entry.short,
+            author: entry.author,
+            email: entry.email,
+            date: entry.date,
+            subject: entry.subject,
+            path: file.path,
+            status: file.status.to_string(),
+            from: file.from,
+        })
+    }
+}
+
+/// Una version de una nota: un commit y la ruta que tenia en el, o el
+/// working tree si `commit` es None.
+#[derive(Deserialize, Clone)]
+pub struct Revision {
+    commit: Option<String>,
+    path: String,
+}
+
+#[derive(Serialize, Clone)]
+pub struct FileDiff {
+    diff: String, // unified diff tal cual lo da git
+    added: usize,
+    removed: usize,
+}
+
+/// Rechaza revisiones que git podria tomar como opciones o que romperian
+/// la sintaxis `commit:ruta`.
+fn check_rev(rev: &str) -> Result<(), AppError> {
+    if rev.is_empty() || rev.starts_with('-') || rev.contains(':') || rev.chars().any(char::is_whitespace) {
+        return Err(AppError::invalid("git.invalid_rev", rev));
+    }
+    Ok(())
+}
+
+/// Ruta relativa al vault con `/`; acepta rutas absolutas o ya relativas.
+fn rel_path(vault: &Path, path: &str) -> String {
+    vault_relative(vault, Path::new(path)).trim_start_matches("./").to_string()
+}
+
+/// Contenido de `rel` (relativa al vault) en `commit`, en bytes.
+fn blob_at(vault_path: &str, commit: &str, rel: &str) -> Result<Vec<u8>, AppError> {
+    check_rev(commit)?;
+    git_backend::backend().blob_at(vault_path, commit, rel).map_err(|e| match e {
+        GitError::Failed(_) | GitError::Unsupported(_) => {
+            AppError::new(ErrorKind::NotFound, "git.missing_at").param("path", rel).param("commit", commit)
+        }
+        e => e.into(),
+    })
+}
+
+// -- Comandos ----------------------------------------------------------------
+
+/// Commits que tocaron la nota, del mas reciente al mas antiguo, siguiendo
+/// los renombres. `path` puede ser absoluta o relativa al vault.
+#[tauri::command]
+pub async fn git_file_history(
+    vault_path: String,
+    path: String,
+    limit: Option<usize>,
+) -> Result<Vec<FileRevision>, AppError> {
+    tokio::task::spawn_blocking(move || {
+        let rel = rel_path(Path::new(&vault_path), &path);
+        let log = git_backend::backend().file_log(&vault_path, &rel, true, Some(limit.unwrap_or(DEFAULT_LIMIT)))?;
+        Ok(log.into_iter().filter_map(FileRevision::from_log).collect())
+    })
+    .await?
+}
+
+/// Contenido de la nota en un commit. `path` es la ruta que tenia en ese
+/// commit (la de `git_file_history`).
+#[tauri::command]
+pub async fn git_file_at(vault_path: String, commit: String, path: String) -> Result<String, AppError> {
+    tokio::task::spawn_blocking(move || {
+        let rel = rel_path(Path::new(&vault_path), &path);
+        let blob = blob_at(&vault_path, &commit, &rel)?;
+        Ok(String::from_utf8_lossy(&blob).to_string())
+    })
+    .await?
+}
+
+/// Diff de la nota entre dos versiones. Si `to.commit` es None se compara
+/// con el working tree. Si las rutas difieren (la nota se renombro entre
+/// ambas) git lo muestra como un renombre.
+#[tauri::command]
+pub async fn git_file_diff(vault_path: String, from: Revision, to: Revision) -> Result<FileDiff, AppError> {
+    tokio::task::spawn_blocking(move || {
+        let vault = Path::new(&vault_path);
+        let Some(from_commit) = from.commit.as_deref() else {
+            return Err(AppError::new(ErrorKind::InvalidInput, "git.diff_from_commit"));
+        };
+        check_rev(from_commit)?;
+
+        let from_rel = rel_path(vault, &from.path);
+        let to_rel = rel_path(vault, &to.path);
+        if let Some(to_commit) = to.commit.as_deref() {
+            check_rev(to_commit)?;
+        }
+        // Limitado a estas dos rutas, cualquier parecido basta para tomarlo
+        // como renombre y mostrar un solo diff
+        let mut paths = vec![from_rel.as_str()];
+        if to_rel != from_rel {
+            paths.push(&to_rel);
+        }
+
+        let diff = git_backend::backend().diff_revs(&vault_path, from_commit, to.commit.as_deref(), &paths)?;
+        let count = |sign: char, header: &str| {
+            diff.lines()
+                .filter(|l| l.starts_with(sign) && !l.starts_with(header))
+                .count()
+        };
+        Ok(FileDiff {
+            added: count('+', "+++"),
+            removed: count('-', "---"),
+            diff,
+        })
+    })
+    .await?
+}
+
+/// Reemplaza la nota `target` (ruta absoluta actual) por su version en
+/// `commit`, sin hacer commit ni stage. Con `expected_revision` no se pisa
+/// la nota si cambio en disco desde que se leyo.
+#[tauri::command]
+pub async fn git_restore_file(
+    vault_path: String,
+    target: String,
+    commit: String,
+    path: String,
+    expected_revision: Option<String>,
+) -> Result<NoteContent, AppError> {
+    tokio::task::spawn_blocking(move || {
+        autosync::exclusive(&vault_path, || {
+            let vault = PathBuf::from(&vault_path);
+            let target = PathBuf::from(&target);
+            if !target.starts_with(&vault) {
+                return Err(AppError::new(ErrorKind::InvalidInput, "vault.outside").param("path", target.display()));
+            }
+
+            if let (Some(expected), Ok(current)) = (expected_revision.as_deref(), fs::read(&target)) {
+                let expected_hash = expected.split_once('-').map_or(expected, |(_, h)| h);
+                if expected_hash != content_hash(&current) {
+                    return Err(AppError::new(ErrorKind::Stale, "history.stale"));
+                }
+            }
+
+            let blob = blob_at(&vault_path, &commit, &rel_path(&vault, &path))?;
+            if let Some(dir) = target.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            atomic_write(&target, &blob)?;
+            index_path_updated(&target);
+
+            Ok(NoteContent {
+                revision: note_revision(&target, &blob),
+                content: String::from_utf8_lossy(&blob).to_string(),
+            })
+        })
+    })
+    .await?
+}
@@ -0,0 +1,267 @@
+use regex::Regex;
+use serde::Serialize;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::OnceLock;
+
+use crate::errors::{AppError, ErrorKind};
+use crate::{autosync, content_hash, git_backend, git_cmd, index_path_updated};
+
+#[derive(Serialize, Clone)]
+pub struct DiffLine {
+    kind: String, // "context", "added", "removed"
+    text: String,
+}
+
+#[derive(Serialize, Clone)]
+pub struct DiffHunk {
+    id: usize,
+    header: String, // "@@ -12,4 +12,6 @@ seccion"
+    old_start: u32,
+    old_lines: u32,
+    new_start: u32,
+    new_lines: u32,
+    lines: Vec<DiffLine>,
+    #[serde(skip)]
+    raw: String, // texto del hunk tal cual, para armar el parche
+}
+
+#[derive(Serialize, Clone)]
+pub struct FileHunks {
+    path: String, // relativa al vault
+    untracked: bool,
+    diff_hash: String, // para detectar que el diff cambio entre listar y aplicar
+    hunks: Vec<DiffHunk>,
+    #[serde(skip)]
+    header: String, // "diff --git ...", "index ...", "--- a/...", "+++ b/..."
+}
+
+fn hunk_header_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"^@@ -(\d+)(?:,(\d+))? \+(\d+)(?:,(\d+))? @@").unwrap())
+}
+
+fn parse_hunk_header(line: &str) -> Option<(u32, u32, u32, u32)> {
+    let caps = hunk_header_regex().captures(line)?;
+    let num = |i: usize, default: u32| caps.get(i).map_or(Some(default), |m| m.as_str().parse().ok());
+    Some((num(1, 0)?, num(2, 1)?, num(3, 0)?, num(4, 1)?))
+}
+
+/// Parsea la salida de `git diff` (uno o varios archivos) en hunks.
+fn parse_diff(diff: &str) -> Vec<FileHunks> {
+    let mut files: Vec<FileHunks> = Vec::new();
+    for line in diff.split_inclusive('\n') {
+        if line.starts_with("diff --git ") {
+            files.push(FileHunks {
+                path: String::new(),
+                untracked: false,
+                diff_hash: String::new(),
+                hunks: vec![],
+                header: String::new(),
+            });
+        }
+        let Some(file) = files.last_mut() else { continue };
+
+        if let Some((old_start, old_lines, new_start, new_lines)) = parse_hunk_header(line) {
+            file.hunks.push(DiffHunk {
+                id: file.hunks.len(),
+                header: line.trim_end().to_string(),
+                old_start,
+                old_lines,
+                new_start,
+                new_lines,
+                lines: vec![],
+                raw: String::new(),
+            });
+        }
+
+        match file.hunks.last_mut() {
+            Some(hunk) => {
+                hunk.raw.push_str(line);
+                let text = line.get(1..).unwrap_or_default().trim_end_matches(['\n', '\r']).to_string();
+                let kind = match line.as_bytes().first() {
+                    Some(b' ') => "context",
+                    Some(b'+') => "added",
+                    Some(b'-') => "removed",
+                    _ => continue, // el propio "@@" o "\ No newline at end of file"
+                };
+                hunk.lines.push(DiffLine { kind: kind.to_string(), text });
+            }
+            None => {
+                file.header.push_str(line);
+                let target = line
+                    .strip_prefix("+++ b/")
+                    .or_else(|| line.strip_prefix("--- a/").filter(|_| file.path.is_empty()));
+                if let Some(path) = target {
+                    // git agrega un tab tras los nombres con espacios
+                    file.path = path.trim_end_matches(['\n', '\r', '\t']).to_string();
+                }
+            }
+        }
+    }
+
+    for file in &mut files {
+        let raw: String = file.hunks.iter().map(|h| h.raw.as_str()).collect();
+        file.diff_hash = content_hash(format!("{}{}", file.header, raw).as_bytes());
+    }
+    files
+}
+
+/// Un archivo sin seguimiento como un unico hunk con todo su contenido.
+fn untracked_hunks(vault_path: &str, path: &str) -> Option<FileHunks> {
+    let content = fs::read_to_string(Path::new(vault_path).join(path)).ok()?;
+    let lines: Vec<DiffLine> = content
+        .lines()
+        .map(|l| DiffLine {
+            kind: "added".to_string(),
+            text: l.to_string(),
+        })
+        .collect();
+    let count = lines.len() as u32;
+    Some(FileHunks {
+        path: path.to_string(),
+        untracked: true,
+        diff_hash: content_hash(content.as_bytes()),
+        hunks: vec![DiffHunk {
+            id: 0,
+            header: format!("@@ -0,0 +1,{} @@", count),
+            old_start: 0,
+            old_lines: 0,
+            new_start: 1,
+            new_lines: count,
+            lines,
+            raw: String::new(),
+        }],
+        header: String::new(),
+    })
+}
+
+fn git_text(vault_path: &str, args: &[&str]) -> Result<String, AppError> {
+    let output = git_cmd(vault_path)
+        .args(["-c", "core.quotePath=false"])
+        .args(args)
+        .output()
+        .map_err(|e| AppError::spawn("git", e))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(AppError::git(String::from_utf8_lossy(&output.stderr).trim()))
+    }
+}
+
+/// Hunks de los cambios sin stage (o ya en stage, con `staged`), de un
+/// archivo o de todos. Los archivos nuevos sin seguimiento solo aparecen sin
+/// `staged`, como un hunk con todo su contenido.
+fn file_hunks(vault_path: &str, path: Option<&str>, staged: bool) -> Result<Vec<FileHunks>, AppError> {
+    let diff = git_backend::backend().diff(vault_path, path, staged)?;
+    let mut files = parse_diff(&diff);
+
+    if !staged {
+        let mut args = vec!["ls-files", "--others", "--exclude-standard", "-z", "--"];
+        if let Some(path) = path {
+            args.push(path);
+        }
+        let untracked = git_text(vault_path, &args)?;
+        files.extend(
+            untracked
+                .split('\0')
+                .filter(|p| !p.is_empty())
+                .filter_map(|p| untracked_hunks(vault_path, p)),
+        );
+    }
+    Ok(files)
+}
+
+fn apply_patch(vault_path: &str, patch: &str, args: &[&str]) -> Result<(), AppError> {
+    let mut child = git_cmd(vault_path)
+        .arg("apply")
+        .args(args)
+        .arg("-")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| AppError::spawn("git", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(patch.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(AppError::new(ErrorKind::Git, "git.apply_failed").detail(String::from_utf8_lossy(&output.stderr).trim()))
+    }
+}
+
+// -- Comandos ----------------------------------------------------------------
+
+/// Diff de cada nota cambiada (o solo de `path`) separado en hunks.
+/// Con `staged` devuelve lo que ya esta en stage.
+#[tauri::command]
+pub async fn git_diff_hunks(
+    vault_path: String,
+    path: Option<String>,
+    staged: Option<bool>,
+) -> Result<Vec<FileHunks>, AppError> {
+    tokio::task::spawn_blocking(move || file_hunks(&vault_path, path.as_deref(), staged.unwrap_or(false)))
+        .await?
+}
+
+/// Aplica hunks sueltos de `path`: `action` es "stage" (agregar al index),
+/// "unstage" (sacarlos del index) o "discard" (deshacerlos en el archivo).
+/// `diff_hash` es el de `git_diff_hunks`; si el diff cambio desde entonces
+/// no se aplica nada.
+#[tauri::command]
+pub async fn git_apply_hunks(
+    vault_path: String,
+    path: String,
+    diff_hash: String,
+    hunks: Vec<usize>,
+    action: String,
+) -> Result<(), AppError> {
+    tokio::task::spawn_blocking(move || {
+        autosync::exclusive(&vault_path, || {
+            let (staged, args): (bool, &[&str]) = match action.as_str() {
+                "stage" => (false, &["--cached"]),
+                "unstage" => (true, &["--cached", "--reverse"]),
+                "discard" => (false, &["--reverse"]),
+                other => return Err(AppError::invalid("git.unknown_action", other)),
+            };
+
+            let files = file_hunks(&vault_path, Some(&path), staged)?;
+            let Some(file) = files.into_iter().find(|f| f.path == path) else {
+                return Err(AppError::new(ErrorKind::NotFound, "git.no_changes").param("path", &path));
+            };
+            if file.diff_hash != diff_hash {
+                return Err(AppError::new(ErrorKind::Stale, "git.diff_changed"));
+            }
+
+            if file.untracked {
+                // Un archivo nuevo es un solo hunk: se agrega entero
+                return match action.as_str() {
+                    "stage" => git_text(&vault_path, &["add", "--", &path]).map(|_| ()),
+                    _ => Err(AppError::new(ErrorKind::InvalidInput, "git.untracked_discard")),
+                };
+            }
+
+            let selected: String = file
+                .hunks
+                .iter()
+                .filter(|h| hunks.contains(&h.id))
+                .map(|h| h.raw.as_str())
+                .collect();
+            if selected.is_empty() {
+                return Err(AppError::new(ErrorKind::InvalidInput, "git.no_hunks"));
+            }
+            apply_patch(&vault_path, &format!("{}{}", file.header, selected), args)?;
+
+            if action == "discard" {
+                index_path_updated(&Path::new(&vault_path).join(&path));
+            }
+            Ok(())
+        })
+    })
+    .await?
+}
@@ -0,0 +1,371 @@
+use serde::Serialize;
+use std::sync::Mutex;
+
+use crate::errors::AppError;
+use crate::settings;
+
+/// Idiomas de los mensajes del backend. Para agregar uno: una variante, su
+/// codigo en `code`/`parse` y su tabla en `catalog`. Las claves que falten en
+/// una tabla se muestran en espanol.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    Es,
+    En,
+}
+
+pub const LOCALES: [Locale; 2] = [Locale::Es, Locale::En];
+
+impl Locale {
+    pub fn code(self) -> &'static str {
+        match self {
+            Locale::Es => "es",
+            Locale::En => "en",
+        }
+    }
+
+    /// "es", "es_MX.UTF-8", "en-US"... Solo importa el idioma.
+    pub fn parse(value: &str) -> Option<Self> {
+        let lang = value.split(['_', '-', '.', '@']).next()?.to_lowercase();
+        LOCALES.into_iter().find(|l| l.code() == lang)
+    }
+
+    /// Del entorno, como hace gettext: LC_ALL, LC_MESSAGES y luego LANG.
+    /// Sin idioma (o "C"/"POSIX") se queda en espanol; un idioma que no
+    /// tenemos, en ingles.
+    pub fn from_env() -> Self {
+        let value = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|v| !v.is_empty());
+        match value.as_deref() {
+            None | Some("C") | Some("POSIX") => Locale::Es,
+            Some(v) if v.starts_with("C.") => Locale::Es,
+            Some(v) => Locale::parse(v).unwrap_or(Locale::En),
+        }
+    }
+}
+
+static LOCALE: Mutex<Option<Locale>> = Mutex::new(None);
+
+/// Idioma actual: el elegido en las preferencias o, si no hay, el del
+/// sistema.
+pub fn current() -> Locale {
+    *LOCALE
+        .lock()
+        .unwrap()
+        .get_or_insert_with(|| settings::current().locale().unwrap_or_else(Locale::from_env))
+}
+
+pub fn set_current(locale: Locale) {
+    *LOCALE.lock().unwrap() = Some(locale);
+}
+
+/// Texto de `key` en el idioma actual; la propia clave si no existe.
+pub fn t(key: &str) -> String {
+    render(key, [])
+}
+
+/// Como `t`, reemplazando los `{nombre}` del texto por `params`.
+pub fn render<'a>(key: &str, params: impl IntoIterator<Item = (&'a str, &'a str)>) -> String {
+    let Some(template) = catalog(current(), key).or_else(|| catalog(Locale::Es, key)) else {
+        return key.to_string();
+    };
+    let mut text = template.to_string();
+    for (name, value) in params {
+        text = text.replace(&format!("{{{}}}", name), value);
+    }
+    text
+}
+
+fn catalog(locale: Locale, key: &str) -> Option<&'static str> {
+    match locale {
+        Locale::Es => es(key),
+        Locale::En => en(key),
+    }
+}
+
+// -- Comandos ----------------------------------------------------------------
+
+#[derive(Serialize, Clone)]
+pub struct LocaleInfo {
+    locale: Locale,
+    available: Vec<Locale>,
+}
+
+/// Idioma actual de los mensajes del backend y los disponibles.
+#[tauri::command]
+pub fn get_locale() -> LocaleInfo {
+    LocaleInfo {
+        locale: current(),
+        available: LOCALES.to_vec(),
+    }
+}
+
+/// Cambia el idioma de los mensajes del backend ("es", "en", "en_US"...).
+/// Sin `locale` se vuelve al del sistema.
+#[tauri::command]
+pub fn set_locale(locale: Option<String>) -> Result<LocaleInfo, AppError> {
+    let parsed = match locale.as_deref().filter(|l| !l.is_empty()) {
+        Some(l) => Locale::parse(l).ok_or_else(|| AppError::invalid("i18n.unsupported", l))?,
+        None => Locale::from_env(),
+    };
+    set_current(parsed);
+    Ok(get_locale())
+}
+
+// -- Catalogos ---------------------------------------------------------------
+
+fn es(key: &str) -> Option<&'static str> {
+    Some(match key {
+        "internal.task" => "Error interno de la tarea",
+        "internal.json" => "Error interno al serializar datos",
+        "io.failed" => "Error de entrada/salida",
+        "process.spawn" => "No se pudo ejecutar {program}",
+        "i18n.unsupported" => "Idioma no soportado: {value}",
+        "settings.unknown_key" => "Preferencia desconocida: {key}",
+        "settings.invalid_value" => "Valor no valido para {key}",
+        "settings.invalid_file" => "settings.json no es valido; se guardo como settings.invalid.json y se usan los valores por defecto",
+        "settings.migrate_failed" => "No se pudo guardar settings.json actualizado",
+        "vault_config.parse" => "No se pudo leer .potato/config",
+        "vault_config.not_a_map" => ".potato/config debe ser un mapa de claves",
+        "vault_config.unknown_key" => "Clave desconocida en .potato/config: {key}",
+        "vault_config.invalid_value" => "Valor no valido para {key} en .potato/config",
+
+        "dialog.open_vault" => "Seleccionar Vault",
+        "dialog.pick_folder" => "Elegir carpeta destino",
+
+        "vault.not_a_dir" => "No es un directorio: {path}",
+        "vault.outside" => "La ruta esta fuera del vault: {path}",
+        "file.not_found" => "El archivo no existe: {path}",
+        "file.dest_not_found" => "La carpeta destino no existe: {path}",
+        "file.dest_exists" => "Ya existe '{name}' en la carpeta destino",
+        "file.invalid_name" => "Nombre de archivo invalido: {value}",
+        "file.move_failed" => "Error al mover archivo",
+        "file.read_failed" => "No se pudo leer {path}",
+        "note.not_utf8" => "La nota no es texto UTF-8 valido",
+        "note.invalid_name" => "Nombre invalido: {value}",
+        "note.exists" => "Ya existe una nota llamada '{name}'",
+        "note.rename_failed" => "Error al renombrar",
+        "note.delete_failed" => "Error al eliminar",
+        "note.links_failed" => "Error al actualizar links en {path}",
+        "note.write_failed" => "Error al escribir {path}",
+        "template.invalid" => "Plantilla invalida: {value}",
+        "template.not_found" => "La plantilla no existe: {name}",
+        "frontmatter.invalid_yaml" => "El frontmatter de la nota no es YAML valido",
+        "frontmatter.invalid_key" => "Nombre de propiedad invalido",
+        "frontmatter.invalid_value" => "Valor invalido para {key}",
+        "frontmatter.missing" => "La nota no tiene la propiedad {key}",
+        "frontmatter.stale" => "La nota cambio en disco; guardala o recargala antes de editar sus propiedades",
+        "periodic.invalid_kind" => "Tipo de nota periodica desconocido: {value}",
+        "periodic.invalid_date" => "Fecha invalida: {value}",
+        "periodic.invalid_direction" => "Direccion invalida: {value}",
+        "periodic.out_of_range" => "Fecha fuera de rango",
+        "replace.empty_pattern" => "El patron de busqueda esta vacio",
+        "replace.invalid_regex" => "Expresion regular invalida",
+        "replace.file_changed" => "Estos archivos cambiaron desde la vista previa, vuelve a generarla: {files}",
+        "replace.nothing_to_undo" => "No hay nada que deshacer para ese reemplazo",
+        "replace.other_vault" => "El reemplazo pertenece a otro vault",
+        "replace.committed" => "Ya hubo un commit despues del reemplazo; no se puede deshacer",
+        "replace.undo_changed" => "Estos archivos se editaron despues del reemplazo: {files}",
+        "replace.restore_failed" => "Error al restaurar {path}",
+        "replace.invalid_undo" => "Deshacer no valido: {value}",
+        "tag.invalid" => "Tag invalido: {value}",
+        "tag.rewrite_failed" => "No se pudo actualizar el frontmatter de {path}",
+        "tag.rename_failed" => "Error al renombrar el tag en {path}",
+        "attachment.outside" => "La carpeta de adjuntos esta fuera del vault: {path}",
+        "watcher.failed" => "No se pudo observar el vault",
+
+        "git.not_a_repo" => "No es un repositorio git",
+        "git.auth" => "Error de autenticacion con el remoto",
+        "git.conflict" => "Hay conflictos sin resolver",
+        "git.no_upstream" => "La rama no sigue ninguna rama remota",
+        "git.nothing_to_commit" => "No hay cambios para commitear",
+        "git.empty_message" => "El mensaje del commit esta vacio",
+        "git.failed" => "Error de git",
+        "git.clone_failed" => "Error al clonar. Verifica la URL y tu autenticacion (SSH o HTTPS).",
+        "git.no_origin" => "El vault no tiene remoto origin",
+        "git.dirty" => "Hay cambios sin commitear; sincroniza antes de continuar",
+        "git.invalid_branch" => "Nombre de rama invalido: {value}",
+        "git.invalid_rev" => "Revision invalida: {value}",
+        "git.branch_not_found" => "No existe la rama {name}",
+        "git.delete_current" => "No se puede borrar la rama actual",
+        "git.not_merged" => "La rama {name} tiene commits sin mezclar",
+        "git.no_changes" => "{path} no tiene cambios",
+        "git.no_conflicts" => "{path} no tiene conflictos",
+        "git.unresolved" => "Quedan {count} archivos sin resolver",
+        "git.no_operation" => "No hay un rebase ni un merge en curso",
+        "git.unknown_action" => "Accion desconocida: {value}",
+        "git.diff_changed" => "El archivo cambio desde que se mostro el diff; vuelve a cargarlo",
+        "git.untracked_discard" => "Un archivo nuevo no se puede descartar por partes; eliminalo",
+        "git.no_hunks" => "No se eligio ningun hunk",
+        "git.apply_failed" => "No se pudo aplicar el hunk",
+        "git.missing_at" => "{path} no existe en {commit}",
+        "git.diff_from_commit" => "La version de origen debe ser un commit",
+        "history.stale" => "La nota cambio en disco; guardala o recargala antes de restaurar",
+        "autosync.inactive" => "La sincronizacion automatica no esta activa",
+
+        "clone.connecting" => "Conectando",
+        "clone.counting" => "Contando objetos",
+        "clone.compressing" => "Comprimiendo",
+        "clone.receiving" => "Recibiendo objetos",
+        "clone.resolving" => "Resolviendo deltas",
+        "clone.done" => "Completado",
+
+        "claude.not_installed" => {
+            "Claude Code no esta instalado. Instala con: curl -fsSL https://claude.ai/install.sh | sh"
+        }
+        "claude.spawn" => "No se pudo ejecutar claude",
+        "claude.exit" => "Claude salio con codigo: {code}",
+        "claude.no_process" => "No hay proceso activo para esa sesion",
+        "claude.command_not_found" => "No existe el comando {name}",
+        "mcp.config_write" => "Error escribiendo MCP config",
+
+        "update.downloading" => "Descargando",
+        "update.download_failed" => "Error al descargar la actualizacion. Verifica tu conexion a internet.",
+        "update.not_found" => "Archivo de actualizacion no encontrado.",
+        "update.cancelled" => "Instalacion cancelada por el usuario.",
+        "update.install_failed" => "Error al instalar",
+        "update.restart_failed" => "No se pudo reiniciar la app",
+
+        "cli.version_greeting" => "Hola José, aquí está la versión",
+        "cli.about" => "Editor de notas markdown con IA integrada",
+        "cli.usage" => "USO:",
+        "cli.usage_run" => "potato [OPCIONES]",
+        "cli.usage_commit_message" => "potato commit-message [VAULT]",
+        "cli.options" => "OPCIONES:",
+        "cli.commands" => "COMANDOS:",
+        "cli.opt_version" => "Muestra la versión",
+        "cli.opt_help" => "Muestra esta ayuda",
+        "cli.cmd_commit_message" => {
+            "Mensaje de commit para los cambios del vault (por defecto el directorio actual)"
+        }
+        _ => return None,
+    })
+}
+
+fn en(key: &str) -> Option<&'static str> {
+    Some(match key {
+        "internal.task" => "Internal task error",
+        "internal.json" => "Internal error while serializing data",
+        "io.failed" => "Input/output error",
+        "process.spawn" => "Could not run {program}",
+        "i18n.unsupported" => "Unsupported language: {value}",
+        "settings.unknown_key" => "Unknown setting: {key}",
+        "settings.invalid_value" => "Invalid value for {key}",
+        "settings.invalid_file" => "settings.json is not valid; it was saved as settings.invalid.json and defaults are used",
+        "settings.migrate_failed" => "Could not save the updated settings.json",
+        "vault_config.parse" => "Could not read .potato/config",
+        "vault_config.not_a_map" => ".potato/config must be a map of keys",
+        "vault_config.unknown_key" => "Unknown key in .potato/config: {key}",
+        "vault_config.invalid_value" => "Invalid value for {key} in .potato/config",
+
+        "dialog.open_vault" => "Select vault",
+        "dialog.pick_folder" => "Choose destination folder",
+
+        "vault.not_a_dir" => "Not a directory: {path}",
+        "vault.outside" => "The path is outside the vault: {path}",
+        "file.not_found" => "The file does not exist: {path}",
+        "file.dest_not_found" => "The destination folder does not exist: {path}",
+        "file.dest_exists" => "'{name}' already exists in the destination folder",
+        "file.invalid_name" => "Invalid file name: {value}",
+        "file.move_failed" => "Could not move the file",
+        "file.read_failed" => "Could not read {path}",
+        "note.not_utf8" => "The note is not valid UTF-8 text",
+        "note.invalid_name" => "Invalid name: {value}",
+        "note.exists" => "A note named '{name}' already exists",
+        "note.rename_failed" => "Could not rename",
+        "note.delete_failed" => "Could not delete",
+        "note.links_failed" => "Could not update links in {path}",
+        "note.write_failed" => "Could not write {path}",
+        "template.invalid" => "Invalid template: {value}",
+        "template.not_found" => "The template does not exist: {name}",
+        "frontmatter.invalid_yaml" => "The note's frontmatter is not valid YAML",
+        "frontmatter.invalid_key" => "Invalid property name",
+        "frontmatter.invalid_value" => "Invalid value for {key}",
+        "frontmatter.missing" => "The note has no property {key}",
+        "frontmatter.stale" => "The note changed on disk; save or reload it before editing its properties",
+        "periodic.invalid_kind" => "Unknown periodic note type: {value}",
+        "periodic.invalid_date" => "Invalid date: {value}",
+        "periodic.invalid_direction" => "Invalid direction: {value}",
+        "periodic.out_of_range" => "Date out of range",
+        "replace.empty_pattern" => "The search pattern is empty",
+        "replace.invalid_regex" => "Invalid regular expression",
+        "replace.file_changed" => "These files changed since the preview, generate it again: {files}",
+        "replace.nothing_to_undo" => "There is nothing to undo for that replacement",
+        "replace.other_vault" => "The replacement belongs to another vault",
+        "replace.committed" => "There was a commit after the replacement; it cannot be undone",
+        "replace.undo_changed" => "These files were edited after the replacement: {files}",
+        "replace.restore_failed" => "Could not restore {path}",
+        "replace.invalid_undo" => "Invalid undo: {value}",
+        "tag.invalid" => "Invalid tag: {value}",
+        "tag.rewrite_failed" => "Could not update the frontmatter of {path}",
+        "tag.rename_failed" => "Could not rename the tag in {path}",
+        "attachment.outside" => "The attachments folder is outside the vault: {path}",
+        "watcher.failed" => "Could not watch the vault",
+
+        "git.not_a_repo" => "Not a git repository",
+        "git.auth" => "Authentication with the remote failed",
+        "git.conflict" => "There are unresolved conflicts",
+        "git.no_upstream" => "The branch does not track any remote branch",
+        "git.nothing_to_commit" => "Nothing to commit",
+        "git.empty_message" => "The commit message is empty",
+        "git.failed" => "Git error",
+        "git.clone_failed" => "Clone failed. Check the URL and your authentication (SSH or HTTPS).",
+        "git.no_origin" => "The vault has no origin remote",
+        "git.dirty" => "There are uncommitted changes; sync before continuing",
+        "git.invalid_branch" => "Invalid branch name: {value}",
+        "git.invalid_rev" => "Invalid revision: {value}",
+        "git.branch_not_found" => "Branch {name} does not exist",
+        "git.delete_current" => "Cannot delete the current branch",
+        "git.not_merged" => "Branch {name} has unmerged commits",
+        "git.no_changes" => "{path} has no changes",
+        "git.no_conflicts" => "{path} has no conflicts",
+        "git.unresolved" => "{count} files are still unresolved",
+        "git.no_operation" => "No rebase or merge in progress",
+        "git.unknown_action" => "Unknown action: {value}",
+        "git.diff_changed" => "The file changed since the diff was shown; reload it",
+        "git.untracked_discard" => "A new file cannot be partially discarded; delete it",
+        "git.no_hunks" => "No hunk was selected",
+        "git.apply_failed" => "Could not apply the hunk",
+        "git.missing_at" => "{path} does not exist in {commit}",
+        "git.diff_from_commit" => "The source version must be a commit",
+        "history.stale" => "The note changed on disk; save or reload it before restoring",
+        "autosync.inactive" => "Automatic sync is not active",
+
+        "clone.connecting" => "Connecting",
+        "clone.counting" => "Counting objects",
+        "clone.compressing" => "Compressing",
+        "clone.receiving" => "Receiving objects",
+        "clone.resolving" => "Resolving deltas",
+        "clone.done" => "Done",
+
+        "claude.not_installed" => {
+            "Claude Code is not installed. Install it with: curl -fsSL https://claude.ai/install.sh | sh"
+        }
+        "claude.spawn" => "Could not run claude",
+        "claude.exit" => "Claude exited with code: {code}",
+        "claude.no_process" => "No active process for that session",
+        "claude.command_not_found" => "Command {name} does not exist",
+        "mcp.config_write" => "Could not write the MCP config",
+
+        "update.downloading" => "Downloading",
+        "update.download_failed" => "Could not download the update. Check your internet connection.",
+        "update.not_found" => "Update file not found.",
+        "update.cancelled" => "Installation cancelled by the user.",
+        "update.install_failed" => "Installation failed",
+        "update.restart_failed" => "Could not restart the app",
+
+        "cli.version_greeting" => "Hi José, here is the version",
+        "cli.about" => "Markdown notes editor with built-in AI",
+        "cli.usage" => "USAGE:",
+        "cli.usage_run" => "potato [OPTIONS]",
+        "cli.usage_commit_message" => "potato commit-message [VAULT]",
+        "cli.options" => "OPTIONS:",
+        "cli.commands" => "COMMANDS:",
+        "cli.opt_version" => "Show the version",
+        "cli.opt_help" => "Show this help",
+        "cli.cmd_commit_message" => "Commit message for the vault's changes (current directory by default)",
+        _ => return None,
+    })
+}
@@ -1,20 +1,50 @@
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::fs;
-use std::io::{BufRead, BufReader, Read as IoRead};
-use std::path::PathBuf;
+use std::io::{BufRead, BufReader, Read as IoRead, Write};
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::sync::Mutex;
 use tauri::ipc::Channel;
 use tauri::Emitter;
 use walkdir::WalkDir;
 
+use errors::{AppError, ErrorKind};
+use git_backend::GitError;
+
+mod attachments;
+mod autosync;
+mod branches;
+mod commit_message;
+mod conflicts;
+mod errors;
+mod frontmatter;
+mod git_backend;
+mod history;
+mod hunks;
+mod i18n;
+mod link_index;
+mod periodic;
+mod query;
+mod replace;
+mod search_index;
+mod settings;
+mod tags;
+mod templates;
+mod vault_config;
+mod vault_ignore;
+mod vault_index;
+mod watcher;
+mod wikilinks;
+
 #[derive(Serialize, Clone)]
 pub struct FileEntry {
     name: String,
     path: String,
     is_dir: bool,
+    kind: String, // "dir", "note" o el tipo de adjunto ("image", "pdf", ...)
     children: Vec<FileEntry>,
 }
 
@@ -46,55 +76,46 @@ struct McpScanResult {
 #[tauri::command]
 fn open_vault() -> Option<String> {
     rfd::FileDialog::new()
-        .set_title("Seleccionar Vault")
+        .set_title(&i18n::t("dialog.open_vault"))
         .pick_folder()
         .map(|p| p.to_string_lossy().to_string())
 }
 
+/// Arbol del vault. Con `attachments: true` incluye tambien imagenes, PDFs
+/// y demas adjuntos, ademas de las notas.
 #[tauri::command]
-fn list_vault(path: String) -> Result<Vec<FileEntry>, String> {
+fn list_vault(path: String, attachments: Option<bool>) -> Result<Vec<FileEntry>, AppError> {
     let root = PathBuf::from(&path);
     if !root.is_dir() {
-        return Err(format!("No es un directorio: {}", path));
+        return Err(AppError::not_a_dir(&path));
     }
-    Ok(build_tree(&root))
+    // .potato/config antes que las reglas de ignorados, que dependen de el
+    vault_config::load(&root);
+    vault_ignore::reload(&root, None);
+    link_index::warm_up(&root);
+    Ok(build_tree(&root, attachments.unwrap_or(false)))
 }
 
-fn build_tree(dir: &PathBuf) -> Vec<FileEntry> {
+fn build_tree(dir: &PathBuf, attachments: bool) -> Vec<FileEntry> {
     let Ok(read_dir) = fs::read_dir(dir) else {
         return vec![];
     };
 
     let mut dirs = Vec::new();
     let mut files = Vec::new();
+    let rules = vault_ignore::rules(dir);
 
     for entry in read_dir.flatten() {
-        let path = entry.path();
-        let name = entry.file_name().to_string_lossy().to_string();
-
-        // Ignorar archivos/dirs ocultos
-        if name.starts_with('.') {
+        // Ignorar ocultos y lo excluido por .potatoignore
+        let is_dir = entry.file_type().is_ok_and(|t| t.is_dir());
+        if rules.ignored(&entry.path(), is_dir) {
             continue;
         }
 
-        if path.is_dir() {
-            dirs.push(FileEntry {
-                name,
-                path: path.to_string_lossy().to_string(),
-                is_dir: true,
-                children: build_tree(&path),
-            });
-        } else if path.extension().is_some_and(|e| e == "md") {
-            files.push(FileEntry {
-                name: path
-                    .file_stem()
-                    .unwrap_or_default()
-                    .to_string_lossy()
-                    .to_string(),
-                path: path.to_string_lossy().to_string(),
-                is_dir: false,
-                children: vec![],
-            });
+        match file_entry(&entry.path(), attachments) {
+            Some(e) if e.is_dir => dirs.push(e),
+            Some(e) => files.push(e),
+            None => {}
         }
     }
 
@@ -104,32 +125,228 @@ fn build_tree(dir: &PathBuf) -> Vec<FileEntry> {
     dirs
 }
 
+/// Todas las notas `.md` del vault, con las mismas reglas que `build_tree`.
+fn vault_notes(root: &Path) -> Vec<PathBuf> {
+    vault_ignore::walk(root)
+        .filter(|e| e.file_type().is_file() && e.path().extension().is_some_and(|x| x == "md"))
+        .map(|e| e.into_path())
+        .collect()
+}
+
+/// Avisa a los indices del backend que `path` (nota o carpeta) cambio en disco.
+fn index_path_updated(path: &Path) {
+    if vault_ignore::is_ignored(path) {
+        index_path_removed(path);
+        return;
+    }
+    link_index::path_updated(path);
+    search_index::path_updated(path);
+    tags::path_updated(path);
+}
+
+/// Avisa a los indices del backend que `path` (nota o carpeta) ya no existe.
+fn index_path_removed(path: &Path) {
+    link_index::path_removed(path);
+    search_index::path_removed(path);
+    tags::path_removed(path);
+}
+
+/// Descarta los indices del vault (p. ej. al cambiar las reglas de ignorados);
+/// cada uno se reconstruye en su siguiente uso.
+fn index_vault_reset(vault: &Path) {
+    link_index::forget(vault);
+    search_index::forget(vault);
+    tags::forget(vault);
+}
+
+/// Construye la entrada del arbol para una carpeta (con sus hijos), una nota
+/// o, si `attachments`, un adjunto. Devuelve None para cualquier otro archivo.
+fn file_entry(path: &std::path::Path, attachments: bool) -> Option<FileEntry> {
+    if path.is_dir() {
+        Some(FileEntry {
+            name: path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+            path: path.to_string_lossy().to_string(),
+            is_dir: true,
+            kind: "dir".to_string(),
+            children: build_tree(&path.to_path_buf(), attachments),
+        })
+    } else if path.extension().is_some_and(|e| e == "md") {
+        Some(FileEntry {
+            name: path
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+            path: path.to_string_lossy().to_string(),
+            is_dir: false,
+            kind: "note".to_string(),
+            children: vec![],
+        })
+    } else {
+        let kind = attachments.then(|| attachments::attachment_kind(path)).flatten()?;
+        Some(FileEntry {
+            name: path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+            path: path.to_string_lossy().to_string(),
+            is_dir: false,
+            kind: kind.to_string(),
+            children: vec![],
+        })
+    }
+}
+
 // -- Comandos: Notas ---------------------------------------------------------
 
+#[derive(Serialize, Clone)]
+pub struct NoteContent {
+    content: String,
+    revision: String,
+}
+
+#[derive(Serialize, Clone)]
+pub struct SaveConflict {
+    disk_content: String,
+    local_content: String,
+}
+
+#[derive(Serialize, Clone)]
+pub struct SaveResult {
+    saved: bool,
+    revision: String, // revision en disco tras la operacion
+    #[serde(skip_serializing_if = "Option::is_none")]
+    conflict: Option<SaveConflict>,
+}
+
+/// Hash corto del contenido (sha256 truncado), suficiente para detectar cambios.
+fn content_hash(content: &[u8]) -> String {
+    let digest = Sha256::digest(content);
+    format!("{:x}", digest)[..16].to_string()
+}
+
+fn file_mtime_ms(path: &Path) -> u128 {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_millis())
+        .unwrap_or(0)
+}
+
+/// Token de revision: "mtime-hash". El mtime permite descartar cambios sin
+/// leer el archivo; el hash evita falsos conflictos si solo cambio el mtime.
+fn note_revision(path: &Path, content: &[u8]) -> String {
+    format!("{}-{}", file_mtime_ms(path), content_hash(content))
+}
+
+/// Escribe via archivo temporal + rename en la misma carpeta, para que un
+/// crash a mitad de escritura nunca deje la nota truncada.
+fn atomic_write(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let tmp = dir.join(format!(".{}.potato-tmp-{}", file_name, std::process::id()));
+
+    let write = || -> std::io::Result<()> {
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(content)?;
+        file.sync_all()?;
+        if let Ok(meta) = fs::metadata(path) {
+            fs::set_permissions(&tmp, meta.permissions())?;
+        }
+        fs::rename(&tmp, path)
+    };
+
+    write().inspect_err(|_| {
+        let _ = fs::remove_file(&tmp);
+    })
+}
+
 #[tauri::command]
-fn read_note(path: String) -> Result<String, String> {
-    fs::read_to_string(&path).map_err(|e| e.to_string())
+fn read_note(path: String) -> Result<NoteContent, AppError> {
+    let bytes = fs::read(&path)?;
+    let revision = note_revision(Path::new(&path), &bytes);
+    let content =
+        String::from_utf8(bytes).map_err(|e| AppError::new(ErrorKind::InvalidInput, "note.not_utf8").detail(e))?;
+    Ok(NoteContent { content, revision })
 }
 
+/// Guarda la nota de forma atomica. Si se pasa `expected_revision` (la que
+/// devolvio `read_note` o el ultimo guardado) y el archivo cambio en disco
+/// desde entonces, no se escribe y se devuelven ambas versiones.
 #[tauri::command]
-fn save_note(path: String, content: String) -> Result<(), String> {
-    fs::write(&path, &content).map_err(|e| e.to_string())
+fn save_note(path: String, content: String, expected_revision: Option<String>) -> Result<SaveResult, AppError> {
+    let file_path = Path::new(&path);
+
+    if let Some(expected) = expected_revision {
+        if file_path.exists() {
+            let (expected_mtime, expected_hash) = expected.split_once('-').unwrap_or(("", ""));
+            if expected_mtime != file_mtime_ms(file_path).to_string() {
+                let disk = fs::read(file_path)?;
+                if content_hash(&disk) != expected_hash {
+                    return Ok(SaveResult {
+                        saved: false,
+                        revision: note_revision(file_path, &disk),
+                        conflict: Some(SaveConflict {
+                            disk_content: String::from_utf8_lossy(&disk).to_string(),
+                            local_content: content,
+                        }),
+                    });
+                }
+            }
+        }
+    }
+
+    atomic_write(file_path, content.as_bytes())?;
+    index_path_updated(file_path);
+    Ok(SaveResult {
+        saved: true,
+        revision: note_revision(file_path, content.as_bytes()),
+        conflict: None,
+    })
 }
 
+#[derive(Serialize, Clone)]
+pub struct CreatedNote {
+    path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cursor: Option<u32>, // posicion del `{{cursor}}` de la plantilla, en UTF-16
+}
+
+/// Crea la nota `name`, vacia con titulo o a partir de una plantilla de la
+/// carpeta de plantillas del vault.
 #[tauri::command]
-fn create_note(vault_path: String, name: String) -> Result<String, String> {
-    let file_path = PathBuf::from(&vault_path).join(format!("{}.md", name));
-    let content = format!("# {}\n\n", name);
-    fs::write(&file_path, &content).map_err(|e| e.to_string())?;
-    Ok(file_path.to_string_lossy().to_string())
+fn create_note(vault_path: String, name: String, template: Option<String>) -> Result<CreatedNote, AppError> {
+    let vault = PathBuf::from(&vault_path);
+    let file_path = vault.join(format!("{}.md", name));
+
+    let (content, cursor) = match template.as_deref().filter(|t| !t.is_empty()) {
+        Some(template) => {
+            let title = file_path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+            templates::render_template(template, &templates::TemplateContext::now(&title, &vault))?
+        }
+        None => (format!("# {}\n\n", name), None),
+    };
+
+    fs::write(&file_path, &content)?;
+    index_path_updated(&file_path);
+    Ok(CreatedNote {
+        path: file_path.to_string_lossy().to_string(),
+        cursor,
+    })
 }
 
 // -- Comandos: Wikilinks -----------------------------------------------------
 
 #[tauri::command]
 fn parse_wikilinks(content: String) -> Vec<WikiLink> {
-    let re = Regex::new(r"\[\[([^\]|]+)(?:\|([^\]]+))?\]\]").unwrap();
-    re.captures_iter(&content)
+    wikilinks::wikilink_regex()
+        .captures_iter(&content)
         .map(|cap| WikiLink {
             target: cap[1].to_string(),
             display: cap
@@ -142,20 +359,33 @@ fn parse_wikilinks(content: String) -> Vec<WikiLink> {
 
 // -- Comandos: Git -----------------------------------------------------------
 
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Clone, Default)]
 pub struct GitStatus {
     is_repo: bool,
     has_changes: bool,
     branch: String,
     remote: String,
+    upstream: Option<String>, // p. ej. "origin/main"; None si la rama no sigue ninguna
+    ahead: u32,               // commits locales sin subir
+    behind: u32,              // commits del upstream sin bajar
+    detached: bool,
+    operation: String, // "rebase", "merge" o "none"
+    stash_count: u32,
+    last_fetch: Option<u64>, // ms desde epoch del ultimo fetch/pull
+    staged: u32,
+    unstaged: u32,
+    untracked: u32,
+    conflicted: u32,
 }
 
 /// Helper: crea un Command de git que no se cuelga esperando autenticacion.
 /// GIT_TERMINAL_PROMPT=0 evita prompts HTTPS.
 /// SSH BatchMode=yes evita prompts de passphrase SSH.
+/// LC_ALL=C deja los mensajes de git en ingles para clasificarlos.
 fn git_cmd(repo_path: &str) -> std::process::Command {
     let mut cmd = std::process::Command::new("git");
     cmd.args(["-C", repo_path]);
+    cmd.env("LC_ALL", "C");
     cmd.env("GIT_TERMINAL_PROMPT", "0");
     cmd.env("GIT_SSH_COMMAND", "ssh -o BatchMode=yes -o StrictHostKeyChecking=accept-new");
     cmd
@@ -164,6 +394,7 @@ fn git_cmd(repo_path: &str) -> std::process::Command {
 /// Helper: git command sin -C (para clone)
 fn git_cmd_bare() -> std::process::Command {
     let mut cmd = std::process::Command::new("git");
+    cmd.env("LC_ALL", "C");
     cmd.env("GIT_TERMINAL_PROMPT", "0");
     cmd.env("GIT_SSH_COMMAND", "ssh -o BatchMode=yes -o StrictHostKeyChecking=accept-new");
     cmd
@@ -176,7 +407,7 @@ struct GitProgress {
 }
 
 #[tauri::command]
-async fn git_clone(app: tauri::AppHandle, url: String, path: String) -> Result<String, String> {
+async fn git_clone(app: tauri::AppHandle, url: String, path: String) -> Result<String, AppError> {
     let app_handle = app.clone();
     tokio::task::spawn_blocking(move || {
         let mut child = git_cmd_bare()
@@ -184,9 +415,10 @@ async fn git_clone(app: tauri::AppHandle, url: String, path: String) -> Result<S
             .stderr(Stdio::piped())
             .stdout(Stdio::piped())
             .spawn()
-            .map_err(|e| format!("No se pudo ejecutar git: {}", e))?;
+            .map_err(|e| AppError::spawn("git", e))?;
 
         // Read stderr byte by byte (git uses \r for progress)
+        let mut errors = String::new();
         if let Some(stderr) = child.stderr.take() {
             let re = Regex::new(r"(\d+)%").unwrap();
             let mut line_buf = String::new();
@@ -194,17 +426,23 @@ async fn git_clone(app: tauri::AppHandle, url: String, path: String) -> Result<S
             for byte in stderr.bytes().flatten() {
                 if byte == b'\r' || byte == b'\n' {
                     if !line_buf.is_empty() {
+                        // Lo que no es progreso es el error, si falla
+                        if ["fatal:", "remote:", "error:"].iter().any(|p| line_buf.starts_with(p)) {
+                            errors.push_str(&line_buf);
+                            errors.push('\n');
+                        }
+
                         // Parse phase and percent
                         let phase = if line_buf.contains("Counting") {
-                            "Contando objetos"
+                            "clone.counting"
                         } else if line_buf.contains("Compressing") {
-                            "Comprimiendo"
+                            "clone.compressing"
                         } else if line_buf.contains("Receiving") {
-                            "Recibiendo objetos"
+                            "clone.receiving"
                         } else if line_buf.contains("Resolving") {
-                            "Resolviendo deltas"
+                            "clone.resolving"
                         } else if line_buf.contains("Cloning") {
-                            "Conectando"
+                            "clone.connecting"
                         } else {
                             ""
                         };
@@ -216,16 +454,16 @@ async fn git_clone(app: tauri::AppHandle, url: String, path: String) -> Result<S
 
                             // Map phases to overall progress
                             let overall = match phase {
-                                "Conectando" => 2,
-                                "Contando objetos" => 5 + percent / 10,
-                                "Comprimiendo" => 15 + percent / 5,
-                                "Recibiendo objetos" => 35 + (percent * 50 / 100),
-                                "Resolviendo deltas" => 85 + (percent * 15 / 100),
+                                "clone.connecting" => 2,
+                                "clone.counting" => 5 + percent / 10,
+                                "clone.compressing" => 15 + percent / 5,
+                                "clone.receiving" => 35 + (percent * 50 / 100),
+                                "clone.resolving" => 85 + (percent * 15 / 100),
                                 _ => percent,
                             };
 
                             let _ = app_handle.emit("git-progress", GitProgress {
-                                phase: phase.to_string(),
+                                phase: i18n::t(phase),
                                 percent: overall.min(100),
                             });
                         }
@@ -238,70 +476,54 @@ async fn git_clone(app: tauri::AppHandle, url: String, path: String) -> Result<S
             }
         }
 
-        let status = child.wait().map_err(|e| format!("Error esperando git: {}", e))?;
+        let status = child.wait()?;
 
         // Emit 100% done
         let _ = app_handle.emit("git-progress", GitProgress {
-            phase: "Completado".to_string(),
+            phase: i18n::t("clone.done"),
             percent: 100,
         });
 
         if status.success() {
             Ok(path)
         } else {
-            Err("Error al clonar. Verifica la URL y tu autenticacion (SSH o HTTPS).".to_string())
+            Err(match GitError::from_cli(&errors) {
+                GitError::Auth => GitError::Auth.into(),
+                _ => AppError::new(ErrorKind::Git, "git.clone_failed").detail(errors.trim()),
+            })
         }
     })
-    .await
-    .map_err(|e| format!("Task error: {}", e))?
+    .await?
 }
 
-#[tauri::command]
-async fn git_status(path: String) -> Result<GitStatus, String> {
-    tokio::task::spawn_blocking(move || {
-        // Check if it's a git repo
-        let is_repo = std::process::Command::new("git")
-            .args(["-C", &path, "rev-parse", "--is-inside-work-tree"])
-            .output()
-            .map(|o| o.status.success())
-            .unwrap_or(false);
-
-        if !is_repo {
-            return Ok(GitStatus {
-                is_repo: false,
-                has_changes: false,
-                branch: String::new(),
-                remote: String::new(),
-            });
+/// Directorio de git del repo (`.git`, o el que apunta un archivo `.git` en
+/// worktrees y submodulos), sin lanzar git.
+fn git_dir(repo_path: &str) -> Option<PathBuf> {
+    for dir in Path::new(repo_path).ancestors() {
+        let dot_git = dir.join(".git");
+        if dot_git.is_dir() {
+            return Some(dot_git);
         }
+        if let Ok(content) = fs::read_to_string(&dot_git) {
+            let target = PathBuf::from(content.strip_prefix("gitdir:")?.trim());
+            return Some(if target.is_absolute() { target } else { dir.join(target) });
+        }
+    }
+    None
+}
 
-        let branch = std::process::Command::new("git")
-            .args(["-C", &path, "branch", "--show-current"])
-            .output()
-            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
-            .unwrap_or_default();
-
-        let remote = std::process::Command::new("git")
-            .args(["-C", &path, "remote", "get-url", "origin"])
-            .output()
-            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
-            .unwrap_or_default();
-
-        let has_changes = std::process::Command::new("git")
-            .args(["-C", &path, "status", "--porcelain"])
-            .output()
-            .map(|o| !o.stdout.is_empty())
-            .unwrap_or(false);
-
-        Ok(GitStatus {
-            is_repo,
-            has_changes,
-            branch,
-            remote,
-        })
+/// Estado del repo. Fuera de un repo devuelve `is_repo: false`; cualquier
+/// otro fallo (index corrupto, lock, git ausente) es un error.
+#[tauri::command]
+async fn git_status(path: String) -> Result<GitStatus, AppError> {
+    tokio::task::spawn_blocking(move || match git_backend::backend().status(&path) {
+        Err(GitError::NotARepo) => Ok(GitStatus {
+            operation: "none".to_string(),
+            ..Default::default()
+        }),
+        result => Ok(result?),
     })
-    .await
-    .map_err(|e| format!("Task error: {}", e))?
+    .await?
 }
 
 #[derive(Serialize, Clone)]
@@ -311,152 +533,125 @@ pub struct GitFileChange {
     status_code: String,  // "M", "?", "D", "R", "A"
 }
 
-#[tauri::command]
-async fn git_pull(path: String) -> Result<String, String> {
-    tokio::task::spawn_blocking(move || {
-        let pull = git_cmd(&path)
-            .args(["pull", "--rebase", "--autostash"])
-            .output()
-            .map_err(|e| format!("Error en pull: {}", e))?;
+/// Resultado de `git_pull`.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PullOutcome {
+    UpToDate,
+    /// La rama no sigue ninguna remota: se crea al hacer push.
+    NoRemoteBranch,
+    Updated,
+}
 
-        if pull.status.success() {
-            let stdout = String::from_utf8_lossy(&pull.stdout).trim().to_string();
-            if stdout.contains("Already up to date") || stdout.contains("Current branch") {
-                Ok("already_up_to_date".to_string())
-            } else {
-                Ok(stdout)
-            }
+/// `pull --rebase --autostash`. Un rebase detenido falla con
+/// `ErrorKind::Conflict`.
+fn pull_rebase(path: &str) -> Result<PullOutcome, AppError> {
+    let pull = git_cmd(path)
+        .args(["pull", "--rebase", "--autostash"])
+        .output()
+        .map_err(|e| AppError::spawn("git", e))?;
+
+    if pull.status.success() {
+        let stdout = String::from_utf8_lossy(&pull.stdout);
+        if stdout.contains("Already up to date") || stdout.contains("Current branch") {
+            Ok(PullOutcome::UpToDate)
         } else {
-            let stderr = String::from_utf8_lossy(&pull.stderr).to_string();
-            if stderr.contains("no tracking information") || stderr.contains("no such ref") {
-                Ok("no_remote_branch".to_string())
-            } else if stderr.contains("Authentication failed")
-                || stderr.contains("Permission denied")
-                || stderr.contains("terminal prompts disabled")
-                || stderr.contains("could not read Username")
-            {
-                Err("auth_error".to_string())
-            } else {
-                Err(stderr)
-            }
+            Ok(PullOutcome::Updated)
         }
-    })
-    .await
-    .map_err(|e| format!("Task error: {}", e))?
+    } else {
+        // Rebase detenido por conflictos: se resuelven con conflicts::*
+        if conflicts::operation_in_progress(path).is_some() && !conflicts::conflicted_paths(path).is_empty() {
+            return Err(GitError::Conflict.into());
+        }
+        match GitError::from_cli(&String::from_utf8_lossy(&pull.stderr)) {
+            GitError::NoUpstream => Ok(PullOutcome::NoRemoteBranch),
+            e => Err(e.into()),
+        }
+    }
 }
 
 #[tauri::command]
-async fn git_changed_files(path: String) -> Result<Vec<GitFileChange>, String> {
-    tokio::task::spawn_blocking(move || {
-        let output = git_cmd(&path)
-            .args(["status", "--porcelain"])
-            .output()
-            .map_err(|e| format!("Error en status: {}", e))?;
-
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let mut files = Vec::new();
+async fn git_pull(path: String) -> Result<PullOutcome, AppError> {
+    tokio::task::spawn_blocking(move || autosync::exclusive(&path, || pull_rebase(&path)))
+        .await?
+}
 
-        for line in stdout.lines() {
-            if line.len() < 3 {
-                continue;
-            }
-            let code = line[..2].trim().to_string();
-            let file_path = line[3..].trim_start_matches("-> ").to_string();
-
-            let (status, status_code) = match code.as_str() {
-                "M" | "MM" => ("modified".to_string(), "M".to_string()),
-                "A" | "AM" => ("new (staged)".to_string(), "A".to_string()),
-                "??" => ("new".to_string(), "?".to_string()),
-                "D" => ("deleted".to_string(), "D".to_string()),
-                "R" | "RM" => ("renamed".to_string(), "R".to_string()),
-                "C" => ("copied".to_string(), "C".to_string()),
-                _ => (format!("changed ({})", code), code.clone()),
-            };
+/// Entrada de `git_changed_files` a partir de las letras `XY` de
+/// `git status --porcelain`, sin espacios.
+fn file_change(code: &str, path: String) -> GitFileChange {
+    let (status, status_code) = match code {
+        "M" | "MM" => ("modified".to_string(), "M".to_string()),
+        "A" | "AM" => ("new (staged)".to_string(), "A".to_string()),
+        "??" => ("new".to_string(), "?".to_string()),
+        "D" => ("deleted".to_string(), "D".to_string()),
+        "R" | "RM" => ("renamed".to_string(), "R".to_string()),
+        "C" => ("copied".to_string(), "C".to_string()),
+        _ => (format!("changed ({})", code), code.to_string()),
+    };
+    GitFileChange { path, status, status_code }
+}
 
-            files.push(GitFileChange {
-                path: file_path,
-                status,
-                status_code,
-            });
-        }
+fn changed_files(path: &str) -> Result<Vec<GitFileChange>, AppError> {
+    Ok(git_backend::backend().changed_files(path)?)
+}
 
-        Ok(files)
-    })
-    .await
-    .map_err(|e| format!("Task error: {}", e))?
+#[tauri::command]
+async fn git_changed_files(path: String) -> Result<Vec<GitFileChange>, AppError> {
+    tokio::task::spawn_blocking(move || changed_files(&path))
+        .await?
 }
 
 #[tauri::command]
-async fn git_stage_files(path: String, files: Vec<String>) -> Result<(), String> {
+async fn git_stage_files(path: String, files: Vec<String>) -> Result<(), AppError> {
     tokio::task::spawn_blocking(move || {
-        let mut args = vec!["add", "--"];
-        let file_refs: Vec<&str> = files.iter().map(|s| s.as_str()).collect();
-        args.extend(file_refs);
-
-        let output = git_cmd(&path)
-            .args(&args)
-            .output()
-            .map_err(|e| format!("Error en git add: {}", e))?;
-
-        if output.status.success() {
-            Ok(())
-        } else {
-            Err(format!("Error en git add: {}", String::from_utf8_lossy(&output.stderr)))
-        }
+        autosync::exclusive(&path, || Ok(git_backend::backend().stage(&path, &files)?))
     })
-    .await
-    .map_err(|e| format!("Task error: {}", e))?
+    .await?
 }
 
 #[tauri::command]
-async fn git_commit(path: String, message: String) -> Result<String, String> {
+async fn git_commit(path: String, message: String) -> Result<String, AppError> {
     tokio::task::spawn_blocking(move || {
-        let output = git_cmd(&path)
-            .args(["commit", "-m", &message])
-            .output()
-            .map_err(|e| format!("Error en commit: {}", e))?;
-
-        if output.status.success() {
-            Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
-        } else {
-            Err(format!("Error en commit: {}", String::from_utf8_lossy(&output.stderr)))
-        }
+        autosync::exclusive(&path, || Ok(git_backend::backend().commit(&path, &message)?))
     })
-    .await
-    .map_err(|e| format!("Task error: {}", e))?
+    .await?
 }
 
-#[tauri::command]
-async fn git_push(path: String) -> Result<String, String> {
-    tokio::task::spawn_blocking(move || {
-        let output = git_cmd(&path)
-            .args(["push", "-u", "origin", "HEAD"])
-            .output()
-            .map_err(|e| format!("Error en push: {}", e))?;
+/// Resultado de `git_push`.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PushOutcome {
+    UpToDate,
+    Pushed,
+}
 
-        if output.status.success() {
-            Ok("Push OK".to_string())
-        } else {
-            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-            if stderr.contains("Authentication failed")
-                || stderr.contains("Permission denied")
-                || stderr.contains("terminal prompts disabled")
-                || stderr.contains("could not read Username")
-            {
-                Err("auth_error".to_string())
-            } else {
-                Err(stderr)
-            }
-        }
-    })
-    .await
-    .map_err(|e| format!("Task error: {}", e))?
+fn push_head(path: &str) -> Result<PushOutcome, AppError> {
+    let output = git_cmd(path)
+        .args(["push", "-u", "origin", "HEAD"])
+        .output()
+        .map_err(|e| AppError::spawn("git", e))?;
+
+    // git informa del resultado por stderr
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        Err(GitError::from_cli(&stderr).into())
+    } else if stderr.contains("Everything up-to-date") {
+        Ok(PushOutcome::UpToDate)
+    } else {
+        Ok(PushOutcome::Pushed)
+    }
+}
+
+#[tauri::command]
+async fn git_push(path: String) -> Result<PushOutcome, AppError> {
+    tokio::task::spawn_blocking(move || autosync::exclusive(&path, || push_head(&path)))
+        .await?
 }
 
 #[tauri::command]
 fn pick_folder() -> Option<String> {
     rfd::FileDialog::new()
-        .set_title("Elegir carpeta destino")
+        .set_title(&i18n::t("dialog.pick_folder"))
         .pick_folder()
         .map(|p| p.to_string_lossy().to_string())
 }
@@ -470,76 +665,16 @@ pub struct SearchResult {
     match_type: String,  // "name" or "content"
     preview: String,     // snippet with context
     line: u32,           // line number (0 for name matches)
+    score: f32,          // BM25 de la nota (mayor = mas relevante)
+    matches: Vec<search_index::MatchRange>, // rangos resaltados en preview (o en name)
 }
 
+/// Busqueda sobre el indice persistente del vault: ranking BM25, sin
+/// distinguir acentos, frases entre comillas y el ultimo termino como prefijo.
 #[tauri::command]
-async fn search_vault(path: String, query: String) -> Result<Vec<SearchResult>, String> {
-    tokio::task::spawn_blocking(move || {
-        let query_lower = query.to_lowercase();
-        let mut results = Vec::new();
-        search_dir(&PathBuf::from(&path), &query_lower, &mut results);
-        // Name matches first, then content matches
-        results.sort_by(|a, b| {
-            let type_ord = if a.match_type == "name" { 0u8 } else { 1 };
-            let type_ord_b = if b.match_type == "name" { 0u8 } else { 1 };
-            type_ord.cmp(&type_ord_b).then(a.name.cmp(&b.name))
-        });
-        // Limit results
-        results.truncate(50);
-        Ok(results)
-    })
-    .await
-    .map_err(|e| format!("Task error: {}", e))?
-}
-
-fn search_dir(dir: &PathBuf, query: &str, results: &mut Vec<SearchResult>) {
-    let Ok(read_dir) = fs::read_dir(dir) else { return };
-
-    for entry in read_dir.flatten() {
-        let path = entry.path();
-        let name = entry.file_name().to_string_lossy().to_string();
-
-        if name.starts_with('.') {
-            continue;
-        }
-
-        if path.is_dir() {
-            search_dir(&path, query, results);
-        } else if path.extension().is_some_and(|e| e == "md") {
-            let stem = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
-
-            // Match file name
-            if stem.to_lowercase().contains(query) {
-                results.push(SearchResult {
-                    path: path.to_string_lossy().to_string(),
-                    name: stem.clone(),
-                    match_type: "name".to_string(),
-                    preview: String::new(),
-                    line: 0,
-                });
-            }
-
-            // Match content
-            if let Ok(content) = fs::read_to_string(&path) {
-                for (i, line_text) in content.lines().enumerate() {
-                    if line_text.to_lowercase().contains(query) {
-                        let preview = line_text.trim().chars().take(120).collect::<String>();
-                        results.push(SearchResult {
-                            path: path.to_string_lossy().to_string(),
-                            name: stem.clone(),
-                            match_type: "content".to_string(),
-                            preview,
-                            line: (i + 1) as u32,
-                        });
-                        // Max 3 content matches per file
-                        if results.iter().filter(|r| r.path == path.to_string_lossy().to_string() && r.match_type == "content").count() >= 3 {
-                            break;
-                        }
-                    }
-                }
-            }
-        }
-    }
+async fn search_vault(path: String, query: String) -> Result<Vec<SearchResult>, AppError> {
+    tokio::task::spawn_blocking(move || Ok(search_index::search(Path::new(&path), &query)))
+        .await?
 }
 
 // -- Comandos: Session -------------------------------------------------------
@@ -558,13 +693,13 @@ fn session_path() -> PathBuf {
 }
 
 #[tauri::command]
-fn save_session(vault_path: Option<String>, note_path: Option<String>, note_title: Option<String>) -> Result<(), String> {
+fn save_session(vault_path: Option<String>, note_path: Option<String>, note_title: Option<String>) -> Result<(), AppError> {
     let dir = session_path();
-    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    fs::create_dir_all(&dir)?;
 
     let session = Session { vault_path, note_path, note_title };
-    let json = serde_json::to_string_pretty(&session).map_err(|e| e.to_string())?;
-    fs::write(dir.join("session.json"), json).map_err(|e| e.to_string())
+    let json = serde_json::to_string_pretty(&session)?;
+    Ok(fs::write(dir.join("session.json"), json)?)
 }
 
 #[tauri::command]
@@ -577,51 +712,195 @@ fn load_session() -> Option<Session> {
 // -- Comandos: Archivos ------------------------------------------------------
 
 #[tauri::command]
-fn move_file(from: String, to_dir: String) -> Result<String, String> {
+fn move_file(from: String, to_dir: String) -> Result<String, AppError> {
     let from_path = PathBuf::from(&from);
 
     if !from_path.exists() {
-        return Err(format!("El archivo no existe: {}", from));
+        return Err(AppError::file_not_found(&from));
     }
 
     let file_name = from_path
         .file_name()
-        .ok_or("No se pudo obtener el nombre del archivo")?
+        .ok_or_else(|| AppError::invalid("file.invalid_name", &from))?
         .to_string_lossy()
         .to_string();
 
     let dest_dir = PathBuf::from(&to_dir);
     if !dest_dir.is_dir() {
-        return Err(format!("La carpeta destino no existe: {}", to_dir));
+        return Err(AppError::new(ErrorKind::NotFound, "file.dest_not_found").param("path", &to_dir));
     }
 
     let dest_path = dest_dir.join(&file_name);
 
     if dest_path.exists() {
-        return Err(format!("Ya existe '{}' en la carpeta destino", file_name));
+        return Err(AppError::new(ErrorKind::AlreadyExists, "file.dest_exists").param("name", &file_name));
     }
 
     fs::rename(&from_path, &dest_path)
-        .map_err(|e| format!("Error al mover archivo: {}", e))?;
+        .map_err(|e| AppError::new(ErrorKind::Io, "file.move_failed").detail(e))?;
+
+    index_path_removed(&from_path);
+    index_path_updated(&dest_path);
 
     Ok(dest_path.to_string_lossy().to_string())
 }
 
+#[derive(Serialize, Clone)]
+pub struct NoteOperation {
+    path: String,               // ruta nueva (rename) o la eliminada (delete)
+    changed_files: Vec<String>, // relativas al vault, listas para git_stage_files
+}
+
+/// Nota a reescribir: ruta donde escribirla, contenido original y nuevo.
+type LinkRewrite = (PathBuf, String, String);
+
+/// Calcula como quedan las notas que enlazan a `old_path` si pasa a
+/// `new_path` (o se borra, con `None`), sin escribir nada. Con `new_path` la
+/// propia nota se incluye ya en su ruta nueva, por sus links a si misma.
+fn plan_link_rewrites(vault: &Path, old_path: &Path, new_path: Option<&Path>) -> Vec<LinkRewrite> {
+    let notes = vault_notes(vault);
+    let old = wikilinks::LinkName::new(vault, old_path);
+    let new = new_path.map(|p| wikilinks::LinkName::new(vault, p));
+
+    // Si otra nota comparte el nombre, `[[nombre]]` es ambiguo: solo se tocan links con ruta
+    let by_stem = !notes.iter().any(|n| {
+        n != old_path && n.file_stem().is_some_and(|s| s.to_string_lossy() == old.stem)
+    });
+
+    let mut pending = Vec::new();
+    for note in &notes {
+        let target = match new_path {
+            _ if note != old_path => note.clone(),
+            Some(dest) => dest.to_path_buf(),
+            None => continue,
+        };
+        let Ok(content) = fs::read_to_string(note) else { continue };
+        if let Some(updated) = wikilinks::rewrite_links(&content, &old, new.as_ref(), by_stem) {
+            pending.push((target, content, updated));
+        }
+    }
+    pending
+}
+
+/// Escribe las notas de `plan_link_rewrites`; si una escritura falla,
+/// restaura las ya escritas para no dejar el vault a medias.
+fn apply_link_rewrites(pending: &[LinkRewrite]) -> Result<Vec<PathBuf>, AppError> {
+    for (i, (note, _, updated)) in pending.iter().enumerate() {
+        if let Err(e) = atomic_write(note, updated.as_bytes()) {
+            for (done, original, _) in &pending[..i] {
+                let _ = atomic_write(done, original.as_bytes());
+            }
+            return Err(AppError::new(ErrorKind::Io, "note.links_failed")
+                .param("path", note.display())
+                .detail(e));
+        }
+    }
+    Ok(pending.iter().map(|(note, _, _)| note.clone()).collect())
+}
+
+fn vault_relative(vault: &Path, path: &Path) -> String {
+    path.strip_prefix(vault)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+#[tauri::command]
+fn rename_note(vault_path: String, path: String, new_name: String) -> Result<NoteOperation, AppError> {
+    let vault = PathBuf::from(&vault_path);
+    let from_path = PathBuf::from(&path);
+
+    if !from_path.is_file() {
+        return Err(AppError::file_not_found(&path));
+    }
+
+    let new_name = new_name.trim().trim_end_matches(".md");
+    if new_name.is_empty() || new_name.contains('/') || new_name.contains('\\') || new_name.starts_with('.') {
+        return Err(AppError::invalid("note.invalid_name", new_name));
+    }
+
+    let dest_path = from_path.with_file_name(format!("{}.md", new_name));
+    if dest_path == from_path {
+        return Ok(NoteOperation { path, changed_files: vec![] });
+    }
+    if dest_path.exists() {
+        return Err(AppError::new(ErrorKind::AlreadyExists, "note.exists").param("name", new_name));
+    }
+
+    // Primero se renombra y luego se tocan los links: si el rename falla no
+    // queda ningun link apuntando a una nota que no existe
+    let pending = plan_link_rewrites(&vault, &from_path, Some(&dest_path));
+    fs::rename(&from_path, &dest_path)
+        .map_err(|e| AppError::new(ErrorKind::Io, "note.rename_failed").detail(e))?;
+    let rewritten = apply_link_rewrites(&pending).inspect_err(|_| {
+        let _ = fs::rename(&dest_path, &from_path);
+    })?;
+
+    index_path_removed(&from_path);
+    index_path_updated(&dest_path);
+    rewritten.iter().for_each(|p| index_path_updated(p));
+
+    let mut changed_files = vec![
+        vault_relative(&vault, &from_path),
+        vault_relative(&vault, &dest_path),
+    ];
+    changed_files.extend(rewritten.iter().filter(|p| **p != dest_path).map(|p| vault_relative(&vault, p)));
+
+    Ok(NoteOperation {
+        path: dest_path.to_string_lossy().to_string(),
+        changed_files,
+    })
+}
+
+/// Elimina la nota. Con `unlink` los links que apuntaban a ella se convierten
+/// en texto plano (su alias o el nombre), para no dejar links rotos.
+#[tauri::command]
+fn delete_note(vault_path: String, path: String, unlink: Option<bool>) -> Result<NoteOperation, AppError> {
+    let vault = PathBuf::from(&vault_path);
+    let note_path = PathBuf::from(&path);
+
+    if !note_path.is_file() {
+        return Err(AppError::file_not_found(&path));
+    }
+
+    let pending = if unlink.unwrap_or(false) {
+        plan_link_rewrites(&vault, &note_path, None)
+    } else {
+        vec![]
+    };
+
+    // Se borra antes de tocar los links; si reescribirlos falla, la nota se
+    // vuelve a escribir con su contenido original
+    let original = fs::read(&note_path)?;
+    fs::remove_file(&note_path).map_err(|e| AppError::new(ErrorKind::Io, "note.delete_failed").detail(e))?;
+    let rewritten = apply_link_rewrites(&pending).inspect_err(|_| {
+        let _ = atomic_write(&note_path, &original);
+    })?;
+
+    index_path_removed(&note_path);
+    rewritten.iter().for_each(|p| index_path_updated(p));
+
+    let mut changed_files = vec![vault_relative(&vault, &note_path)];
+    changed_files.extend(rewritten.iter().map(|p| vault_relative(&vault, p)));
+
+    Ok(NoteOperation { path, changed_files })
+}
+
 // -- Comandos: Sistema -------------------------------------------------------
 
 #[tauri::command]
-fn open_in_explorer(path: String) -> Result<(), String> {
+fn open_in_explorer(path: String) -> Result<(), AppError> {
     std::process::Command::new("xdg-open")
         .arg(&path)
         .spawn()
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| AppError::spawn("xdg-open", e))?;
     Ok(())
 }
 
 // -- MCP: Escanear servidores ------------------------------------------------
 
 #[tauri::command]
-async fn scan_mcp_servers() -> Result<McpScanResult, String> {
+async fn scan_mcp_servers() -> Result<McpScanResult, AppError> {
     tokio::task::spawn_blocking(|| {
         let home = std::env::var("HOME").unwrap_or_default();
         if home.is_empty() {
@@ -636,6 +915,8 @@ async fn scan_mcp_servers() -> Result<McpScanResult, String> {
         ].iter().cloned().collect();
 
         let mut servers = Vec::new();
+        // Reglas de los .potatoignore encontrados por el camino
+        let mut ignore_rules: Vec<vault_ignore::IgnoreRules> = Vec::new();
 
         for entry in WalkDir::new(&home)
             .max_depth(6)
@@ -645,10 +926,16 @@ async fn scan_mcp_servers() -> Result<McpScanResult, String> {
                 if e.file_type().is_dir() {
                     let name = e.file_name().to_string_lossy();
                     // Permitir el home dir raiz
-                    if e.depth() == 0 { return true; }
-                    // Excluir directorios ocultos (excepto .mcp.json que es archivo)
-                    if name.starts_with('.') && name != ".mcp.json" { return false; }
-                    !exclude_dirs.contains(name.as_ref())
+                    if e.depth() > 0 {
+                        // Excluir directorios ocultos (excepto .mcp.json que es archivo)
+                        if name.starts_with('.') && name != ".mcp.json" { return false; }
+                        if exclude_dirs.contains(name.as_ref()) { return false; }
+                        if ignore_rules.iter().any(|r| r.ignored(e.path(), true)) { return false; }
+                    }
+                    if e.path().join(vault_ignore::IGNORE_FILE).is_file() {
+                        ignore_rules.push(vault_ignore::IgnoreRules::load(e.path(), false));
+                    }
+                    true
                 } else {
                     true
                 }
@@ -728,8 +1015,7 @@ async fn scan_mcp_servers() -> Result<McpScanResult, String> {
         let elapsed = start.elapsed().as_millis() as u64;
         Ok(McpScanResult { servers, scan_time_ms: elapsed })
     })
-    .await
-    .map_err(|e| format!("Task error: {}", e))?
+    .await?
 }
 
 // -- Claude: Listar agentes --------------------------------------------------
@@ -738,6 +1024,47 @@ async fn scan_mcp_servers() -> Result<McpScanResult, String> {
 pub struct AgentInfo {
     name: String,
     description: String,
+    tools: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    argument_hint: Option<String>,
+}
+
+/// Datos de un agente o comando a partir de su frontmatter. Sin
+/// `description`, se usa la primera linea util del cuerpo.
+fn agent_info(name: String, content: &str) -> AgentInfo {
+    let meta = frontmatter::parse(content).unwrap_or_default();
+    let text = |key: &str| {
+        meta.get(key)
+            .map(frontmatter::value_text)
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+    };
+
+    let description = text("description")
+        .or_else(|| frontmatter::first_text_line(content))
+        .map(|d| d.chars().take(120).collect())
+        .unwrap_or_default();
+
+    // `tools` puede venir como lista YAML o como "Read, Grep, Bash"
+    let tools = match meta.get("tools") {
+        Some(serde_json::Value::Array(items)) => items.iter().map(frontmatter::value_text).collect(),
+        Some(other) => frontmatter::value_text(other)
+            .split(',')
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .collect(),
+        None => vec![],
+    };
+
+    AgentInfo {
+        name,
+        description,
+        tools,
+        model: text("model"),
+        argument_hint: text("argument-hint"),
+    }
 }
 
 #[tauri::command]
@@ -761,42 +1088,8 @@ fn list_claude_agents(path: String) -> Vec<AgentInfo> {
                 .to_string_lossy()
                 .to_string();
 
-            // Leer descripcion del frontmatter YAML o primera linea util
-            let description = fs::read_to_string(&file_path)
-                .ok()
-                .and_then(|content| {
-                    let lines: Vec<&str> = content.lines().collect();
-                    // Si tiene frontmatter YAML (---), buscar campo description
-                    if lines.first().map(|l| l.trim()) == Some("---") {
-                        let end = lines.iter().skip(1).position(|l| l.trim() == "---");
-                        if let Some(end_idx) = end {
-                            // Buscar "description:" dentro del frontmatter
-                            for line in &lines[1..=end_idx] {
-                                if let Some(desc) = line.strip_prefix("description:") {
-                                    let d = desc.trim().trim_matches('"').trim_matches('\'');
-                                    if !d.is_empty() {
-                                        return Some(d.chars().take(120).collect::<String>());
-                                    }
-                                }
-                            }
-                        }
-                        // Frontmatter sin description: buscar primera linea despues
-                        let skip = end.map(|i| i + 2).unwrap_or(1);
-                        lines.iter().skip(skip).find(|l| {
-                            let t = l.trim();
-                            !t.is_empty() && !t.starts_with('#')
-                        }).map(|l| l.trim().chars().take(120).collect::<String>())
-                    } else {
-                        // Sin frontmatter: primera linea no vacia ni titulo
-                        lines.iter().find(|l| {
-                            let t = l.trim();
-                            !t.is_empty() && !t.starts_with('#')
-                        }).map(|l| l.trim().chars().take(120).collect::<String>())
-                    }
-                })
-                .unwrap_or_default();
-
-            agents.push(AgentInfo { name, description });
+            let content = fs::read_to_string(&file_path).unwrap_or_default();
+            agents.push(agent_info(name, &content));
         }
     }
 
@@ -866,7 +1159,7 @@ fn claude_unregister(id: &str) {
     });
 }
 
-fn claude_stop(id: &str) -> Result<(), String> {
+fn claude_stop(id: &str) -> Result<(), AppError> {
     let pid = claude_registry(|map| map.remove(id).map(|e| e.pid));
     match pid {
         Some(p) => {
@@ -875,7 +1168,7 @@ fn claude_stop(id: &str) -> Result<(), String> {
                 .output();
             Ok(())
         }
-        None => Err("No hay proceso activo para esa sesion".to_string()),
+        None => Err(AppError::new(ErrorKind::NotFound, "claude.no_process")),
     }
 }
 
@@ -912,37 +1205,8 @@ fn list_claude_commands(path: String) -> Vec<AgentInfo> {
                 .to_string_lossy()
                 .to_string();
 
-            let description = fs::read_to_string(&resolved)
-                .ok()
-                .and_then(|content| {
-                    let lines: Vec<&str> = content.lines().collect();
-                    if lines.first().map(|l| l.trim()) == Some("---") {
-                        let end = lines.iter().skip(1).position(|l| l.trim() == "---");
-                        if let Some(end_idx) = end {
-                            for line in &lines[1..=end_idx] {
-                                if let Some(desc) = line.strip_prefix("description:") {
-                                    let d = desc.trim().trim_matches('"').trim_matches('\'');
-                                    if !d.is_empty() {
-                                        return Some(d.chars().take(120).collect::<String>());
-                                    }
-                                }
-                            }
-                        }
-                        let skip = end.map(|i| i + 2).unwrap_or(1);
-                        lines.iter().skip(skip).find(|l| {
-                            let t = l.trim();
-                            !t.is_empty() && !t.starts_with('#')
-                        }).map(|l| l.trim().chars().take(120).collect::<String>())
-                    } else {
-                        lines.iter().find(|l| {
-                            let t = l.trim();
-                            !t.is_empty() && !t.starts_with('#')
-                        }).map(|l| l.trim().chars().take(120).collect::<String>())
-                    }
-                })
-                .unwrap_or_default();
-
-            commands.push(AgentInfo { name, description });
+            let content = fs::read_to_string(&resolved).unwrap_or_default();
+            commands.push(agent_info(name, &content));
         }
     }
 
@@ -951,7 +1215,7 @@ fn list_claude_commands(path: String) -> Vec<AgentInfo> {
 }
 
 #[tauri::command]
-fn read_claude_command(path: String, command: String) -> Result<String, String> {
+fn read_claude_command(path: String, command: String) -> Result<String, AppError> {
     let base = PathBuf::from(&path);
     let commands_dir = if base.ends_with(".claude/commands") || base.ends_with(".claude\\commands") {
         base
@@ -961,8 +1225,9 @@ fn read_claude_command(path: String, command: String) -> Result<String, String>
         base.join(".claude").join("commands")
     };
     let cmd_file = commands_dir.join(format!("{}.md", command));
-    let resolved = fs::canonicalize(&cmd_file).map_err(|e| e.to_string())?;
-    fs::read_to_string(&resolved).map_err(|e| e.to_string())
+    let resolved = fs::canonicalize(&cmd_file)
+        .map_err(|e| AppError::new(ErrorKind::NotFound, "claude.command_not_found").param("name", &command).detail(e))?;
+    Ok(fs::read_to_string(&resolved)?)
 }
 
 // -- Claude: Resolver binario ------------------------------------------------
@@ -1063,7 +1328,7 @@ fn resolve_claude_binary() -> Option<String> {
 }
 
 /// Devuelve la ruta al binario de claude, cacheada tras la primera resolucion.
-fn get_claude_binary() -> Result<String, String> {
+fn get_claude_binary() -> Result<String, AppError> {
     let mut guard = CLAUDE_BINARY.lock().unwrap();
     if let Some(ref path) = *guard {
         return Ok(path.clone());
@@ -1076,27 +1341,24 @@ fn get_claude_binary() -> Result<String, String> {
             *guard = Some(path.clone());
             Ok(path)
         }
-        None => Err("Claude Code no esta instalado. Instala con: curl -fsSL https://claude.ai/install.sh | sh".to_string()),
+        None => Err(AppError::new(ErrorKind::Claude, "claude.not_installed")),
     }
 }
 
 // -- Claude: Comandos --------------------------------------------------------
 
 #[tauri::command]
-fn check_claude() -> Result<String, String> {
+fn check_claude() -> Result<String, AppError> {
     let binary = get_claude_binary()?;
     let output = std::process::Command::new(&binary)
         .arg("--version")
         .output()
-        .map_err(|_| {
-            "Claude Code no esta instalado. Instala con: curl -fsSL https://claude.ai/install.sh | sh"
-                .to_string()
-        })?;
+        .map_err(|e| AppError::new(ErrorKind::Claude, "claude.not_installed").detail(e))?;
 
     if output.status.success() {
         Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
     } else {
-        Err("Claude Code no esta instalado. Instala con: curl -fsSL https://claude.ai/install.sh | sh".to_string())
+        Err(AppError::new(ErrorKind::Claude, "claude.not_installed"))
     }
 }
 
@@ -1111,7 +1373,7 @@ async fn send_claude_message(
     system_prompt: Option<String>,
     mcp_config_json: Option<String>,
     on_event: Channel<StreamChunk>,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     tokio::task::spawn_blocking(move || {
         let mut args: Vec<String> = vec![
             "--print".to_string(),
@@ -1126,7 +1388,7 @@ async fn send_claude_message(
         // termine la coleccion variadic de --mcp-config
         let _mcp_temp_file: Option<std::path::PathBuf> = if let Some(ref mcp_json) = mcp_config_json {
             let tmp_path = std::env::temp_dir().join(format!("potato-mcp-{}.json", process_id));
-            fs::write(&tmp_path, mcp_json).map_err(|e| format!("Error escribiendo MCP config: {}", e))?;
+            fs::write(&tmp_path, mcp_json).map_err(|e| AppError::new(ErrorKind::Mcp, "mcp.config_write").detail(e))?;
             args.push("--mcp-config".to_string());
             args.push(tmp_path.to_string_lossy().to_string());
             Some(tmp_path)
@@ -1154,8 +1416,7 @@ async fn send_claude_message(
 
         args.push(message);
 
-        let binary = get_claude_binary()
-            .map_err(|e| format!("No se encontro claude: {}", e))?;
+        let binary = get_claude_binary()?;
 
         let mut cmd = std::process::Command::new(&binary);
         cmd.args(&args)
@@ -1174,10 +1435,10 @@ async fn send_claude_message(
                 let bin_exists = std::path::Path::new(&binary).exists();
                 let dir_info = working_dir.as_deref().unwrap_or("(none)");
                 let dir_exists = working_dir.as_ref().map_or(true, |d| std::path::Path::new(d).is_dir());
-                format!(
-                    "No se pudo ejecutar claude: {} | binary={} (exists={}) | workdir={} (exists={})",
+                AppError::new(ErrorKind::Claude, "claude.spawn").detail(format!(
+                    "{} | binary={} (exists={}) | workdir={} (exists={})",
                     e, binary, bin_exists, dir_info, dir_exists
-                )
+                ))
             })?;
 
         claude_register(&process_id, child.id());
@@ -1461,7 +1722,7 @@ async fn send_claude_message(
             }
         }
 
-        let status = child.wait().map_err(|e| e.to_string())?;
+        let status = child.wait()?;
         let stderr_output = stderr_thread.join().unwrap_or_default();
 
         // Limpiar archivo temporal MCP
@@ -1486,20 +1747,16 @@ async fn send_claude_message(
         if status.success() || !full_response.trim().is_empty() {
             Ok(full_response.trim().to_string())
         } else {
-            let err_msg = if stderr_output.trim().is_empty() {
-                format!("Claude salio con codigo: {}", status.code().unwrap_or(-1))
-            } else {
-                stderr_output.trim().to_string()
-            };
-            Err(err_msg)
+            Err(AppError::new(ErrorKind::Claude, "claude.exit")
+                .param("code", status.code().unwrap_or(-1))
+                .detail(stderr_output.trim()))
         }
     })
-    .await
-    .map_err(|e| format!("Task error: {}", e))?
+    .await?
 }
 
 #[tauri::command]
-fn stop_claude(process_id: String) -> Result<(), String> {
+fn stop_claude(process_id: String) -> Result<(), AppError> {
     claude_stop(&process_id)
 }
 
@@ -1512,7 +1769,7 @@ struct UpdateProgress {
 }
 
 #[tauri::command]
-async fn download_update(app: tauri::AppHandle, url: String) -> Result<String, String> {
+async fn download_update(app: tauri::AppHandle, url: String) -> Result<String, AppError> {
     let app_handle = app.clone();
     tokio::task::spawn_blocking(move || {
         let dest = "/tmp/potato-update.deb";
@@ -1525,7 +1782,7 @@ async fn download_update(app: tauri::AppHandle, url: String) -> Result<String, S
             .stderr(Stdio::piped())
             .stdout(Stdio::piped())
             .spawn()
-            .map_err(|e| format!("No se pudo ejecutar curl: {}", e))?;
+            .map_err(|e| AppError::spawn("curl", e))?;
 
         // Parse stderr byte by byte for progress (curl uses \r for progress bar)
         if let Some(stderr) = child.stderr.take() {
@@ -1542,7 +1799,7 @@ async fn download_update(app: tauri::AppHandle, url: String) -> Result<String, S
                                 if pct >= last_emitted + 2 || pct == 100 {
                                     last_emitted = pct;
                                     let _ = app_handle.emit("update-progress", UpdateProgress {
-                                        phase: "Descargando".to_string(),
+                                        phase: i18n::t("update.downloading"),
                                         percent: pct,
                                     });
                                 }
@@ -1556,30 +1813,29 @@ async fn download_update(app: tauri::AppHandle, url: String) -> Result<String, S
             }
         }
 
-        let status = child.wait().map_err(|e| format!("Error esperando curl: {}", e))?;
+        let status = child.wait()?;
 
         if status.success() && PathBuf::from(dest).exists() {
             Ok(dest.to_string())
         } else {
             let _ = fs::remove_file(dest);
-            Err("Error al descargar la actualizacion. Verifica tu conexion a internet.".to_string())
+            Err(AppError::new(ErrorKind::Update, "update.download_failed"))
         }
     })
-    .await
-    .map_err(|e| format!("Task error: {}", e))?
+    .await?
 }
 
 #[tauri::command]
-async fn install_update(path: String) -> Result<(), String> {
+async fn install_update(path: String) -> Result<(), AppError> {
     tokio::task::spawn_blocking(move || {
         if !PathBuf::from(&path).exists() {
-            return Err("Archivo de actualizacion no encontrado.".to_string());
+            return Err(AppError::new(ErrorKind::NotFound, "update.not_found"));
         }
 
         let output = std::process::Command::new("pkexec")
             .args(["dpkg", "-i", &path])
             .output()
-            .map_err(|e| format!("No se pudo ejecutar pkexec: {}", e))?;
+            .map_err(|e| AppError::spawn("pkexec", e))?;
 
         // Clean up temp file regardless of result
         let _ = fs::remove_file(&path);
@@ -1589,20 +1845,19 @@ async fn install_update(path: String) -> Result<(), String> {
         } else {
             let stderr = String::from_utf8_lossy(&output.stderr).to_string();
             if stderr.contains("dismissed") || stderr.contains("Not authorized") || output.status.code() == Some(126) {
-                Err("Instalacion cancelada por el usuario.".to_string())
+                Err(AppError::new(ErrorKind::Cancelled, "update.cancelled"))
             } else {
-                Err(format!("Error al instalar: {}", stderr))
+                Err(AppError::new(ErrorKind::Update, "update.install_failed").detail(stderr))
             }
         }
     })
-    .await
-    .map_err(|e| format!("Task error: {}", e))?
+    .await?
 }
 
 #[tauri::command]
-async fn restart_app(app: tauri::AppHandle) -> Result<(), String> {
+async fn restart_app(app: tauri::AppHandle) -> Result<(), AppError> {
     let exe = std::env::current_exe()
-        .map_err(|e| format!("No se pudo obtener la ruta del ejecutable: {}", e))?;
+        .map_err(|e| AppError::new(ErrorKind::Update, "update.restart_failed").detail(e))?;
 
     // After dpkg -i replaces the binary, /proc/self/exe points to "path (deleted)"
     let exe_str = exe.to_string_lossy().to_string();
@@ -1614,7 +1869,7 @@ async fn restart_app(app: tauri::AppHandle) -> Result<(), String> {
 
     std::process::Command::new(&exe_path)
         .spawn()
-        .map_err(|e| format!("No se pudo reiniciar la app: {}", e))?;
+        .map_err(|e| AppError::new(ErrorKind::Update, "update.restart_failed").detail(e))?;
 
     app.exit(0);
 
@@ -1632,81 +1887,67 @@ pub struct RecentFile {
 }
 
 #[tauri::command]
-async fn git_recent_files(path: String, limit: Option<usize>) -> Result<Vec<RecentFile>, String> {
+async fn git_recent_files(path: String, limit: Option<usize>) -> Result<Vec<RecentFile>, AppError> {
     tokio::task::spawn_blocking(move || {
         let max = limit.unwrap_or(20);
-
-        let output = git_cmd(&path)
-            .args([
-                "log", "--all", "--diff-filter=A", "--name-only",
-                "--pretty=format:COMMIT_SEP|%an|%as",
-                "--", "*.md",
-            ])
-            .output()
-            .map_err(|e| format!("Error git log: {}", e))?;
-
-        if !output.status.success() {
+        let Ok(log) = git_backend::backend().log(&path, true, Some("*.md"), None) else {
             return Ok(Vec::new());
-        }
+        };
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
         let mut results: Vec<RecentFile> = Vec::new();
         let mut seen = std::collections::HashSet::new();
-        let mut current_author = String::new();
-        let mut current_date = String::new();
 
-        for line in stdout.lines() {
-            let trimmed = line.trim();
-            if trimmed.is_empty() {
+        // Notas en el orden en que se agregaron, de la mas reciente
+        let added = log.iter().flat_map(|entry| {
+            entry.files.iter().filter(|f| f.status == 'A').map(move |f| (entry, &f.path))
+        });
+        for (entry, rel) in added {
+            if !rel.ends_with(".md") || !seen.insert(rel.clone()) {
                 continue;
             }
-            if trimmed.starts_with("COMMIT_SEP|") {
-                let parts: Vec<&str> = trimmed.splitn(3, '|').collect();
-                if parts.len() >= 3 {
-                    current_author = parts[1].to_string();
-                    current_date = parts[2].to_string();
-                }
-            } else if trimmed.ends_with(".md") {
-                if seen.contains(trimmed) {
-                    continue;
-                }
-                seen.insert(trimmed.to_string());
 
-                let full_path = format!("{}/{}", path, trimmed);
-                // Only include files that still exist
-                if !std::path::Path::new(&full_path).exists() {
-                    continue;
-                }
+            let full_path = format!("{}/{}", path, rel);
+            // Only include files that still exist
+            if !std::path::Path::new(&full_path).exists() {
+                continue;
+            }
 
-                let name = trimmed.rsplit('/').next().unwrap_or(trimmed)
-                    .trim_end_matches(".md").to_string();
+            let name = rel.rsplit('/').next().unwrap_or(rel).trim_end_matches(".md").to_string();
 
-                results.push(RecentFile {
-                    path: full_path,
-                    name,
-                    author: current_author.clone(),
-                    date: current_date.clone(),
-                });
+            results.push(RecentFile {
+                path: full_path,
+                name,
+                author: entry.author.clone(),
+                date: entry.date.chars().take(10).collect(),
+            });
 
-                if results.len() >= max {
-                    break;
-                }
+            if results.len() >= max {
+                break;
             }
         }
 
         Ok(results)
     })
-    .await
-    .map_err(|e| format!("Task error: {}", e))?
+    .await?
 }
 
 // -- App ---------------------------------------------------------------------
 
+/// Textos de la CLI en el idioma del sistema.
+pub use i18n::t;
+
+/// Para la CLI: mensaje de commit para los cambios actuales del vault.
+pub fn commit_message(vault_path: &str) -> Result<String, String> {
+    commit_message::for_vault(vault_path, None).map_err(|e| e.to_string())
+}
+
 pub fn run() {
     tauri::Builder::default()
         .invoke_handler(tauri::generate_handler![
             open_vault,
             list_vault,
+            watcher::watch_vault,
+            watcher::unwatch_vault,
             read_note,
             save_note,
             create_note,
@@ -1719,12 +1960,65 @@ pub fn run() {
             git_commit,
             git_push,
             git_recent_files,
+            conflicts::git_conflicts,
+            conflicts::git_resolve_conflict,
+            conflicts::git_auto_merge_conflicts,
+            conflicts::git_conflict_continue,
+            conflicts::git_conflict_abort,
+            commit_message::git_commit_message,
+            hunks::git_diff_hunks,
+            hunks::git_apply_hunks,
+            autosync::start_auto_sync,
+            autosync::stop_auto_sync,
+            autosync::auto_sync_now,
+            history::git_file_history,
+            history::git_file_at,
+            history::git_file_diff,
+            history::git_restore_file,
+            branches::git_branches,
+            branches::git_create_branch,
+            branches::git_switch_branch,
+            branches::git_delete_branch,
+            branches::git_merge_branch,
             pick_folder,
             move_file,
+            rename_note,
+            delete_note,
+            link_index::get_backlinks,
+            link_index::get_outgoing_links,
+            link_index::get_unresolved_links,
+            link_index::get_orphan_notes,
+            link_index::get_link_graph,
             open_in_explorer,
             search_vault,
+            search_index::rebuild_search_index,
+            query::query_vault,
+            query::parse_query,
+            templates::list_templates,
+            attachments::save_attachment,
+            attachments::find_unused_attachments,
+            periodic::open_periodic_note,
+            periodic::periodic_neighbor,
+            periodic::list_periodic_notes,
+            periodic::shift_period,
+            tags::get_tags,
+            tags::get_notes_with_tag,
+            tags::rename_tag,
+            frontmatter::get_frontmatter,
+            frontmatter::set_frontmatter_property,
+            frontmatter::delete_frontmatter_property,
+            vault_ignore::set_ignore_options,
+            replace::preview_replace,
+            replace::apply_replace,
+            replace::undo_replace,
             save_session,
             load_session,
+            i18n::get_locale,
+            i18n::set_locale,
+            settings::get_settings,
+            settings::set_setting,
+            settings::import_local_settings,
+            vault_config::get_vault_config,
             check_claude,
             send_claude_message,
             stop_claude,
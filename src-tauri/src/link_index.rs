@@ -0,0 +1,363 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::errors::AppError;
+use crate::vault_index::{Registry, VaultIndex};
+use crate::vault_notes;
+use crate::wikilinks::{split_target, wikilink_regex, LinkName};
+
+/// Un wikilink tal como aparece en una nota.
+struct LinkRef {
+    target: String, // nombre de la nota, sin seccion
+    line: u32,
+    context: String,
+}
+
+struct NoteLinks {
+    name: LinkName,
+    links: Vec<LinkRef>,
+}
+
+/// Link ya resuelto: `link` es su posicion en `NoteLinks::links` de `source`.
+struct Edge {
+    source: PathBuf,
+    link: usize,
+    dest: Option<PathBuf>,
+}
+
+/// Indice de links de un vault: por cada nota, sus links salientes.
+/// Backlinks y huerfanas se derivan de las aristas resueltas, que se
+/// calculan en la primera consulta y se descartan al cambiar una nota.
+struct VaultLinks {
+    notes: BTreeMap<PathBuf, NoteLinks>,
+    edges: OnceLock<Vec<Edge>>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct Backlink {
+    path: String,
+    name: String,
+    line: u32,
+    context: String,
+}
+
+#[derive(Serialize, Clone)]
+pub struct OutgoingLink {
+    target: String,
+    line: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>, // None si el link no resuelve a ninguna nota
+}
+
+#[derive(Serialize, Clone)]
+pub struct UnresolvedLink {
+    target: String,
+    sources: Vec<String>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct NoteRef {
+    path: String,
+    name: String,
+}
+
+#[derive(Serialize, Clone)]
+pub struct GraphNode {
+    id: String, // ruta de la nota, o el target si no resuelve
+    name: String,
+    unresolved: bool,
+    links: u32, // grado (entrantes + salientes), util para el tamano del nodo
+}
+
+#[derive(Serialize, Clone)]
+pub struct GraphEdge {
+    source: String,
+    target: String,
+}
+
+#[derive(Serialize, Clone)]
+pub struct LinkGraph {
+    nodes: Vec<GraphNode>,
+    edges: Vec<GraphEdge>,
+}
+
+// -- Construccion ------------------------------------------------------------
+
+fn parse_note(vault: &Path, path: &Path) -> Option<NoteLinks> {
+    let content = fs::read_to_string(path).ok()?;
+    let mut links = Vec::new();
+
+    for (i, line) in content.lines().enumerate() {
+        for cap in wikilink_regex().captures_iter(line) {
+            let (name, _) = split_target(&cap[1]);
+            if name.is_empty() {
+                continue; // [[#seccion]] apunta a la propia nota
+            }
+            links.push(LinkRef {
+                target: name.strip_suffix(".md").unwrap_or(name).to_string(),
+                line: (i + 1) as u32,
+                context: line.trim().chars().take(120).collect(),
+            });
+        }
+    }
+
+    Some(NoteLinks {
+        name: LinkName::new(vault, path),
+        links,
+    })
+}
+
+impl VaultLinks {
+    /// Resuelve un target igual que el frontend: `[[carpeta/nota]]` por ruta,
+    /// `[[nota]]` por nombre (la primera en orden alfabetico de ruta).
+    fn resolve(&self, target: &str) -> Option<&PathBuf> {
+        self.notes
+            .iter()
+            .find(|(_, n)| n.name.matches(target, true))
+            .map(|(p, _)| p)
+    }
+
+    fn build_edges(&self) -> Vec<Edge> {
+        let mut by_target: HashMap<&str, Option<&PathBuf>> = HashMap::new();
+        let mut edges = Vec::new();
+        for (source, note) in &self.notes {
+            for (i, link) in note.links.iter().enumerate() {
+                let dest = *by_target
+                    .entry(link.target.as_str())
+                    .or_insert_with(|| self.resolve(&link.target));
+                edges.push(Edge {
+                    source: source.clone(),
+                    link: i,
+                    dest: dest.cloned(),
+                });
+            }
+        }
+        edges
+    }
+
+    fn resolved_edges(&self) -> impl Iterator<Item = (&PathBuf, &LinkRef, Option<&PathBuf>)> {
+        self.edges
+            .get_or_init(|| self.build_edges())
+            .iter()
+            .map(|e| (&e.source, &self.notes[&e.source].links[e.link], e.dest.as_ref()))
+    }
+
+    fn invalidate(&mut self) {
+        self.edges = OnceLock::new();
+    }
+}
+
+// -- Registry ----------------------------------------------------------------
+
+impl VaultIndex for VaultLinks {
+    type Note = NoteLinks;
+
+    fn build(vault: &Path) -> Self {
+        let notes = vault_notes(vault)
+            .into_iter()
+            .filter_map(|p| parse_note(vault, &p).map(|n| (p, n)))
+            .collect();
+        VaultLinks { notes, edges: OnceLock::new() }
+    }
+
+    fn read_note(vault: &Path, path: &Path) -> Option<NoteLinks> {
+        parse_note(vault, path)
+    }
+
+    fn update_note(&mut self, _vault: &Path, path: PathBuf, note: Option<NoteLinks>) {
+        match note {
+            Some(note) => self.notes.insert(path, note),
+            None => self.notes.remove(&path),
+        };
+        self.invalidate();
+    }
+
+    fn remove_notes(&mut self, _vault: &Path, path: &Path) {
+        self.notes.retain(|note, _| !note.starts_with(path));
+        self.invalidate();
+    }
+}
+
+static LINK_INDEX: Registry<VaultLinks> = Registry::new();
+
+fn with_vault<F, R>(vault_path: &str, f: F) -> R
+where
+    F: FnOnce(&VaultLinks) -> R,
+{
+    LINK_INDEX.with_vault(Path::new(vault_path), |index| f(index))
+}
+
+/// Construye el indice en segundo plano al abrir un vault.
+pub fn warm_up(vault: &Path) {
+    if LINK_INDEX.is_loaded(vault) {
+        return;
+    }
+    let vault = vault.to_path_buf();
+    std::thread::spawn(move || {
+        LINK_INDEX.with_vault(&vault, |_| ());
+    });
+}
+
+pub fn path_updated(path: &Path) {
+    LINK_INDEX.path_updated(path);
+}
+
+pub fn path_removed(path: &Path) {
+    LINK_INDEX.path_removed(path);
+}
+
+pub fn forget(vault: &Path) {
+    LINK_INDEX.forget(vault);
+}
+
+// -- Comandos ----------------------------------------------------------------
+
+#[tauri::command]
+pub async fn get_backlinks(vault_path: String, path: String) -> Result<Vec<Backlink>, AppError> {
+    tokio::task::spawn_blocking(move || {
+        let note = PathBuf::from(&path);
+        with_vault(&vault_path, |index| {
+            let mut backlinks: Vec<Backlink> = index
+                .resolved_edges()
+                .filter(|(source, _, dest)| *dest == Some(&note) && **source != note)
+                .map(|(source, link, _)| Backlink {
+                    path: source.to_string_lossy().to_string(),
+                    name: index.notes[source].name.stem.clone(),
+                    line: link.line,
+                    context: link.context.clone(),
+                })
+                .collect();
+            backlinks.sort_by(|a, b| a.name.cmp(&b.name).then(a.line.cmp(&b.line)));
+            Ok(backlinks)
+        })
+    })
+    .await?
+}
+
+#[tauri::command]
+pub async fn get_outgoing_links(vault_path: String, path: String) -> Result<Vec<OutgoingLink>, AppError> {
+    tokio::task::spawn_blocking(move || {
+        let note = PathBuf::from(&path);
+        with_vault(&vault_path, |index| {
+            let Some(links) = index.notes.get(&note) else {
+                return Ok(vec![]);
+            };
+            Ok(links
+                .links
+                .iter()
+                .map(|link| OutgoingLink {
+                    target: link.target.clone(),
+                    line: link.line,
+                    path: index
+                        .resolve(&link.target)
+                        .map(|p| p.to_string_lossy().to_string()),
+                })
+                .collect())
+        })
+    })
+    .await?
+}
+
+#[tauri::command]
+pub async fn get_unresolved_links(vault_path: String) -> Result<Vec<UnresolvedLink>, AppError> {
+    tokio::task::spawn_blocking(move || {
+        with_vault(&vault_path, |index| {
+            let mut unresolved: BTreeMap<String, Vec<String>> = BTreeMap::new();
+            for (source, link, dest) in index.resolved_edges() {
+                if dest.is_none() {
+                    let sources = unresolved.entry(link.target.clone()).or_default();
+                    let source = source.to_string_lossy().to_string();
+                    if !sources.contains(&source) {
+                        sources.push(source);
+                    }
+                }
+            }
+            Ok(unresolved
+                .into_iter()
+                .map(|(target, sources)| UnresolvedLink { target, sources })
+                .collect())
+        })
+    })
+    .await?
+}
+
+/// Notas a las que ninguna otra nota enlaza.
+#[tauri::command]
+pub async fn get_orphan_notes(vault_path: String) -> Result<Vec<NoteRef>, AppError> {
+    tokio::task::spawn_blocking(move || {
+        with_vault(&vault_path, |index| {
+            let linked: HashSet<&PathBuf> = index
+                .resolved_edges()
+                .filter(|(source, _, dest)| *dest != Some(*source))
+                .filter_map(|(_, _, dest)| dest)
+                .collect();
+            Ok(index
+                .notes
+                .iter()
+                .filter(|(path, _)| !linked.contains(path))
+                .map(|(path, note)| NoteRef {
+                    path: path.to_string_lossy().to_string(),
+                    name: note.name.stem.clone(),
+                })
+                .collect())
+        })
+    })
+    .await?
+}
+
+/// Grafo completo del vault (nodos y aristas) para dibujarlo en el frontend.
+/// Los links sin resolver aparecen como nodos con `unresolved: true`.
+#[tauri::command]
+pub async fn get_link_graph(vault_path: String) -> Result<LinkGraph, AppError> {
+    tokio::task::spawn_blocking(move || {
+        with_vault(&vault_path, |index| {
+            let mut degree: HashMap<String, u32> = HashMap::new();
+            let mut seen = HashSet::new();
+            let mut edges = Vec::new();
+            let mut unresolved = BTreeSet::new();
+
+            for (source, link, dest) in index.resolved_edges() {
+                let source = source.to_string_lossy().to_string();
+                let target = match dest {
+                    Some(d) => d.to_string_lossy().to_string(),
+                    None => {
+                        unresolved.insert(link.target.clone());
+                        link.target.clone()
+                    }
+                };
+                if source == target || !seen.insert((source.clone(), target.clone())) {
+                    continue;
+                }
+                *degree.entry(source.clone()).or_default() += 1;
+                *degree.entry(target.clone()).or_default() += 1;
+                edges.push(GraphEdge { source, target });
+            }
+
+            let mut nodes: Vec<GraphNode> = index
+                .notes
+                .iter()
+                .map(|(path, note)| {
+                    let id = path.to_string_lossy().to_string();
+                    GraphNode {
+                        links: degree.get(&id).copied().unwrap_or(0),
+                        id,
+                        name: note.name.stem.clone(),
+                        unresolved: false,
+                    }
+                })
+                .collect();
+            nodes.extend(unresolved.into_iter().map(|target| GraphNode {
+                links: degree.get(&target).copied().unwrap_or(0),
+                name: target.rsplit('/').next().unwrap_or(&target).to_string(),
+                id: target,
+                unresolved: true,
+            }));
+
+            Ok(LinkGraph { nodes, edges })
+        })
+    })
+    .await?
+}
@@ -5,19 +5,34 @@ fn main() {
     if args.len() > 1 {
         match args[1].as_str() {
             "--version" | "-v" | "-V" => {
-                println!("Hola José, aquí está la versión");
+                println!("{}", potato_lib::t("cli.version_greeting"));
                 println!("POTATO {}", env!("CARGO_PKG_VERSION"));
                 return;
             }
             "--help" | "-h" => {
-                println!("POTATO {} — Editor de notas markdown con IA integrada", env!("CARGO_PKG_VERSION"));
+                println!("POTATO {} — {}", env!("CARGO_PKG_VERSION"), potato_lib::t("cli.about"));
                 println!();
-                println!("USO:");
-                println!("  potato [OPCIONES]");
+                println!("{}", potato_lib::t("cli.usage"));
+                println!("  {}", potato_lib::t("cli.usage_run"));
+                println!("  {}", potato_lib::t("cli.usage_commit_message"));
                 println!();
-                println!("OPCIONES:");
-                println!("  -v, --version    Muestra la versión");
-                println!("  -h, --help       Muestra esta ayuda");
+                println!("{}", potato_lib::t("cli.options"));
+                println!("  -v, --version    {}", potato_lib::t("cli.opt_version"));
+                println!("  -h, --help       {}", potato_lib::t("cli.opt_help"));
+                println!();
+                println!("{}", potato_lib::t("cli.commands"));
+                println!("  commit-message   {}", potato_lib::t("cli.cmd_commit_message"));
+                return;
+            }
+            "commit-message" => {
+                let vault = args.get(2).map(String::as_str).unwrap_or(".");
+                match potato_lib::commit_message(vault) {
+                    Ok(message) => println!("{}", message),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        std::process::exit(1);
+                    }
+                }
                 return;
             }
             _ => {}
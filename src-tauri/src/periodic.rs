@@ -0,0 +1,346 @@
+use chrono::{Datelike, Duration, Local, Months, NaiveDate, Weekday};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::errors::{AppError, ErrorKind};
+use crate::{index_path_updated, vault_ignore};
+use crate::templates::{self, format_date, TemplateContext};
+
+#[derive(Clone, Copy, PartialEq)]
+enum Period {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// Carpeta, patron de nombre (tokens de `format_date`, puede incluir `/`)
+/// y plantilla opcional de un tipo de nota periodica.
+#[derive(Deserialize, Serialize, Clone)]
+pub struct PeriodicSettings {
+    #[serde(default)]
+    folder: Option<String>,
+    #[serde(default)]
+    format: Option<String>,
+    #[serde(default)]
+    template: Option<String>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct PeriodicNote {
+    path: String,
+    name: String,
+    date: String, // inicio del periodo, YYYY-MM-DD
+    created: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cursor: Option<u32>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct PeriodicEntry {
+    date: String,
+    path: String,
+}
+
+impl Period {
+    fn parse(kind: &str) -> Result<Self, AppError> {
+        match kind {
+            "daily" => Ok(Period::Daily),
+            "weekly" => Ok(Period::Weekly),
+            "monthly" => Ok(Period::Monthly),
+            _ => Err(AppError::invalid("periodic.invalid_kind", kind)),
+        }
+    }
+
+    fn default_folder(self) -> &'static str {
+        match self {
+            Period::Daily => "Diario",
+            Period::Weekly => "Semanal",
+            Period::Monthly => "Mensual",
+        }
+    }
+
+    fn default_format(self) -> &'static str {
+        match self {
+            Period::Daily => "YYYY-MM-DD",
+            Period::Weekly => "GGGG-[W]WW",
+            Period::Monthly => "YYYY-MM",
+        }
+    }
+
+    /// Primer dia del periodo que contiene `date`.
+    fn start(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Daily => date,
+            Period::Weekly => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+            Period::Monthly => date.with_day(1).unwrap_or(date),
+        }
+    }
+}
+
+/// Configuracion resuelta: (carpeta relativa al vault, patron, plantilla).
+fn resolve(period: Period, settings: Option<PeriodicSettings>) -> (String, String, Option<String>) {
+    let settings = settings.unwrap_or(PeriodicSettings {
+        folder: None,
+        format: None,
+        template: None,
+    });
+    let folder = settings
+        .folder
+        .map(|f| f.trim().trim_matches('/').to_string())
+        .unwrap_or_else(|| period.default_folder().to_string());
+    let format = settings
+        .format
+        .filter(|f| !f.trim().is_empty())
+        .unwrap_or_else(|| period.default_format().to_string());
+    let template = settings.template.filter(|t| !t.trim().is_empty());
+    (folder, format, template)
+}
+
+fn parse_date(date: Option<&str>) -> Result<NaiveDate, AppError> {
+    match date {
+        Some(d) => NaiveDate::parse_from_str(d.trim(), "%Y-%m-%d").map_err(|_| AppError::invalid("periodic.invalid_date", d)),
+        None => Ok(Local::now().date_naive()),
+    }
+}
+
+fn note_path(vault: &Path, folder: &str, format: &str, start: NaiveDate) -> PathBuf {
+    let name = format_date(&start.and_hms_opt(0, 0, 0).unwrap_or_default(), format);
+    vault.join(folder).join(format!("{}.md", name))
+}
+
+// -- Notas existentes --------------------------------------------------------
+
+/// Regex que reconoce los nombres generados por `format` y, por cada grupo,
+/// que parte de la fecha captura ('y', 'm', 'd', 'n' = nombre del mes,
+/// 'g' = anio ISO, 'w' = semana).
+fn format_regex(format: &str) -> Option<(Regex, Vec<char>)> {
+    const TOKENS: &[(&str, Option<char>, &str)] = &[
+        ("YYYY", Some('y'), r"(\d{4})"),
+        ("GGGG", Some('g'), r"(\d{4})"),
+        ("MMMM", Some('n'), r"([^/]+?)"),
+        ("dddd", None, r"[^/]+?"),
+        ("MMM", Some('n'), r"([^/]+?)"),
+        ("ddd", None, r"[^/]+?"),
+        ("DDD", None, r"\d{1,3}"),
+        ("YY", None, r"\d{2}"),
+        ("MM", Some('m'), r"(\d{2})"),
+        ("DD", Some('d'), r"(\d{2})"),
+        ("WW", Some('w'), r"(\d{2})"),
+        ("ww", Some('w'), r"(\d{2})"),
+        ("M", Some('m'), r"(\d{1,2})"),
+        ("D", Some('d'), r"(\d{1,2})"),
+        ("W", Some('w'), r"(\d{1,2})"),
+        ("w", Some('w'), r"(\d{1,2})"),
+        ("Q", None, r"\d"),
+        ("E", None, r"\d"),
+    ];
+
+    let mut pattern = String::from("^");
+    let mut groups = Vec::new();
+    let mut rest = format;
+    while let Some(c) = rest.chars().next() {
+        if c == '[' {
+            if let Some(end) = rest.find(']') {
+                pattern.push_str(&regex::escape(&rest[1..end]));
+                rest = &rest[end + 1..];
+                continue;
+            }
+        }
+        match TOKENS.iter().find(|(t, _, _)| rest.starts_with(t)) {
+            Some((token, group, re)) => {
+                pattern.push_str(re);
+                groups.extend(group);
+                rest = &rest[token.len()..];
+            }
+            None => {
+                pattern.push_str(&regex::escape(&c.to_string()));
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    pattern.push('$');
+    Regex::new(&pattern).ok().map(|re| (re, groups))
+}
+
+/// Fecha de inicio del periodo a partir del nombre de una nota, o None si el
+/// nombre no corresponde al patron.
+fn date_from_name(period: Period, format: &str, re: &Regex, groups: &[char], name: &str) -> Option<NaiveDate> {
+    let caps = re.captures(name)?;
+    let text = |kind: char| -> Option<&str> {
+        let idx = groups.iter().position(|g| *g == kind)?;
+        Some(caps.get(idx + 1)?.as_str())
+    };
+    let part = |kind: char| -> Option<u32> { text(kind)?.parse().ok() };
+    // `MMMM`/`MMM`: el nombre se compara contra los que genera `format_date`
+    let month_by_name = || -> Option<u32> {
+        let name = text('n')?.to_lowercase();
+        (1..=12).find(|m| {
+            let first = NaiveDate::from_ymd_opt(2000, *m, 1).unwrap_or_default();
+            [first.format("%B").to_string(), first.format("%b").to_string()]
+                .iter()
+                .any(|n| n.to_lowercase() == name)
+        })
+    };
+
+    let date = match (part('g'), part('w')) {
+        (Some(year), Some(week)) => NaiveDate::from_isoywd_opt(year as i32, week, Weekday::Mon)?,
+        _ => {
+            let year = part('y')? as i32;
+            NaiveDate::from_ymd_opt(year, part('m').or_else(month_by_name).unwrap_or(1), part('d').unwrap_or(1))?
+        }
+    };
+    let start = period.start(date);
+
+    // Descarta coincidencias ambiguas: el nombre debe ser exactamente el generado
+    let expected = format_date(&start.and_hms_opt(0, 0, 0)?, format);
+    (expected == name).then_some(start)
+}
+
+/// Notas periodicas existentes, ordenadas por fecha.
+fn existing_notes(vault: &Path, period: Period, folder: &str, format: &str) -> Vec<(NaiveDate, PathBuf)> {
+    let Some((re, groups)) = format_regex(format) else {
+        return vec![];
+    };
+    let base = vault.join(folder);
+    let mut notes: Vec<(NaiveDate, PathBuf)> = vault_ignore::walk(&base)
+        .filter(|e| e.file_type().is_file() && e.path().extension().is_some_and(|x| x == "md"))
+        .filter_map(|e| {
+            let name = e
+                .path()
+                .strip_prefix(&base)
+                .ok()?
+                .with_extension("")
+                .to_string_lossy()
+                .replace('\\', "/");
+            let date = date_from_name(period, format, &re, &groups, &name)?;
+            Some((date, e.into_path()))
+        })
+        .collect();
+    notes.sort();
+    notes
+}
+
+fn entry(date: NaiveDate, path: &Path) -> PeriodicEntry {
+    PeriodicEntry {
+        date: date.format("%Y-%m-%d").to_string(),
+        path: path.to_string_lossy().to_string(),
+    }
+}
+
+// -- Comandos ----------------------------------------------------------------
+
+/// Abre la nota del periodo que contiene `date` (hoy si no se indica),
+/// creandola con la plantilla configurada si aun no existe.
+#[tauri::command]
+pub fn open_periodic_note(
+    vault_path: String,
+    kind: String,
+    date: Option<String>,
+    settings: Option<PeriodicSettings>,
+) -> Result<PeriodicNote, AppError> {
+    let period = Period::parse(&kind)?;
+    let vault = PathBuf::from(&vault_path);
+    let (folder, format, template) = resolve(period, settings);
+    let start = period.start(parse_date(date.as_deref())?);
+
+    let path = note_path(&vault, &folder, &format, start);
+    let name = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    let mut note = PeriodicNote {
+        path: path.to_string_lossy().to_string(),
+        name: name.clone(),
+        date: start.format("%Y-%m-%d").to_string(),
+        created: false,
+        cursor: None,
+    };
+    if path.exists() {
+        return Ok(note);
+    }
+
+    let (content, cursor) = match template {
+        Some(template) => {
+            let ctx = TemplateContext {
+                title: &name,
+                vault: &vault,
+                date: start.and_time(Local::now().time()),
+            };
+            templates::render_template(&template, &ctx)?
+        }
+        None => (format!("# {}\n\n", name), None),
+    };
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, &content)?;
+    index_path_updated(&path);
+
+    note.created = true;
+    note.cursor = cursor;
+    Ok(note)
+}
+
+/// Nota periodica existente mas cercana antes (`direction: "prev"`) o despues
+/// (`"next"`) de `date`, saltando periodos sin nota. None si no hay.
+#[tauri::command]
+pub fn periodic_neighbor(
+    vault_path: String,
+    kind: String,
+    date: String,
+    direction: String,
+    settings: Option<PeriodicSettings>,
+) -> Result<Option<PeriodicEntry>, AppError> {
+    let period = Period::parse(&kind)?;
+    let (folder, format, _) = resolve(period, settings);
+    let start = period.start(parse_date(Some(&date))?);
+    let notes = existing_notes(Path::new(&vault_path), period, &folder, &format);
+
+    let found = match direction.as_str() {
+        "prev" => notes.iter().rev().find(|(d, _)| *d < start),
+        "next" => notes.iter().find(|(d, _)| *d > start),
+        _ => return Err(AppError::invalid("periodic.invalid_direction", &direction)),
+    };
+    Ok(found.map(|(d, p)| entry(*d, p)))
+}
+
+/// Notas periodicas con fecha entre `from` y `to` (inclusive), para marcar
+/// en el calendario que dias, semanas o meses tienen entrada.
+#[tauri::command]
+pub async fn list_periodic_notes(
+    vault_path: String,
+    kind: String,
+    from: String,
+    to: String,
+    settings: Option<PeriodicSettings>,
+) -> Result<Vec<PeriodicEntry>, AppError> {
+    tokio::task::spawn_blocking(move || {
+        let period = Period::parse(&kind)?;
+        let (folder, format, _) = resolve(period, settings);
+        let from = period.start(parse_date(Some(&from))?);
+        let to = parse_date(Some(&to))?;
+
+        Ok(existing_notes(Path::new(&vault_path), period, &folder, &format)
+            .into_iter()
+            .filter(|(d, _)| *d >= from && *d <= to)
+            .map(|(d, p)| entry(d, &p))
+            .collect())
+    })
+    .await?
+}
+
+/// Fecha del periodo anterior o siguiente, para navegar aunque no exista nota.
+#[tauri::command]
+pub fn shift_period(kind: String, date: String, offset: i32) -> Result<String, AppError> {
+    let period = Period::parse(&kind)?;
+    let start = period.start(parse_date(Some(&date))?);
+    let shifted = match period {
+        Period::Daily => start.checked_add_signed(Duration::days(offset as i64)),
+        Period::Weekly => start.checked_add_signed(Duration::weeks(offset as i64)),
+        Period::Monthly if offset >= 0 => start.checked_add_months(Months::new(offset as u32)),
+        Period::Monthly => start.checked_sub_months(Months::new(offset.unsigned_abs())),
+    };
+    shifted
+        .map(|d| d.format("%Y-%m-%d").to_string())
+        .ok_or_else(|| AppError::new(ErrorKind::InvalidInput, "periodic.out_of_range"))
+}
@@ -0,0 +1,328 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::errors::AppError;
+use crate::search_index::{candidate_notes, contains_phrase, find_matches, fold, tokenize};
+use crate::{frontmatter, tags, vault_notes};
+
+const DEFAULT_LIMIT: usize = 200;
+
+/// Una condicion de la consulta. Todas deben cumplirse (AND); `negated`
+/// invierte la condicion (`-borrador`, `-tag:archivo`).
+#[derive(Serialize, Clone)]
+pub struct QueryClause {
+    kind: String, // "term", "phrase", "tag", "path", "name", "field"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    field: Option<String>,
+    value: String,
+    negated: bool,
+    text: String, // la condicion tal como se escribio
+    /// Solo el ultimo termino positivo de la consulta (el que se esta
+    /// escribiendo) busca su ultimo token como prefijo.
+    #[serde(skip)]
+    prefix: bool,
+}
+
+#[derive(Serialize, Clone)]
+pub struct ClauseMatch {
+    clause: usize, // indice en `clauses`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    preview: Option<String>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct QueryResult {
+    path: String,
+    name: String,
+    matches: Vec<ClauseMatch>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct QueryResponse {
+    clauses: Vec<QueryClause>,
+    results: Vec<QueryResult>,
+    truncated: bool,
+}
+
+// -- Parser ------------------------------------------------------------------
+
+/// Parte la consulta respetando comillas: `status:"en curso" -"frase"`.
+fn split_clauses(raw: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+
+    for c in raw.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                current.push(c);
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    parts.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+    parts
+}
+
+fn unquote(s: &str) -> (&str, bool) {
+    match s.strip_prefix('"') {
+        Some(inner) => (inner.strip_suffix('"').unwrap_or(inner), true),
+        None => (s, false),
+    }
+}
+
+pub fn parse(raw: &str) -> Vec<QueryClause> {
+    let mut clauses = Vec::new();
+
+    for text in split_clauses(raw) {
+        let (negated, body) = match text.strip_prefix('-') {
+            Some(rest) if !rest.is_empty() => (true, rest),
+            _ => (false, text.as_str()),
+        };
+
+        let (kind, field, value) = match body.split_once(':') {
+            Some((key, value)) if !key.is_empty() && !key.starts_with('"') => {
+                let (value, _) = unquote(value);
+                let key = key.to_lowercase();
+                match key.as_str() {
+                    "tag" | "path" | "name" => (key, None, value.to_string()),
+                    _ => ("field".to_string(), Some(key), value.to_string()),
+                }
+            }
+            _ => {
+                let (value, quoted) = unquote(body);
+                let kind = if quoted { "phrase" } else { "term" };
+                (kind.to_string(), None, value.to_string())
+            }
+        };
+
+        // `-` o `...` no dejan tokens: una condicion vacia aceptaria todo
+        let no_tokens = matches!(kind.as_str(), "term" | "phrase") && tokenize(&value).is_empty();
+        if (value.is_empty() && kind != "field") || no_tokens {
+            continue;
+        }
+        clauses.push(QueryClause {
+            kind,
+            field,
+            value,
+            negated,
+            text,
+            prefix: false,
+        });
+    }
+
+    if let Some(last) = clauses.last_mut().filter(|c| c.kind == "term" && !c.negated) {
+        last.prefix = true;
+    }
+    clauses
+}
+
+// -- Evaluacion --------------------------------------------------------------
+
+struct Note<'a> {
+    rel: String,
+    name: String,
+    content: &'a str,
+    frontmatter: Option<Value>,
+}
+
+fn field_matches(value: &Value, expected: &str) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Array(items) => items.iter().any(|v| field_matches(v, expected)),
+        Value::String(s) => expected.is_empty() || fold(s) == fold(expected),
+        Value::Object(_) => expected.is_empty(),
+        other => expected.is_empty() || expected.eq_ignore_ascii_case(&other.to_string()),
+    }
+}
+
+/// Primera linea del cuerpo que cumple `pred`, como (numero, preview).
+fn first_line(content: &str, pred: impl Fn(&str) -> bool) -> Option<(u32, String)> {
+    content.lines().enumerate().find(|(_, l)| pred(l)).map(|(i, l)| {
+        ((i + 1) as u32, l.trim().chars().take(120).collect())
+    })
+}
+
+/// Evalua una condicion (sin tener en cuenta `negated`). Si se cumple,
+/// devuelve la linea y el preview donde aplica, cuando tiene sentido.
+fn evaluate(clause: &QueryClause, note: &Note) -> Option<(Option<u32>, Option<String>)> {
+    match clause.kind.as_str() {
+        "term" => {
+            let tokens = tokenize(&clause.value);
+            let last = tokens.len().saturating_sub(1);
+            let required: Vec<(&str, bool)> = tokens
+                .iter()
+                .enumerate()
+                .map(|(i, t)| (t.as_str(), clause.prefix && i == last))
+                .collect();
+            let in_name = required.iter().all(|t| !find_matches(&note.name, &[*t]).is_empty());
+            let in_body = required
+                .iter()
+                .all(|t| !find_matches(note.content, &[*t]).is_empty());
+            if !in_name && !in_body {
+                return None;
+            }
+            let hit = first_line(note.content, |l| !find_matches(l, &required).is_empty());
+            Some(hit.map_or((None, None), |(l, p)| (Some(l), Some(p))))
+        }
+        "phrase" => {
+            let phrase = tokenize(&clause.value);
+            if !contains_phrase(&tokenize(note.content), &phrase) {
+                return None;
+            }
+            let hit = first_line(note.content, |l| contains_phrase(&tokenize(l), &phrase));
+            Some(hit.map_or((None, None), |(l, p)| (Some(l), Some(p))))
+        }
+        "tag" => {
+            let wanted = fold(clause.value.trim_start_matches('#'));
+            let found = tags::note_tags(note.content)
+                .into_iter()
+                .map(|t| fold(&t))
+                .any(|t| t == wanted || t.starts_with(&format!("{}/", wanted)));
+            found.then_some((None, None))
+        }
+        "path" => {
+            let wanted = fold(clause.value.trim_start_matches('/'));
+            let rel = fold(&note.rel);
+            (rel.starts_with(&wanted) || rel.contains(&format!("/{}", wanted))).then_some((None, None))
+        }
+        "name" => fold(&note.name)
+            .contains(&fold(&clause.value))
+            .then_some((None, None)),
+        "field" => {
+            let key = clause.field.as_deref().unwrap_or_default();
+            let value = note
+                .frontmatter
+                .as_ref()?
+                .as_object()?
+                .iter()
+                .find(|(k, _)| k.to_lowercase() == key)
+                .map(|(_, v)| v)?;
+            if !field_matches(value, &clause.value) {
+                return None;
+            }
+            let preview = format!("{}: {}", key, frontmatter::value_text(value));
+            Some((None, Some(preview)))
+        }
+        _ => None,
+    }
+}
+
+/// Tokens que toda nota resultado debe contener, para acotar con el indice.
+fn required_tokens(clauses: &[QueryClause]) -> Vec<(String, bool)> {
+    let mut required = Vec::new();
+    for clause in clauses.iter().filter(|c| !c.negated) {
+        let tokens = tokenize(&clause.value);
+        match clause.kind.as_str() {
+            "term" => {
+                let last = tokens.len().saturating_sub(1);
+                required.extend(tokens.into_iter().enumerate().map(|(i, t)| (t, clause.prefix && i == last)));
+            }
+            "phrase" => required.extend(tokens.into_iter().map(|t| (t, false))),
+            _ => {}
+        }
+    }
+    required
+}
+
+pub fn run(vault: &Path, raw: &str, limit: usize) -> QueryResponse {
+    let clauses = parse(raw);
+    if clauses.is_empty() {
+        return QueryResponse {
+            clauses,
+            results: vec![],
+            truncated: false,
+        };
+    }
+
+    let required = required_tokens(&clauses);
+    let mut notes: Vec<PathBuf> = if required.is_empty() {
+        vault_notes(vault)
+    } else {
+        let tokens: Vec<(&str, bool)> = required.iter().map(|(t, p)| (t.as_str(), *p)).collect();
+        candidate_notes(vault, &tokens)
+    };
+    notes.sort();
+
+    let mut results = Vec::new();
+    let mut truncated = false;
+
+    for path in notes {
+        let Ok(content) = fs::read_to_string(&path) else { continue };
+        let note = Note {
+            rel: path
+                .strip_prefix(vault)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .replace('\\', "/"),
+            name: path.file_stem().unwrap_or_default().to_string_lossy().to_string(),
+            frontmatter: frontmatter::parse(&content),
+            content: &content,
+        };
+
+        let mut matches = Vec::new();
+        let all = clauses.iter().enumerate().all(|(i, clause)| {
+            match (evaluate(clause, &note), clause.negated) {
+                (Some((line, preview)), false) => {
+                    matches.push(ClauseMatch { clause: i, line, preview });
+                    true
+                }
+                (None, true) => true,
+                _ => false,
+            }
+        });
+        if !all {
+            continue;
+        }
+
+        if results.len() >= limit {
+            truncated = true;
+            break;
+        }
+        results.push(QueryResult {
+            path: path.to_string_lossy().to_string(),
+            name: note.name,
+            matches,
+        });
+    }
+
+    QueryResponse {
+        clauses,
+        results,
+        truncated,
+    }
+}
+
+// -- Comandos ----------------------------------------------------------------
+
+/// Consulta estructurada: `tag:proyecto path:clientes/ status:abierto "frase exacta" -borrador`.
+#[tauri::command]
+pub async fn query_vault(vault_path: String, query: String, limit: Option<usize>) -> Result<QueryResponse, AppError> {
+    tokio::task::spawn_blocking(move || {
+        let vault = PathBuf::from(&vault_path);
+        if !vault.is_dir() {
+            return Err(AppError::not_a_dir(&vault_path));
+        }
+        Ok(run(&vault, &query, limit.unwrap_or(DEFAULT_LIMIT)))
+    })
+    .await?
+}
+
+/// Solo parsea la consulta, para mostrar sus condiciones en la UI o
+/// validarla antes de guardarla como busqueda guardada.
+#[tauri::command]
+pub fn parse_query(query: String) -> Vec<QueryClause> {
+    parse(&query)
+}
@@ -0,0 +1,360 @@
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::errors::{AppError, ErrorKind};
+use crate::{atomic_write, content_hash, git_cmd, index_path_updated, session_path, vault_notes, vault_relative};
+
+/// Maximo de coincidencias en un preview, para no congelar la UI.
+const MAX_PREVIEW_MATCHES: usize = 5000;
+
+#[derive(Deserialize, Clone)]
+pub struct ReplaceOptions {
+    pattern: String,
+    replacement: String,
+    #[serde(default)]
+    is_regex: bool,
+    #[serde(default)]
+    case_sensitive: bool,
+    #[serde(default)]
+    whole_word: bool,
+}
+
+#[derive(Serialize, Clone)]
+pub struct ReplaceMatch {
+    id: usize, // orden de la coincidencia dentro del archivo
+    line: u32,
+    matched: String,
+    replacement: String,
+    before: String, // linea original
+    after: String,  // linea con solo esta coincidencia reemplazada
+}
+
+#[derive(Serialize, Clone)]
+pub struct FileReplacePreview {
+    path: String, // relativa al vault
+    hash: String, // hash del contenido previsualizado; apply lo exige igual
+    matches: Vec<ReplaceMatch>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct ReplacePreview {
+    files: Vec<FileReplacePreview>,
+    total: usize,
+    truncated: bool,
+}
+
+/// Coincidencias aceptadas de un archivo, tal como vinieron en el preview.
+#[derive(Deserialize, Clone)]
+pub struct AcceptedFile {
+    path: String,
+    hash: String,
+    matches: Vec<usize>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct ReplaceOutcome {
+    undo_id: String,
+    changed_files: Vec<String>, // relativas al vault
+    replaced: usize,
+}
+
+/// Estado previo de los archivos tocados por un reemplazo, para deshacerlo.
+#[derive(Serialize, Deserialize)]
+struct UndoManifest {
+    vault: String,
+    head: Option<String>, // commit en HEAD al aplicar; si cambia ya no se deshace
+    files: Vec<UndoFile>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct UndoFile {
+    path: String,
+    original: String,
+    applied_hash: String,
+}
+
+fn build_regex(opts: &ReplaceOptions) -> Result<Regex, AppError> {
+    if opts.pattern.is_empty() {
+        return Err(AppError::new(ErrorKind::InvalidInput, "replace.empty_pattern"));
+    }
+    let mut pattern = if opts.is_regex {
+        opts.pattern.clone()
+    } else {
+        regex::escape(&opts.pattern)
+    };
+    if opts.whole_word {
+        pattern = format!(r"\b(?:{})\b", pattern);
+    }
+    RegexBuilder::new(&pattern)
+        .case_insensitive(!opts.case_sensitive)
+        .multi_line(true)
+        .build()
+        .map_err(|e| AppError::new(ErrorKind::InvalidInput, "replace.invalid_regex").detail(e))
+}
+
+/// Texto de reemplazo para una coincidencia: en modo regex admite `$1`, `${name}`.
+fn expand(caps: &regex::Captures, opts: &ReplaceOptions) -> String {
+    if opts.is_regex {
+        let mut out = String::new();
+        caps.expand(&opts.replacement, &mut out);
+        out
+    } else {
+        opts.replacement.clone()
+    }
+}
+
+/// Aplica solo las coincidencias cuyo id esta en `accepted` (None = todas).
+fn replace_selected(re: &Regex, content: &str, opts: &ReplaceOptions, accepted: Option<&[usize]>) -> (String, usize) {
+    let mut out = String::with_capacity(content.len());
+    let mut last = 0;
+    let mut count = 0;
+    for (id, caps) in re.captures_iter(content).enumerate() {
+        let m = caps.get(0).unwrap();
+        if accepted.is_some_and(|ids| !ids.contains(&id)) {
+            continue;
+        }
+        out.push_str(&content[last..m.start()]);
+        out.push_str(&expand(&caps, opts));
+        last = m.end();
+        count += 1;
+    }
+    out.push_str(&content[last..]);
+    (out, count)
+}
+
+fn line_bounds(content: &str, start: usize, end: usize) -> (usize, usize) {
+    let line_start = content[..start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = content[end..].find('\n').map_or(content.len(), |i| end + i);
+    (line_start, line_end)
+}
+
+fn preview_file(re: &Regex, content: &str, opts: &ReplaceOptions, budget: usize) -> Vec<ReplaceMatch> {
+    re.captures_iter(content)
+        .take(budget)
+        .enumerate()
+        .map(|(id, caps)| {
+            let m = caps.get(0).unwrap();
+            let replacement = expand(&caps, opts);
+            let (ls, le) = line_bounds(content, m.start(), m.end());
+            let before = &content[ls..le];
+            let after = format!("{}{}{}", &content[ls..m.start()], replacement, &content[m.end()..le]);
+            ReplaceMatch {
+                id,
+                line: (content[..m.start()].matches('\n').count() + 1) as u32,
+                matched: m.as_str().to_string(),
+                replacement,
+                before: before.trim().chars().take(200).collect(),
+                after: after.trim().chars().take(200).collect(),
+            }
+        })
+        .collect()
+}
+
+fn undo_dir() -> PathBuf {
+    session_path().join("undo")
+}
+
+static UNDO_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Id de un manifiesto de undo: milisegundos, proceso y contador, para que
+/// dos reemplazos en el mismo milisegundo no se pisen.
+fn new_undo_id() -> String {
+    let millis = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+    let n = UNDO_COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("{}-{}-{}", millis, std::process::id(), n)
+}
+
+/// Ruta del manifiesto `id`. Solo se aceptan ids como los de `new_undo_id`,
+/// para que no se pueda salir de `undo_dir()`.
+fn manifest_path(id: &str) -> Option<PathBuf> {
+    let valid = !id.is_empty() && id.chars().all(|c| c.is_ascii_digit() || c.is_ascii_lowercase() || c == '-');
+    valid.then(|| undo_dir().join(format!("{}.json", id)))
+}
+
+/// Ruta relativa que no sale del vault (sin `..` ni raiz).
+fn inside_vault(rel: &str) -> bool {
+    !rel.is_empty() && Path::new(rel).components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+}
+
+fn git_head(vault: &str) -> Option<String> {
+    let output = git_cmd(vault).args(["rev-parse", "HEAD"]).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+// -- Comandos ----------------------------------------------------------------
+
+/// Lista, por archivo, cada reemplazo que se haria. No modifica nada.
+#[tauri::command]
+pub async fn preview_replace(vault_path: String, options: ReplaceOptions) -> Result<ReplacePreview, AppError> {
+    tokio::task::spawn_blocking(move || {
+        let vault = PathBuf::from(&vault_path);
+        let re = build_regex(&options)?;
+
+        let mut notes = vault_notes(&vault);
+        notes.sort();
+
+        let mut files = Vec::new();
+        let mut total = 0;
+        let mut truncated = false;
+
+        for note in notes {
+            let Ok(content) = fs::read_to_string(&note) else { continue };
+            let budget = MAX_PREVIEW_MATCHES - total;
+            let matches = preview_file(&re, &content, &options, budget);
+            if matches.is_empty() {
+                continue;
+            }
+            total += matches.len();
+            files.push(FileReplacePreview {
+                path: vault_relative(&vault, &note),
+                hash: content_hash(content.as_bytes()),
+                matches,
+            });
+            if total >= MAX_PREVIEW_MATCHES {
+                truncated = true;
+                break;
+            }
+        }
+
+        Ok(ReplacePreview { files, total, truncated })
+    })
+    .await?
+}
+
+/// Aplica las coincidencias aceptadas. Si algun archivo cambio desde el
+/// preview no se toca nada. Guarda un manifiesto para `undo_replace`.
+#[tauri::command]
+pub async fn apply_replace(
+    vault_path: String,
+    options: ReplaceOptions,
+    accepted: Vec<AcceptedFile>,
+) -> Result<ReplaceOutcome, AppError> {
+    tokio::task::spawn_blocking(move || {
+        let vault = PathBuf::from(&vault_path);
+        let re = build_regex(&options)?;
+
+        // 1. Calcular todo antes de escribir
+        let mut pending = Vec::new();
+        let mut stale = Vec::new();
+        let mut replaced = 0;
+        for file in &accepted {
+            if file.matches.is_empty() {
+                continue;
+            }
+            if !inside_vault(&file.path) {
+                return Err(AppError::invalid("file.invalid_name", &file.path));
+            }
+            let path = vault.join(&file.path);
+            let original = fs::read_to_string(&path)
+                .map_err(|e| AppError::new(ErrorKind::Io, "file.read_failed").param("path", &file.path).detail(e))?;
+            if content_hash(original.as_bytes()) != file.hash {
+                stale.push(file.path.clone());
+                continue;
+            }
+            let (updated, count) = replace_selected(&re, &original, &options, Some(&file.matches));
+            if count > 0 {
+                replaced += count;
+                pending.push((path, file.path.clone(), original, updated));
+            }
+        }
+        if !stale.is_empty() {
+            return Err(AppError::new(ErrorKind::Stale, "replace.file_changed").param("files", stale.join(", ")));
+        }
+
+        // 2. Manifiesto de undo antes de tocar el vault
+        let undo_id = new_undo_id();
+        let manifest_path = undo_dir().join(format!("{}.json", undo_id));
+        let manifest = UndoManifest {
+            vault: vault_path.clone(),
+            head: git_head(&vault_path),
+            files: pending
+                .iter()
+                .map(|(_, rel, original, updated)| UndoFile {
+                    path: rel.clone(),
+                    original: original.clone(),
+                    applied_hash: content_hash(updated.as_bytes()),
+                })
+                .collect(),
+        };
+        fs::create_dir_all(undo_dir())?;
+        let json = serde_json::to_vec(&manifest)?;
+        atomic_write(&manifest_path, &json)?;
+
+        // 3. Escribir; si algo falla, restaurar lo ya escrito
+        for (i, (path, rel, _, updated)) in pending.iter().enumerate() {
+            if let Err(e) = atomic_write(path, updated.as_bytes()) {
+                for (done, _, original, _) in &pending[..i] {
+                    let _ = atomic_write(done, original.as_bytes());
+                }
+                let _ = fs::remove_file(&manifest_path);
+                return Err(AppError::new(ErrorKind::Io, "note.write_failed").param("path", rel).detail(e));
+            }
+        }
+
+        pending.iter().for_each(|(path, _, _, _)| index_path_updated(path));
+
+        Ok(ReplaceOutcome {
+            undo_id,
+            changed_files: pending.into_iter().map(|(_, rel, _, _)| rel).collect(),
+            replaced,
+        })
+    })
+    .await?
+}
+
+/// Revierte un reemplazo. Solo es posible mientras no haya commits nuevos y
+/// los archivos sigan tal como los dejo el reemplazo.
+#[tauri::command]
+pub async fn undo_replace(vault_path: String, undo_id: String) -> Result<Vec<String>, AppError> {
+    tokio::task::spawn_blocking(move || {
+        let manifest_path =
+            manifest_path(&undo_id).ok_or_else(|| AppError::invalid("replace.invalid_undo", &undo_id))?;
+        let manifest: UndoManifest = fs::read(&manifest_path)
+            .ok()
+            .and_then(|b| serde_json::from_slice(&b).ok())
+            .ok_or_else(|| AppError::new(ErrorKind::NotFound, "replace.nothing_to_undo"))?;
+
+        if manifest.vault != vault_path {
+            return Err(AppError::new(ErrorKind::InvalidInput, "replace.other_vault"));
+        }
+        if let Some(file) = manifest.files.iter().find(|f| !inside_vault(&f.path)) {
+            return Err(AppError::invalid("replace.invalid_undo", &file.path));
+        }
+        if manifest.head.is_some() && git_head(&vault_path) != manifest.head {
+            return Err(AppError::new(ErrorKind::Stale, "replace.committed"));
+        }
+
+        let vault = Path::new(&vault_path);
+        let modified: Vec<&str> = manifest
+            .files
+            .iter()
+            .filter(|f| {
+                fs::read(vault.join(&f.path)).map(|b| content_hash(&b)).ok().as_deref() != Some(f.applied_hash.as_str())
+            })
+            .map(|f| f.path.as_str())
+            .collect();
+        if !modified.is_empty() {
+            return Err(AppError::new(ErrorKind::Stale, "replace.undo_changed").param("files", modified.join(", ")));
+        }
+
+        for file in &manifest.files {
+            let path = vault.join(&file.path);
+            atomic_write(&path, file.original.as_bytes())
+                .map_err(|e| AppError::new(ErrorKind::Io, "replace.restore_failed").param("path", &file.path).detail(e))?;
+            index_path_updated(&path);
+        }
+
+        let _ = fs::remove_file(&manifest_path);
+        Ok(manifest.files.into_iter().map(|f| f.path).collect())
+    })
+    .await?
+}
@@ -0,0 +1,649 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::errors::AppError;
+use crate::vault_index::{Registry, VaultIndex};
+use crate::{atomic_write, content_hash, file_mtime_ms, session_path, vault_notes, SearchResult};
+
+/// Sube si cambia el formato en disco o la tokenizacion: fuerza reindexar.
+const INDEX_VERSION: u32 = 1;
+const MAX_RESULTS: usize = 50;
+const MAX_LINES_PER_NOTE: usize = 3;
+/// Cada aparicion en el nombre de la nota cuenta como varias en el cuerpo.
+const NAME_WEIGHT: u32 = 5;
+const BM25_K1: f32 = 1.2;
+const BM25_B: f32 = 0.75;
+/// Cada cuanto se persisten al disco los cambios incrementales.
+const FLUSH_INTERVAL_SECS: u64 = 60;
+
+// -- Normalizacion -----------------------------------------------------------
+
+/// Minusculas y sin acentos: "Canción" -> "cancion", "año" -> "ano".
+pub fn fold(text: &str) -> String {
+    text.nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Como `fold`, pero devuelve tambien, por cada byte del texto plegado, el
+/// offset en bytes del caracter original del que proviene.
+fn fold_with_offsets(text: &str) -> (String, Vec<usize>) {
+    let mut folded = String::with_capacity(text.len());
+    let mut offsets = Vec::with_capacity(text.len());
+    for (i, ch) in text.char_indices() {
+        for c in ch.to_string().nfd().filter(|c| !is_combining_mark(*c)).flat_map(char::to_lowercase) {
+            folded.push(c);
+            for _ in 0..c.len_utf8() {
+                offsets.push(i);
+            }
+        }
+    }
+    (folded, offsets)
+}
+
+pub fn tokenize(text: &str) -> Vec<String> {
+    fold(text)
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty() && t.chars().count() <= 64)
+        .map(|t| t.to_string())
+        .collect()
+}
+
+// -- Consulta ----------------------------------------------------------------
+
+struct Query {
+    terms: Vec<String>,
+    phrases: Vec<Vec<String>>,
+    /// El ultimo termino se busca como prefijo mientras el usuario escribe.
+    prefix_last: bool,
+}
+
+impl Query {
+    fn parse(raw: &str) -> Self {
+        let mut terms = Vec::new();
+        let mut phrases = Vec::new();
+        let mut prefix_last = false;
+
+        for (i, part) in raw.split('"').enumerate() {
+            if i % 2 == 1 {
+                let phrase = tokenize(part);
+                if !phrase.is_empty() {
+                    phrases.push(phrase);
+                }
+                prefix_last = false;
+            } else {
+                let tokens = tokenize(part);
+                if !tokens.is_empty() {
+                    prefix_last = !part.ends_with(char::is_whitespace);
+                    terms.extend(tokens);
+                }
+            }
+        }
+        // Comilla sin cerrar: la frase sigue escribiendose
+        if raw.matches('"').count() % 2 == 1 {
+            prefix_last = false;
+        }
+
+        Query { terms, phrases, prefix_last }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.terms.is_empty() && self.phrases.is_empty()
+    }
+
+    /// Tokens requeridos, con `true` si se aceptan como prefijo.
+    fn required(&self) -> Vec<(&str, bool)> {
+        let mut required: Vec<(&str, bool)> = Vec::new();
+        for (i, t) in self.terms.iter().enumerate() {
+            let prefix = self.prefix_last && i == self.terms.len() - 1;
+            required.push((t, prefix));
+        }
+        for t in self.phrases.iter().flatten() {
+            required.push((t, false));
+        }
+        required
+    }
+}
+
+// -- Indice ------------------------------------------------------------------
+
+#[derive(Serialize, Deserialize)]
+struct IndexedDoc {
+    path: String, // relativa al vault
+    name: String,
+    mtime: u128,
+    len: u32,
+    #[serde(skip)]
+    terms: Vec<String>, // se reconstruye desde `postings` al cargar
+}
+
+/// Indice invertido de un vault: termino -> [(doc, frecuencia)].
+#[derive(Serialize, Deserialize, Default)]
+struct SearchIndex {
+    version: u32,
+    docs: Vec<Option<IndexedDoc>>,
+    postings: BTreeMap<String, Vec<(u32, u32)>>,
+    #[serde(skip)]
+    by_path: HashMap<String, u32>,
+    #[serde(skip)]
+    dirty: bool,
+}
+
+fn index_file(vault: &Path) -> PathBuf {
+    session_path()
+        .join("search-index")
+        .join(format!("{}.json", content_hash(vault.to_string_lossy().as_bytes())))
+}
+
+impl SearchIndex {
+    fn load(vault: &Path) -> Self {
+        let loaded = fs::read(index_file(vault))
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<SearchIndex>(&bytes).ok())
+            .filter(|index| index.version == INDEX_VERSION);
+
+        let Some(mut index) = loaded else {
+            return SearchIndex {
+                version: INDEX_VERSION,
+                ..Default::default()
+            };
+        };
+
+        for (id, doc) in index.docs.iter().enumerate() {
+            if let Some(doc) = doc {
+                index.by_path.insert(doc.path.clone(), id as u32);
+            }
+        }
+        for (term, postings) in &index.postings {
+            for (id, _) in postings {
+                if let Some(Some(doc)) = index.docs.get_mut(*id as usize) {
+                    doc.terms.push(term.clone());
+                }
+            }
+        }
+        index
+    }
+
+    fn save(&mut self, vault: &Path) -> Result<(), AppError> {
+        let path = index_file(vault);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_vec(self)?;
+        atomic_write(&path, &json)?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    fn add(&mut self, rel: String, name: String, mtime: u128, content: &str) {
+        self.remove(&rel);
+
+        let mut freqs: HashMap<String, u32> = HashMap::new();
+        let body = tokenize(content);
+        let len = body.len() as u32;
+        for token in body {
+            *freqs.entry(token).or_default() += 1;
+        }
+        for token in tokenize(&name) {
+            *freqs.entry(token).or_default() += NAME_WEIGHT;
+        }
+
+        let id = match self.docs.iter().position(|d| d.is_none()) {
+            Some(free) => free as u32,
+            None => {
+                self.docs.push(None);
+                (self.docs.len() - 1) as u32
+            }
+        };
+
+        let terms: Vec<String> = freqs.keys().cloned().collect();
+        for (term, tf) in freqs {
+            self.postings.entry(term).or_default().push((id, tf));
+        }
+
+        self.by_path.insert(rel.clone(), id);
+        self.docs[id as usize] = Some(IndexedDoc {
+            path: rel,
+            name,
+            mtime,
+            len,
+            terms,
+        });
+        self.dirty = true;
+    }
+
+    fn remove(&mut self, rel: &str) {
+        let Some(id) = self.by_path.remove(rel) else { return };
+        if let Some(doc) = self.docs[id as usize].take() {
+            for term in &doc.terms {
+                if let Some(list) = self.postings.get_mut(term) {
+                    list.retain(|(d, _)| *d != id);
+                    if list.is_empty() {
+                        self.postings.remove(term);
+                    }
+                }
+            }
+        }
+        self.dirty = true;
+    }
+
+    fn index_file_at(&mut self, vault: &Path, path: &Path) {
+        self.index_note(vault, path, read_note(path));
+    }
+
+    /// Indexa una nota ya leida con `read_note`, o la quita si no se pudo leer.
+    fn index_note(&mut self, vault: &Path, path: &Path, note: Option<(String, u128)>) {
+        let rel = rel_path(vault, path);
+        match note {
+            Some((content, mtime)) => {
+                let name = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+                self.add(rel, name, mtime, &content);
+            }
+            None => self.remove(&rel),
+        }
+    }
+
+    /// Quita la nota `rel` o todas las notas bajo esa carpeta.
+    fn remove_under(&mut self, rel: &str) {
+        let gone: Vec<String> = self
+            .by_path
+            .keys()
+            .filter(|p| *p == rel || p.starts_with(&format!("{}/", rel)))
+            .cloned()
+            .collect();
+        for p in gone {
+            self.remove(&p);
+        }
+    }
+
+    /// Sincroniza con el disco: indexa notas nuevas o con mtime distinto y
+    /// quita las que ya no existen.
+    fn refresh(&mut self, vault: &Path) {
+        let mut present = HashSet::new();
+        for note in vault_notes(vault) {
+            let rel = rel_path(vault, &note);
+            let indexed_mtime = self
+                .by_path
+                .get(&rel)
+                .and_then(|id| self.docs[*id as usize].as_ref())
+                .map(|d| d.mtime);
+            if indexed_mtime != Some(file_mtime_ms(&note)) {
+                self.index_file_at(vault, &note);
+            }
+            present.insert(rel);
+        }
+
+        let gone: Vec<String> = self
+            .by_path
+            .keys()
+            .filter(|p| !present.contains(*p))
+            .cloned()
+            .collect();
+        for rel in gone {
+            self.remove(&rel);
+        }
+    }
+
+    /// Documentos que contienen el token (o algun termino con ese prefijo),
+    /// con su frecuencia sumada.
+    fn matching_docs(&self, token: &str, prefix: bool) -> HashMap<u32, u32> {
+        let mut docs: HashMap<u32, u32> = HashMap::new();
+        if prefix {
+            for (_, list) in self
+                .postings
+                .range(token.to_string()..)
+                .take_while(|(term, _)| term.starts_with(token))
+            {
+                for (id, tf) in list {
+                    *docs.entry(*id).or_default() += tf;
+                }
+            }
+        } else if let Some(list) = self.postings.get(token) {
+            docs.extend(list.iter().copied());
+        }
+        docs
+    }
+
+    /// Documentos que contienen todos los tokens, ordenados por BM25.
+    fn rank(&self, query: &Query) -> Vec<(u32, f32)> {
+        let live = self.by_path.len().max(1) as f32;
+        let avg_len = self
+            .docs
+            .iter()
+            .flatten()
+            .map(|d| d.len as f32)
+            .sum::<f32>()
+            / live;
+
+        let mut scores: Option<HashMap<u32, f32>> = None;
+        for (token, prefix) in query.required() {
+            let docs = self.matching_docs(token, prefix);
+            let df = docs.len() as f32;
+            let idf = ((live - df + 0.5) / (df + 0.5) + 1.0).ln();
+
+            let mut next = HashMap::new();
+            for (id, tf) in docs {
+                let prev = match &scores {
+                    Some(s) => match s.get(&id) {
+                        Some(score) => *score,
+                        None => continue,
+                    },
+                    None => 0.0,
+                };
+                let len = self.docs[id as usize].as_ref().map_or(0.0, |d| d.len as f32);
+                let tf = tf as f32;
+                let norm = tf + BM25_K1 * (1.0 - BM25_B + BM25_B * len / avg_len.max(1.0));
+                next.insert(id, prev + idf * tf * (BM25_K1 + 1.0) / norm);
+            }
+            scores = Some(next);
+        }
+
+        let mut ranked: Vec<(u32, f32)> = scores.unwrap_or_default().into_iter().collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+        ranked
+    }
+}
+
+fn rel_path(vault: &Path, path: &Path) -> String {
+    path.strip_prefix(vault)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+// -- Snippets ----------------------------------------------------------------
+
+/// Rango en bytes dentro de un texto: (inicio, fin).
+type ByteRange = (usize, usize);
+
+#[derive(Serialize, Clone)]
+pub struct MatchRange {
+    start: usize, // en unidades UTF-16, directamente usables como indices de string en JS
+    end: usize,
+}
+
+/// Rangos (en bytes del texto original) donde aparece algun token de la
+/// consulta al inicio de una palabra. Los tokens exactos exigen ademas fin
+/// de palabra.
+pub fn find_matches(text: &str, tokens: &[(&str, bool)]) -> Vec<ByteRange> {
+    let (folded, offsets) = fold_with_offsets(text);
+    let mut ranges = Vec::new();
+
+    for (token, prefix) in tokens {
+        for (pos, _) in folded.match_indices(token) {
+            let end = pos + token.len();
+            let starts_word = !matches!(folded[..pos].chars().next_back(), Some(c) if c.is_alphanumeric());
+            let ends_word = !matches!(folded[end..].chars().next(), Some(c) if c.is_alphanumeric());
+            if !starts_word || (!prefix && !ends_word) {
+                continue;
+            }
+            // Extender hasta el final de la palabra original
+            let orig_start = offsets[pos];
+            let orig_end = if end < offsets.len() { offsets[end] } else { text.len() };
+            let word_end = text[orig_end..]
+                .char_indices()
+                .find(|(_, c)| !c.is_alphanumeric())
+                .map_or(text.len(), |(i, _)| orig_end + i);
+            ranges.push((orig_start, if *prefix { word_end } else { orig_end }));
+        }
+    }
+
+    ranges.sort();
+    ranges.dedup();
+    ranges
+}
+
+fn utf16_len(s: &str) -> usize {
+    s.encode_utf16().count()
+}
+
+/// Recorta la linea a ~120 caracteres alrededor de la primera coincidencia
+/// y convierte los rangos a offsets dentro del preview.
+fn snippet(line: &str, ranges: &[ByteRange]) -> (String, Vec<MatchRange>) {
+    let first = ranges.first().map_or(0, |r| r.0);
+    let chars_before = line[..first].chars().count();
+    let skip = chars_before.saturating_sub(40);
+    let start = line.char_indices().nth(skip).map_or(0, |(i, _)| i);
+    let end = line[start..].char_indices().nth(120).map_or(line.len(), |(i, _)| start + i);
+    let preview = &line[start..end];
+
+    let matches = ranges
+        .iter()
+        .filter(|(s, e)| *s >= start && *e <= end)
+        .map(|(s, e)| MatchRange {
+            start: utf16_len(&line[start..*s]),
+            end: utf16_len(&line[start..*e]),
+        })
+        .collect();
+    (preview.to_string(), matches)
+}
+
+pub fn contains_phrase(tokens: &[String], phrase: &[String]) -> bool {
+    tokens.windows(phrase.len()).any(|w| w == phrase)
+}
+
+// -- Registry ----------------------------------------------------------------
+
+struct LoadedIndex {
+    index: SearchIndex,
+    last_flush: Instant,
+}
+
+/// Contenido y mtime de una nota, leidos fuera del lock del registry.
+fn read_note(path: &Path) -> Option<(String, u128)> {
+    let content = fs::read_to_string(path).ok()?;
+    Some((content, file_mtime_ms(path)))
+}
+
+/// La primera vez en la sesion el indice se carga del disco y se sincroniza;
+/// despues se mantiene con actualizaciones incrementales.
+impl VaultIndex for LoadedIndex {
+    type Note = (String, u128);
+
+    fn build(vault: &Path) -> Self {
+        let mut index = SearchIndex::load(vault);
+        index.refresh(vault);
+        if index.dirty {
+            let _ = index.save(vault);
+        }
+        LoadedIndex {
+            index,
+            last_flush: Instant::now(),
+        }
+    }
+
+    fn read_note(_vault: &Path, path: &Path) -> Option<(String, u128)> {
+        read_note(path)
+    }
+
+    fn update_note(&mut self, vault: &Path, path: PathBuf, note: Option<(String, u128)>) {
+        self.index.index_note(vault, &path, note);
+    }
+
+    fn remove_notes(&mut self, vault: &Path, path: &Path) {
+        self.index.remove_under(&rel_path(vault, path));
+    }
+}
+
+static SEARCH_INDEX: Registry<LoadedIndex> = Registry::new();
+
+/// Ejecuta `f` sobre el indice del vault y persiste los cambios pendientes
+/// cada `FLUSH_INTERVAL_SECS`.
+fn with_index<F, R>(vault: &Path, f: F) -> R
+where
+    F: FnOnce(&mut SearchIndex) -> R,
+{
+    SEARCH_INDEX.with_vault(vault, |loaded| {
+        let result = f(&mut loaded.index);
+        if loaded.index.dirty && loaded.last_flush.elapsed().as_secs() >= FLUSH_INTERVAL_SECS {
+            let _ = loaded.index.save(vault);
+            loaded.last_flush = Instant::now();
+        }
+        result
+    })
+}
+
+/// Reindexa `path` (nota o carpeta) en el vault cargado que lo contenga.
+pub fn path_updated(path: &Path) {
+    SEARCH_INDEX.path_updated(path);
+}
+
+pub fn path_removed(path: &Path) {
+    SEARCH_INDEX.path_removed(path);
+}
+
+/// Descarta el indice cargado del vault. En el siguiente uso se vuelve a
+/// cargar del disco y se sincroniza con las notas que existan entonces.
+pub fn forget(vault: &Path) {
+    SEARCH_INDEX.forget(vault);
+}
+
+// -- Busqueda ----------------------------------------------------------------
+
+pub fn search(vault: &Path, raw_query: &str) -> Vec<SearchResult> {
+    let query = Query::parse(raw_query);
+    if query.is_empty() {
+        return vec![];
+    }
+
+    let ranked: Vec<(String, String, f32)> = with_index(vault, |index| {
+        index
+            .rank(&query)
+            .into_iter()
+            .filter_map(|(id, score)| {
+                let doc = index.docs[id as usize].as_ref()?;
+                Some((doc.path.clone(), doc.name.clone(), score))
+            })
+            .collect()
+    });
+
+    let required = query.required();
+    let mut results = Vec::new();
+
+    for (rel, name, score) in ranked {
+        if results.len() >= MAX_RESULTS {
+            break;
+        }
+        let path = vault.join(&rel);
+        let Ok(content) = fs::read_to_string(&path) else { continue };
+
+        if !query.phrases.is_empty() {
+            let tokens = tokenize(&content);
+            if !query.phrases.iter().all(|p| contains_phrase(&tokens, p)) {
+                continue;
+            }
+        }
+
+        let path_str = path.to_string_lossy().to_string();
+        let name_ranges = find_matches(&name, &required);
+        if !name_ranges.is_empty() {
+            results.push(SearchResult {
+                path: path_str.clone(),
+                name: name.clone(),
+                match_type: "name".to_string(),
+                preview: String::new(),
+                line: 0,
+                score,
+                matches: name_ranges
+                    .iter()
+                    .map(|(s, e)| MatchRange {
+                        start: utf16_len(&name[..*s]),
+                        end: utf16_len(&name[..*e]),
+                    })
+                    .collect(),
+            });
+        }
+
+        // Lineas con mas terminos distintos primero
+        let mut lines: Vec<(usize, usize, Vec<ByteRange>)> = content
+            .lines()
+            .enumerate()
+            .filter_map(|(i, line)| {
+                let ranges = find_matches(line, &required);
+                if ranges.is_empty() {
+                    return None;
+                }
+                let distinct = fold(line);
+                let hits = required.iter().filter(|(t, _)| distinct.contains(*t)).count();
+                Some((i, hits, ranges))
+            })
+            .collect();
+        lines.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        let all_lines: Vec<&str> = content.lines().collect();
+        for (i, _, ranges) in lines.into_iter().take(MAX_LINES_PER_NOTE) {
+            let trimmed_start = all_lines[i].len() - all_lines[i].trim_start().len();
+            let shifted: Vec<ByteRange> = ranges
+                .iter()
+                .map(|(s, e)| (s.saturating_sub(trimmed_start), e.saturating_sub(trimmed_start)))
+                .collect();
+            let (preview, matches) = snippet(all_lines[i].trim_start(), &shifted);
+            results.push(SearchResult {
+                path: path_str.clone(),
+                name: name.clone(),
+                match_type: "content".to_string(),
+                preview,
+                line: (i + 1) as u32,
+                score,
+                matches,
+            });
+        }
+    }
+
+    results.truncate(MAX_RESULTS);
+    results
+}
+
+/// Notas que contienen todos los tokens (`true` = como prefijo), segun el
+/// indice. Sirve para acotar evaluaciones mas caras sin leer todo el vault.
+pub fn candidate_notes(vault: &Path, tokens: &[(&str, bool)]) -> Vec<PathBuf> {
+    with_index(vault, |index| {
+        let mut candidates: Option<HashSet<u32>> = None;
+        for (token, prefix) in tokens {
+            let docs: HashSet<u32> = index.matching_docs(token, *prefix).into_keys().collect();
+            candidates = Some(match candidates {
+                Some(prev) => prev.intersection(&docs).copied().collect(),
+                None => docs,
+            });
+        }
+        candidates
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|id| index.docs[id as usize].as_ref())
+            .map(|doc| vault.join(&doc.path))
+            .collect()
+    })
+}
+
+/// Descarta el indice del vault y lo reconstruye desde cero.
+#[tauri::command]
+pub async fn rebuild_search_index(vault_path: String) -> Result<u32, AppError> {
+    tokio::task::spawn_blocking(move || {
+        let vault = PathBuf::from(&vault_path);
+        if !vault.is_dir() {
+            return Err(AppError::not_a_dir(&vault_path));
+        }
+        let mut index = SearchIndex {
+            version: INDEX_VERSION,
+            ..Default::default()
+        };
+        index.refresh(&vault);
+        index.save(&vault)?;
+        let count = index.by_path.len() as u32;
+        SEARCH_INDEX.replace(
+            vault,
+            LoadedIndex {
+                index,
+                last_flush: Instant::now(),
+            },
+        );
+        Ok(count)
+    })
+    .await?
+}
@@ -0,0 +1,382 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::Emitter;
+
+use crate::errors::{AppError, ErrorKind};
+use crate::i18n::{self, Locale};
+use crate::periodic::PeriodicSettings;
+use crate::{atomic_write, session_path};
+
+/// Version del esquema de `settings.json`. Al cambiarlo hay que agregar la
+/// migracion correspondiente al final de `MIGRATIONS`.
+pub const CURRENT_VERSION: u32 = 1;
+
+// -- Esquema -----------------------------------------------------------------
+
+/// Preferencias globales de la app. Los campos que falten en el archivo toman
+/// su valor por defecto y los desconocidos se ignoran.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Settings {
+    pub version: u32,
+    /// Idioma de los mensajes del backend; `None` = el del sistema.
+    pub locale: Option<String>,
+    pub editor: EditorSettings,
+    pub vault: VaultSettings,
+    pub sync: SyncSettings,
+    pub periodic: PeriodicPrefs,
+    pub claude: ClaudeSettings,
+    pub updates: UpdateSettings,
+    /// Problemas al leer el archivo (p. ej. JSON roto). Solo se informan al
+    /// frontend; nunca se guardan.
+    #[serde(skip_deserializing, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<AppError>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct EditorSettings {
+    pub autosave: bool,
+    pub toc: bool,
+    pub font_size: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct VaultSettings {
+    pub show_attachments: bool,
+    pub use_gitignore: bool,
+    pub attachments_folder: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct SyncSettings {
+    pub indicators: bool,
+    pub color: String,
+    pub auto_sync: bool,
+    pub auto_sync_minutes: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct PeriodicPrefs {
+    pub daily: Option<PeriodicSettings>,
+    pub weekly: Option<PeriodicSettings>,
+    pub monthly: Option<PeriodicSettings>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct ClaudeSettings {
+    pub workdir: Option<String>,
+    pub project_dir: Option<String>,
+    pub commands_dir: Option<String>,
+    pub agents_only: bool,
+    pub context: String,
+    pub mcp_selected: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct UpdateSettings {
+    pub whatsnew_version: Option<String>,
+    pub dismissed_version: Option<String>,
+}
+
+const FONT_SIZE_RANGE: (u32, u32) = (10, 24);
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            version: CURRENT_VERSION,
+            locale: None,
+            editor: EditorSettings::default(),
+            vault: VaultSettings::default(),
+            sync: SyncSettings::default(),
+            periodic: PeriodicPrefs::default(),
+            claude: ClaudeSettings::default(),
+            updates: UpdateSettings::default(),
+            warnings: vec![],
+        }
+    }
+}
+
+impl Default for EditorSettings {
+    fn default() -> Self {
+        EditorSettings { autosave: true, toc: false, font_size: 14 }
+    }
+}
+
+impl Default for SyncSettings {
+    fn default() -> Self {
+        SyncSettings {
+            indicators: true,
+            color: "#10b981".to_string(),
+            auto_sync: false,
+            auto_sync_minutes: 5,
+        }
+    }
+}
+
+impl Settings {
+    /// Comprueba los valores que el tipo no alcanza a restringir. Devuelve la
+    /// clave del primero que no es valido.
+    fn invalid_key(&self) -> Option<&'static str> {
+        let (min, max) = FONT_SIZE_RANGE;
+        if !(min..=max).contains(&self.editor.font_size) {
+            return Some("editor.font_size");
+        }
+        if self.sync.auto_sync_minutes == 0 {
+            return Some("sync.auto_sync_minutes");
+        }
+        if !is_hex_color(&self.sync.color) {
+            return Some("sync.color");
+        }
+        if self.locale.as_deref().is_some_and(|l| Locale::parse(l).is_none()) {
+            return Some("locale");
+        }
+        None
+    }
+
+    /// Vuelve a su valor por defecto lo que no pase `invalid_key` (archivo
+    /// editado a mano, version anterior con otros limites...).
+    fn sanitize(&mut self) {
+        let defaults = Settings::default();
+        while let Some(key) = self.invalid_key() {
+            match key {
+                "editor.font_size" => self.editor.font_size = defaults.editor.font_size,
+                "sync.auto_sync_minutes" => self.sync.auto_sync_minutes = defaults.sync.auto_sync_minutes,
+                "sync.color" => self.sync.color = defaults.sync.color.clone(),
+                _ => self.locale = None,
+            }
+        }
+    }
+
+    pub fn locale(&self) -> Option<Locale> {
+        self.locale.as_deref().and_then(Locale::parse)
+    }
+}
+
+fn is_hex_color(value: &str) -> bool {
+    value.len() == 7 && value.starts_with('#') && value[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+// -- Migraciones -------------------------------------------------------------
+
+/// `MIGRATIONS[n]` lleva un archivo de la version `n` a la `n + 1`. La
+/// version 0 son las claves `potato-*` que la app guardaba en localStorage.
+const MIGRATIONS: [fn(&mut Map<String, Value>); CURRENT_VERSION as usize] = [from_local_storage];
+
+/// v0 -> v1: de claves planas de localStorage (todo texto) al esquema por
+/// secciones. Los valores que no se puedan interpretar se descartan.
+fn from_local_storage(map: &mut Map<String, Value>) {
+    let old = std::mem::take(map);
+    let text = |key: &str| old.get(key).and_then(Value::as_str).filter(|v| !v.is_empty());
+    let flag = |key: &str| text(key).and_then(|v| v.parse::<bool>().ok()).map(Value::Bool);
+    let number = |key: &str| text(key).and_then(|v| v.trim().parse::<u32>().ok()).map(Value::from);
+    let string = |key: &str| text(key).map(Value::from);
+    let json = |key: &str| text(key).and_then(|v| serde_json::from_str::<Value>(v).ok());
+
+    let mut section = |name: &str, fields: Vec<(&str, Option<Value>)>| {
+        let entries: Map<String, Value> = fields
+            .into_iter()
+            .filter_map(|(field, value)| value.map(|v| (field.to_string(), v)))
+            .collect();
+        map.insert(name.to_string(), Value::Object(entries));
+    };
+
+    section("editor", vec![
+        ("autosave", flag("potato-autosave")),
+        ("toc", flag("potato-toc")),
+        ("font_size", number("potato-font-size")),
+    ]);
+    section("vault", vec![
+        ("show_attachments", flag("potato-attachments")),
+        ("use_gitignore", flag("potato-use-gitignore")),
+        ("attachments_folder", string("potato-attachments-folder")),
+    ]);
+    section("sync", vec![
+        ("indicators", flag("potato-sync-indicators")),
+        ("color", string("potato-sync-color")),
+        ("auto_sync", flag("potato-autosync")),
+        ("auto_sync_minutes", number("potato-autosync-minutes")),
+    ]);
+    section("periodic", vec![
+        ("daily", json("potato-periodic-daily").filter(Value::is_object)),
+        ("weekly", json("potato-periodic-weekly").filter(Value::is_object)),
+        ("monthly", json("potato-periodic-monthly").filter(Value::is_object)),
+    ]);
+    section("claude", vec![
+        ("workdir", string("potato-claude-workdir")),
+        ("project_dir", string("potato-claude-projectdir")),
+        ("commands_dir", string("potato-claude-commandsdir")),
+        ("agents_only", flag("potato-claude-agents-only")),
+        ("context", string("potato-claude-context")),
+        ("mcp_selected", json("potato-mcp-selected").filter(Value::is_array)),
+    ]);
+    section("updates", vec![
+        ("whatsnew_version", string("potato-whatsnew-version")),
+        ("dismissed_version", string("potato-update-dismissed")),
+    ]);
+    if let Some(locale) = string("potato-locale") {
+        map.insert("locale".to_string(), locale);
+    }
+}
+
+/// Aplica las migraciones pendientes y convierte al esquema actual. Un
+/// archivo de una version mas nueva se lee tal cual.
+fn migrate(mut map: Map<String, Value>) -> Settings {
+    let from = map.get("version").and_then(Value::as_u64).unwrap_or(0) as usize;
+    for migration in MIGRATIONS.iter().skip(from) {
+        migration(&mut map);
+    }
+    map.insert("version".to_string(), Value::from(CURRENT_VERSION.max(from as u32)));
+
+    let mut settings = parse_lenient(map);
+    settings.sanitize();
+    settings
+}
+
+/// Como `serde_json::from_value`, pero una seccion con tipos incorrectos
+/// se reemplaza por la de defecto en vez de perder todo el archivo.
+fn parse_lenient(mut map: Map<String, Value>) -> Settings {
+    if let Ok(settings) = serde_json::from_value(Value::Object(map.clone())) {
+        return settings;
+    }
+    let keys: Vec<String> = map.keys().cloned().collect();
+    for key in keys {
+        let mut probe = Map::new();
+        probe.insert(key.clone(), map[&key].clone());
+        if serde_json::from_value::<Settings>(Value::Object(probe)).is_err() {
+            map.remove(&key);
+        }
+    }
+    serde_json::from_value(Value::Object(map)).unwrap_or_default()
+}
+
+// -- Archivo -----------------------------------------------------------------
+
+static SETTINGS: Mutex<Option<Settings>> = Mutex::new(None);
+
+fn settings_file() -> PathBuf {
+    session_path().join("settings.json")
+}
+
+/// Lee `settings.json`. `None` si no existe todavia; un archivo ilegible se
+/// aparta como `settings.invalid.json` y se usan los valores por defecto.
+/// Lo que falle queda en `warnings`.
+fn read_file() -> Option<Settings> {
+    let path = settings_file();
+    let content = fs::read_to_string(&path).ok()?;
+    let map = match serde_json::from_str::<Value>(&content) {
+        Ok(Value::Object(map)) => map,
+        _ => {
+            let _ = fs::rename(&path, path.with_file_name("settings.invalid.json"));
+            let mut settings = Settings::default();
+            settings.warnings.push(AppError::new(ErrorKind::InvalidInput, "settings.invalid_file"));
+            return Some(settings);
+        }
+    };
+    let outdated = map.get("version").and_then(Value::as_u64).unwrap_or(0) < CURRENT_VERSION as u64;
+    let mut settings = migrate(map);
+    if outdated {
+        if let Err(e) = write_file(&settings) {
+            settings
+                .warnings
+                .push(AppError::new(ErrorKind::Io, "settings.migrate_failed").detail(e));
+        }
+    }
+    Some(settings)
+}
+
+fn write_file(settings: &Settings) -> Result<(), AppError> {
+    let path = settings_file();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let json = serde_json::to_string_pretty(&Settings {
+        warnings: vec![],
+        ..settings.clone()
+    })?;
+    Ok(atomic_write(&path, json.as_bytes())?)
+}
+
+/// Preferencias actuales; se leen del disco la primera vez.
+pub fn current() -> Settings {
+    let mut guard = SETTINGS.lock().unwrap();
+    guard.get_or_insert_with(|| read_file().unwrap_or_default()).clone()
+}
+
+fn store(settings: Settings) -> Result<Settings, AppError> {
+    write_file(&settings)?;
+    *SETTINGS.lock().unwrap() = Some(settings.clone());
+    Ok(settings)
+}
+
+// -- Comandos ----------------------------------------------------------------
+
+#[derive(Serialize, Clone)]
+struct SettingsChanged {
+    key: Option<String>, // None = se reemplazo todo (importacion)
+    settings: Settings,
+}
+
+fn notify(app: &tauri::AppHandle, key: Option<String>, settings: &Settings) {
+    let _ = app.emit("settings-changed", SettingsChanged { key, settings: settings.clone() });
+}
+
+#[tauri::command]
+pub fn get_settings() -> Settings {
+    current()
+}
+
+/// Cambia una preferencia por su ruta con puntos ("editor.autosave",
+/// "sync.color", "claude.mcp_selected"...) y emite `settings-changed`.
+#[tauri::command]
+pub fn set_setting(app: tauri::AppHandle, key: String, value: Value) -> Result<Settings, AppError> {
+    let mut tree = serde_json::to_value(current())?;
+    let pointer = format!("/{}", key.replace('.', "/"));
+    let slot = tree
+        .pointer_mut(&pointer)
+        .filter(|_| key != "version" && key != "warnings")
+        .ok_or_else(|| AppError::new(ErrorKind::InvalidInput, "settings.unknown_key").param("key", &key))?;
+    *slot = value;
+
+    let invalid = || AppError::new(ErrorKind::InvalidInput, "settings.invalid_value").param("key", &key);
+    let mut settings: Settings = serde_json::from_value(tree).map_err(|e| invalid().detail(e))?;
+    if settings.locale.as_deref() == Some("") {
+        settings.locale = None;
+    }
+    if settings.invalid_key().is_some() {
+        return Err(invalid());
+    }
+
+    let settings = store(settings)?;
+    if key == "locale" {
+        i18n::set_current(settings.locale().unwrap_or_else(Locale::from_env));
+    }
+    notify(&app, Some(key), &settings);
+    Ok(settings)
+}
+
+/// Importacion unica de las preferencias que la app guardaba en
+/// localStorage. Si `settings.json` ya existe no toca nada y devuelve lo que
+/// hay, asi que el frontend puede llamarla en cada arranque.
+#[tauri::command]
+pub fn import_local_settings(app: tauri::AppHandle, values: HashMap<String, String>) -> Result<Settings, AppError> {
+    if settings_file().exists() {
+        return Ok(current());
+    }
+    let map = values.into_iter().map(|(k, v)| (k, Value::String(v))).collect();
+    let settings = store(migrate(map))?;
+    if let Some(locale) = settings.locale() {
+        i18n::set_current(locale);
+    }
+    notify(&app, None, &settings);
+    Ok(settings)
+}
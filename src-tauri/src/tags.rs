@@ -0,0 +1,371 @@
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::errors::{AppError, ErrorKind};
+use crate::vault_index::{Registry, VaultIndex};
+use crate::{atomic_write, frontmatter, index_path_updated, vault_notes, vault_relative};
+
+/// `#tag` en el cuerpo: precedido de inicio de linea o espacio, sin espacio
+/// tras el `#` (asi `# Titulo` no cuenta). Admite tags anidados `#a/b`.
+fn inline_tag_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?:^|\s)#([\p{L}\p{N}_][\p{L}\p{N}_/-]*)").unwrap())
+}
+
+/// Tags del frontmatter: `tags: [a, b]`, `tags: a, b` o lista YAML.
+/// Acepta tambien la clave singular `tag` y el `#` opcional.
+pub fn frontmatter_tags(fm: &Value) -> Vec<String> {
+    let Some(obj) = fm.as_object() else {
+        return vec![];
+    };
+    let mut tags = Vec::new();
+    for key in ["tags", "tag"] {
+        match obj.get(key) {
+            Some(Value::Array(items)) => {
+                tags.extend(items.iter().filter_map(|v| v.as_str()).map(str::to_string))
+            }
+            Some(Value::String(s)) => tags.extend(
+                s.split([',', ' '])
+                    .filter(|t| !t.is_empty())
+                    .map(str::to_string),
+            ),
+            _ => {}
+        }
+    }
+    tags.into_iter()
+        .map(|t| t.trim().trim_start_matches('#').to_string())
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
+/// Tags `#inline` del cuerpo, ignorando bloques de codigo.
+pub fn inline_tags(body: &str) -> Vec<String> {
+    let mut tags = Vec::new();
+    let mut in_code = false;
+    for line in body.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+        for cap in inline_tag_regex().captures_iter(line) {
+            let tag = cap[1].trim_end_matches('/');
+            // `#123` suele ser una referencia a issue, no un tag
+            if !tag.chars().all(|c| c.is_ascii_digit()) {
+                tags.push(tag.to_string());
+            }
+        }
+    }
+    tags
+}
+
+/// Todos los tags de una nota (frontmatter + inline), sin repetir.
+pub fn note_tags(content: &str) -> Vec<String> {
+    let (_, body) = frontmatter::split(content);
+    let mut tags = frontmatter::parse(content)
+        .map(|fm| frontmatter_tags(&fm))
+        .unwrap_or_default();
+    for tag in inline_tags(body) {
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+// -- Indice ------------------------------------------------------------------
+
+/// Tags de cada nota del vault, tal como estan escritos.
+struct VaultTags {
+    notes: BTreeMap<PathBuf, Vec<String>>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct TagCount {
+    tag: String,
+    count: u32, // notas que lo usan
+}
+
+#[derive(Serialize, Clone)]
+pub struct TaggedNote {
+    path: String,
+    name: String,
+    tags: Vec<String>, // los tags de la nota que coincidieron
+}
+
+fn read_tags(path: &Path) -> Option<Vec<String>> {
+    fs::read_to_string(path).ok().map(|c| note_tags(&c))
+}
+
+impl VaultIndex for VaultTags {
+    type Note = Vec<String>;
+
+    fn build(vault: &Path) -> Self {
+        let notes = vault_notes(vault)
+            .into_iter()
+            .filter_map(|p| read_tags(&p).map(|t| (p, t)))
+            .collect();
+        VaultTags { notes }
+    }
+
+    fn read_note(_vault: &Path, path: &Path) -> Option<Vec<String>> {
+        read_tags(path)
+    }
+
+    fn update_note(&mut self, _vault: &Path, path: PathBuf, tags: Option<Vec<String>>) {
+        match tags {
+            Some(tags) => self.notes.insert(path, tags),
+            None => self.notes.remove(&path),
+        };
+    }
+
+    fn remove_notes(&mut self, _vault: &Path, path: &Path) {
+        self.notes.retain(|note, _| !note.starts_with(path));
+    }
+}
+
+static TAG_INDEX: Registry<VaultTags> = Registry::new();
+
+fn with_vault<F, R>(vault_path: &str, f: F) -> R
+where
+    F: FnOnce(&VaultTags) -> R,
+{
+    TAG_INDEX.with_vault(Path::new(vault_path), |index| f(index))
+}
+
+pub fn path_updated(path: &Path) {
+    TAG_INDEX.path_updated(path);
+}
+
+pub fn path_removed(path: &Path) {
+    TAG_INDEX.path_removed(path);
+}
+
+pub fn forget(vault: &Path) {
+    TAG_INDEX.forget(vault);
+}
+
+// -- Renombrar ---------------------------------------------------------------
+
+/// Nuevo nombre de `tag` si es `from` o un tag anidado bajo `from`
+/// (`proyecto/web` -> `trabajo/web`). Sin distinguir mayusculas.
+fn renamed(tag: &str, from: &str, to: &str) -> Option<String> {
+    let lower = tag.to_lowercase();
+    let from = from.to_lowercase();
+    if lower == from {
+        return Some(to.to_string());
+    }
+    lower
+        .strip_prefix(&format!("{}/", from))
+        .map(|_| format!("{}{}", to, tag.chars().skip(from.chars().count()).collect::<String>()))
+}
+
+fn rewrite_inline(body: &str, from: &str, to: &str) -> String {
+    let mut out = String::with_capacity(body.len());
+    let mut in_code = false;
+    for line in body.split_inclusive('\n') {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+        }
+        if in_code || line.trim_start().starts_with("```") {
+            out.push_str(line);
+            continue;
+        }
+        let mut last = 0;
+        for cap in inline_tag_regex().captures_iter(line) {
+            let m = cap.get(1).unwrap();
+            let tag = m.as_str().trim_end_matches('/');
+            if let Some(new_tag) = renamed(tag, from, to) {
+                out.push_str(&line[last..m.start()]);
+                out.push_str(&new_tag);
+                last = m.start() + tag.len();
+            }
+        }
+        out.push_str(&line[last..]);
+    }
+    out
+}
+
+/// Reescribe `tags`/`tag` del frontmatter. Un merge puede dejar duplicados:
+/// se conserva la primera aparicion. Respeta la forma original (lista o texto).
+fn rewrite_frontmatter(content: &str, from: &str, to: &str) -> Result<String, AppError> {
+    let Some(fm) = frontmatter::parse(content) else {
+        return Ok(content.to_string());
+    };
+    let mut content = content.to_string();
+    for key in ["tags", "tag"] {
+        let (items, separator) = match fm.get(key) {
+            Some(Value::Array(items)) => (
+                items.iter().map(frontmatter::value_text).collect::<Vec<_>>(),
+                None,
+            ),
+            Some(Value::String(s)) => (
+                s.split([',', ' ']).filter(|t| !t.is_empty()).map(str::to_string).collect(),
+                Some(if s.contains(',') { ", " } else { " " }),
+            ),
+            _ => continue,
+        };
+
+        let mut changed = false;
+        let mut updated: Vec<String> = Vec::new();
+        for item in items {
+            let hash = if item.trim().starts_with('#') { "#" } else { "" };
+            let tag = item.trim().trim_start_matches('#');
+            let new_item = match renamed(tag, from, to) {
+                Some(new_tag) => {
+                    changed = true;
+                    format!("{}{}", hash, new_tag)
+                }
+                None => item.trim().to_string(),
+            };
+            let key_of = |t: &str| t.trim_start_matches('#').to_lowercase();
+            if !updated.iter().any(|u| key_of(u) == key_of(&new_item)) {
+                updated.push(new_item);
+            }
+        }
+        if !changed {
+            continue;
+        }
+
+        let value = match separator {
+            Some(sep) => Value::String(updated.join(sep)),
+            None => Value::Array(updated.into_iter().map(Value::String).collect()),
+        };
+        content = frontmatter::set_property(&content, key, &value)?;
+    }
+    Ok(content)
+}
+
+/// Tag escrito por el usuario, sin `#`. Error si no es un tag valido.
+fn clean_tag(raw: &str) -> Result<String, AppError> {
+    let tag = raw.trim().trim_start_matches('#').trim_end_matches('/');
+    let valid = !tag.is_empty()
+        && !tag.chars().all(|c| c.is_ascii_digit())
+        && !tag.starts_with(['/', '-'])
+        && tag.chars().all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '/'));
+    if !valid {
+        return Err(AppError::invalid("tag.invalid", raw));
+    }
+    Ok(tag.to_string())
+}
+
+// -- Comandos ----------------------------------------------------------------
+
+/// Todos los tags del vault con el numero de notas que los usan. Las
+/// variantes de mayusculas se agrupan bajo la primera forma encontrada.
+#[tauri::command]
+pub async fn get_tags(vault_path: String) -> Result<Vec<TagCount>, AppError> {
+    tokio::task::spawn_blocking(move || {
+        with_vault(&vault_path, |index| {
+            let mut counts: BTreeMap<String, TagCount> = BTreeMap::new();
+            for tags in index.notes.values() {
+                let mut seen = HashSet::new();
+                for tag in tags.iter().filter(|t| seen.insert(t.to_lowercase())) {
+                    counts
+                        .entry(tag.to_lowercase())
+                        .or_insert_with(|| TagCount { tag: tag.clone(), count: 0 })
+                        .count += 1;
+                }
+            }
+            Ok(counts.into_values().collect())
+        })
+    })
+    .await?
+}
+
+/// Notas con el tag (o un tag anidado bajo el, salvo `nested: false`).
+#[tauri::command]
+pub async fn get_notes_with_tag(
+    vault_path: String,
+    tag: String,
+    nested: Option<bool>,
+) -> Result<Vec<TaggedNote>, AppError> {
+    tokio::task::spawn_blocking(move || {
+        let wanted = tag.trim().trim_start_matches('#').to_lowercase();
+        let nested = nested.unwrap_or(true);
+        with_vault(&vault_path, |index| {
+            let notes = index
+                .notes
+                .iter()
+                .filter_map(|(path, tags)| {
+                    let matched: Vec<String> = tags
+                        .iter()
+                        .filter(|t| {
+                            let t = t.to_lowercase();
+                            t == wanted || (nested && t.starts_with(&format!("{}/", wanted)))
+                        })
+                        .cloned()
+                        .collect();
+                    (!matched.is_empty()).then(|| TaggedNote {
+                        path: path.to_string_lossy().to_string(),
+                        name: path.file_stem().unwrap_or_default().to_string_lossy().to_string(),
+                        tags: matched,
+                    })
+                })
+                .collect();
+            Ok(notes)
+        })
+    })
+    .await?
+}
+
+/// Renombra un tag (y sus anidados) en todo el vault, en el frontmatter y en
+/// el cuerpo. Si `to` ya existe, ambos quedan fusionados. Todo o nada:
+/// si una escritura falla se restauran las notas ya escritas.
+/// Devuelve las notas modificadas, relativas al vault.
+#[tauri::command]
+pub async fn rename_tag(vault_path: String, from: String, to: String) -> Result<Vec<String>, AppError> {
+    tokio::task::spawn_blocking(move || {
+        let vault = PathBuf::from(&vault_path);
+        let from = clean_tag(&from)?;
+        let to = clean_tag(&to)?;
+        if from == to {
+            return Ok(vec![]);
+        }
+
+        let candidates: Vec<PathBuf> = with_vault(&vault_path, |index| {
+            index
+                .notes
+                .iter()
+                .filter(|(_, tags)| tags.iter().any(|t| renamed(t, &from, &to).is_some()))
+                .map(|(p, _)| p.clone())
+                .collect()
+        });
+
+        let mut pending = Vec::new();
+        for note in candidates {
+            let Ok(content) = fs::read_to_string(&note) else { continue };
+            let with_fm = rewrite_frontmatter(&content, &from, &to)
+                .map_err(|e| AppError::new(e.kind, "tag.rewrite_failed").param("path", note.display()).detail(e))?;
+            let (yaml, body) = frontmatter::split(&with_fm);
+            let head_len = with_fm.len() - body.len();
+            let updated = match yaml {
+                Some(_) => format!("{}{}", &with_fm[..head_len], rewrite_inline(body, &from, &to)),
+                None => rewrite_inline(&with_fm, &from, &to),
+            };
+            if updated != content {
+                pending.push((note, content, updated));
+            }
+        }
+
+        for (i, (note, _, updated)) in pending.iter().enumerate() {
+            if let Err(e) = atomic_write(note, updated.as_bytes()) {
+                for (done, original, _) in &pending[..i] {
+                    let _ = atomic_write(done, original.as_bytes());
+                }
+                return Err(AppError::new(ErrorKind::Io, "tag.rename_failed").param("path", note.display()).detail(e));
+            }
+        }
+
+        pending.iter().for_each(|(note, _, _)| index_path_updated(note));
+        Ok(pending.iter().map(|(note, _, _)| vault_relative(&vault, note)).collect())
+    })
+    .await?
+}
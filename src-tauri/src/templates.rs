@@ -0,0 +1,221 @@
+use chrono::{Datelike, Local, NaiveDateTime, Timelike};
+use regex::Regex;
+use serde::Serialize;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::OnceLock;
+use walkdir::WalkDir;
+
+use crate::errors::{AppError, ErrorKind};
+use crate::{frontmatter, git_cmd, vault_config};
+
+/// Carpeta de plantillas por defecto, relativa al vault. Al ser oculta no
+/// aparece en el arbol ni en los indices. Cada vault puede cambiarla con
+/// `templates_folder` en `.potato/config`.
+pub const TEMPLATES_DIR: &str = ".potato/templates";
+
+/// Marca donde queda el cursor al abrir la nota creada.
+const CURSOR_MARKER: &str = "cursor";
+
+#[derive(Serialize, Clone)]
+pub struct TemplateInfo {
+    name: String, // relativo a la carpeta de plantillas, sin `.md`
+    path: String,
+    description: String,
+}
+
+/// Datos disponibles para las variables de una plantilla.
+pub struct TemplateContext<'a> {
+    pub title: &'a str,
+    pub vault: &'a Path,
+    pub date: NaiveDateTime, // `{{date}}` y `{{time}}`; en notas periodicas, la fecha del periodo
+}
+
+impl<'a> TemplateContext<'a> {
+    pub fn now(title: &'a str, vault: &'a Path) -> Self {
+        TemplateContext {
+            title,
+            vault,
+            date: Local::now().naive_local(),
+        }
+    }
+}
+
+// -- Fechas ------------------------------------------------------------------
+
+/// Tokens soportados, de mas largo a mas corto para que `YYYY` gane a `YY`.
+const DATE_TOKENS: &[&str] = &[
+    "YYYY", "GGGG", "MMMM", "dddd", "MMM", "ddd", "DDD", "YY", "MM", "DD", "HH", "hh", "mm", "ss", "ww",
+    "WW", "M", "D", "H", "h", "m", "s", "A", "a", "Q", "W", "w", "E",
+];
+
+/// Formatea una fecha con tokens estilo moment.js (`YYYY-MM-DD`, `dddd`,
+/// `[Semana] WW`), los mismos que usan Obsidian y otros editores. El texto
+/// entre corchetes se copia tal cual.
+pub fn format_date(date: &NaiveDateTime, pattern: &str) -> String {
+    let mut out = String::new();
+    let mut rest = pattern;
+
+    while let Some(c) = rest.chars().next() {
+        if c == '[' {
+            if let Some(end) = rest.find(']') {
+                out.push_str(&rest[1..end]);
+                rest = &rest[end + 1..];
+                continue;
+            }
+        }
+
+        let Some(token) = DATE_TOKENS.iter().find(|t| rest.starts_with(**t)) else {
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+            continue;
+        };
+
+        let hour12 = match date.hour() % 12 {
+            0 => 12,
+            h => h,
+        };
+        let value = match *token {
+            "YYYY" => format!("{:04}", date.year()),
+            "YY" => format!("{:02}", date.year() % 100),
+            "GGGG" => format!("{:04}", date.iso_week().year()),
+            "MMMM" => date.format("%B").to_string(),
+            "MMM" => date.format("%b").to_string(),
+            "MM" => format!("{:02}", date.month()),
+            "M" => date.month().to_string(),
+            "DDD" => date.ordinal().to_string(),
+            "DD" => format!("{:02}", date.day()),
+            "D" => date.day().to_string(),
+            "dddd" => date.format("%A").to_string(),
+            "ddd" => date.format("%a").to_string(),
+            "E" => date.weekday().number_from_monday().to_string(),
+            "HH" => format!("{:02}", date.hour()),
+            "H" => date.hour().to_string(),
+            "hh" => format!("{:02}", hour12),
+            "h" => hour12.to_string(),
+            "mm" => format!("{:02}", date.minute()),
+            "m" => date.minute().to_string(),
+            "ss" => format!("{:02}", date.second()),
+            "s" => date.second().to_string(),
+            "A" => if date.hour() < 12 { "AM" } else { "PM" }.to_string(),
+            "a" => if date.hour() < 12 { "am" } else { "pm" }.to_string(),
+            "Q" => (date.month0() / 3 + 1).to_string(),
+            "WW" | "ww" => format!("{:02}", date.iso_week().week()),
+            _ => date.iso_week().week().to_string(), // "W" | "w"
+        };
+        out.push_str(&value);
+        rest = &rest[token.len()..];
+    }
+
+    out
+}
+
+// -- Render ------------------------------------------------------------------
+
+fn variable_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\{\{\s*([^{}]+?)\s*\}\}").unwrap())
+}
+
+fn git_config(vault: &Path, key: &str) -> Option<String> {
+    let output = git_cmd(&vault.to_string_lossy()).args(["config", key]).output().ok()?;
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !value.is_empty()).then_some(value)
+}
+
+/// Sustituye las variables de la plantilla. Devuelve el contenido y la
+/// posicion del cursor (en unidades UTF-16, como `selectionStart`) si la
+/// plantilla tiene `{{cursor}}`. Las variables desconocidas se dejan tal cual.
+pub fn render(template: &str, ctx: &TemplateContext) -> (String, Option<u32>) {
+    let mut out = String::with_capacity(template.len());
+    let mut cursor = None;
+    let mut last = 0;
+
+    for cap in variable_regex().captures_iter(template) {
+        let m = cap.get(0).unwrap();
+        out.push_str(&template[last..m.start()]);
+        last = m.end();
+
+        let (name, arg) = match cap[1].split_once(':') {
+            Some((n, a)) => (n.trim(), Some(a.trim())),
+            None => (cap[1].trim(), None),
+        };
+        let value = match name {
+            "title" => Some(ctx.title.to_string()),
+            "date" => Some(format_date(&ctx.date, arg.unwrap_or("YYYY-MM-DD"))),
+            "time" => Some(format_date(&ctx.date, arg.unwrap_or("HH:mm"))),
+            "vault" => Some(ctx.vault.file_name().unwrap_or_default().to_string_lossy().to_string()),
+            "git.user" => Some(git_config(ctx.vault, "user.name").unwrap_or_default()),
+            "git.email" => Some(git_config(ctx.vault, "user.email").unwrap_or_default()),
+            CURSOR_MARKER => {
+                if cursor.is_none() {
+                    cursor = Some(out.encode_utf16().count() as u32);
+                }
+                Some(String::new())
+            }
+            _ => None,
+        };
+        out.push_str(value.as_deref().unwrap_or(m.as_str()));
+    }
+    out.push_str(&template[last..]);
+
+    (out, cursor)
+}
+
+/// Ruta de una plantilla por nombre. Error si no existe o sale de la carpeta.
+pub fn template_path(vault: &Path, name: &str) -> Result<PathBuf, AppError> {
+    let name = name.trim().trim_end_matches(".md");
+    // Solo partes normales: ni `..` ni rutas absolutas, que reemplazarian la carpeta
+    let inside = Path::new(name).components().all(|c| matches!(c, Component::Normal(_)));
+    if name.is_empty() || !inside {
+        return Err(AppError::invalid("template.invalid", name));
+    }
+    let path = vault_config::templates_dir(vault).join(format!("{}.md", name));
+    if !path.is_file() {
+        return Err(AppError::new(ErrorKind::NotFound, "template.not_found").param("name", name));
+    }
+    Ok(path)
+}
+
+/// Contenido de una nota nueva a partir de la plantilla `name`.
+pub fn render_template(name: &str, ctx: &TemplateContext) -> Result<(String, Option<u32>), AppError> {
+    let path = template_path(ctx.vault, name)?;
+    let template = fs::read_to_string(&path)?;
+    Ok(render(&template, ctx))
+}
+
+// -- Comandos ----------------------------------------------------------------
+
+/// Plantillas del vault (`.potato/templates/**/*.md` o la carpeta de
+/// `.potato/config`), por nombre.
+#[tauri::command]
+pub fn list_templates(vault_path: String) -> Vec<TemplateInfo> {
+    let dir = vault_config::templates_dir(Path::new(&vault_path));
+    let mut templates: Vec<TemplateInfo> = WalkDir::new(&dir)
+        .into_iter()
+        .flatten()
+        .filter(|e| e.file_type().is_file() && e.path().extension().is_some_and(|x| x == "md"))
+        .map(|e| {
+            let name = e
+                .path()
+                .strip_prefix(&dir)
+                .unwrap_or(e.path())
+                .with_extension("")
+                .to_string_lossy()
+                .replace('\\', "/");
+            let content = fs::read_to_string(e.path()).unwrap_or_default();
+            let description = frontmatter::parse(&content)
+                .and_then(|fm| fm.get("description").map(frontmatter::value_text))
+                .or_else(|| frontmatter::first_text_line(&content))
+                .map(|d| d.chars().take(120).collect())
+                .unwrap_or_default();
+            TemplateInfo {
+                name,
+                path: e.path().to_string_lossy().to_string(),
+                description,
+            }
+        })
+        .collect();
+    templates.sort_by(|a, b| a.name.cmp(&b.name));
+    templates
+}
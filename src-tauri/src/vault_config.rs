@@ -0,0 +1,278 @@
+use ignore::gitignore::GitignoreBuilder;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::errors::{AppError, ErrorKind};
+use crate::settings::{self, Settings};
+use crate::{attachments, file_mtime_ms, frontmatter, templates};
+
+/// Configuracion del vault, versionada con el repositorio. YAML (o JSON).
+pub const CONFIG_FILE: &str = ".potato/config";
+
+/// Valores de `.potato/config` ya validados. `None` = no lo define el vault
+/// y se usa el global o el de por defecto.
+#[derive(Default)]
+pub struct VaultConfig {
+    pub templates_folder: Option<String>,
+    pub attachments_folder: Option<String>,
+    pub auto_sync: Option<bool>,
+    pub auto_sync_minutes: Option<u32>,
+    pub use_gitignore: Option<bool>,
+    pub ignore_patterns: Vec<String>,
+    pub agents_dir: Option<PathBuf>, // ya resuelto desde la raiz del vault
+    /// Claves invalidas o desconocidas; se ignoran pero se reportan.
+    pub warnings: Vec<AppError>,
+    exists: bool,
+    stamp: u128,
+}
+
+// -- Lectura y validacion ----------------------------------------------------
+
+fn config_path(vault: &Path) -> PathBuf {
+    vault.join(CONFIG_FILE)
+}
+
+/// Carpeta relativa al vault que no se sale de el. Para adjuntos tambien
+/// valen `.` y `./sub` (relativas a la nota).
+fn vault_folder(value: &Value) -> Option<String> {
+    let folder = value.as_str()?.trim().trim_end_matches('/');
+    let path = Path::new(folder);
+    let escapes = path
+        .components()
+        .any(|c| matches!(c, Component::ParentDir | Component::RootDir | Component::Prefix(_)));
+    (!folder.is_empty() && !escapes).then(|| folder.to_string())
+}
+
+fn read(vault: &Path) -> VaultConfig {
+    let path = config_path(vault);
+    let mut config = VaultConfig {
+        stamp: file_mtime_ms(&path),
+        ..Default::default()
+    };
+    let Ok(content) = fs::read_to_string(&path) else {
+        return config;
+    };
+    config.exists = true;
+
+    // YAML acepta tambien JSON; un archivo vacio es un mapa vacio
+    let root = match frontmatter::parse_yaml(&content) {
+        Ok(None) => return config,
+        Ok(Some(Value::Object(map))) => map,
+        Ok(Some(_)) => {
+            config.warnings.push(AppError::new(ErrorKind::InvalidInput, "vault_config.not_a_map"));
+            return config;
+        }
+        Err(e) => {
+            config.warnings.push(AppError::new(ErrorKind::InvalidInput, "vault_config.parse").detail(e));
+            return config;
+        }
+    };
+
+    let mut values = BTreeMap::new();
+    flatten("", &root, &mut values);
+    for (key, value) in values {
+        let valid = match key.as_str() {
+            "templates_folder" => vault_folder(&value).map(|f| config.templates_folder = Some(f)),
+            "attachments_folder" => vault_folder(&value).map(|f| config.attachments_folder = Some(f)),
+            "sync.auto_sync" => value.as_bool().map(|b| config.auto_sync = Some(b)),
+            "sync.auto_sync_minutes" => value
+                .as_u64()
+                .filter(|m| (1..=u32::MAX as u64).contains(m))
+                .map(|m| config.auto_sync_minutes = Some(m as u32)),
+            "ignore.use_gitignore" => value.as_bool().map(|b| config.use_gitignore = Some(b)),
+            "ignore.patterns" => ignore_patterns(vault, &value).map(|p| config.ignore_patterns = p),
+            "claude.agents_dir" => vault_folder(&value).map(|d| config.agents_dir = Some(vault.join(d))),
+            _ => {
+                config.warnings.push(
+                    AppError::new(ErrorKind::InvalidInput, "vault_config.unknown_key").param("key", &key),
+                );
+                continue;
+            }
+        };
+        if valid.is_none() {
+            config.warnings.push(
+                AppError::new(ErrorKind::InvalidInput, "vault_config.invalid_value")
+                    .param("key", &key)
+                    .detail(value),
+            );
+        }
+    }
+    config
+}
+
+/// `{"sync": {"auto_sync": true}}` -> `"sync.auto_sync" => true`. Las
+/// listas son valores, no secciones.
+fn flatten(prefix: &str, map: &Map<String, Value>, out: &mut BTreeMap<String, Value>) {
+    for (key, value) in map {
+        let full = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+        match value {
+            Value::Object(inner) => flatten(&full, inner, out),
+            _ => {
+                out.insert(full, value.clone());
+            }
+        }
+    }
+}
+
+/// Lista de patrones con sintaxis de `.gitignore`. None si alguno no lo es.
+fn ignore_patterns(vault: &Path, value: &Value) -> Option<Vec<String>> {
+    let mut builder = GitignoreBuilder::new(vault);
+    value
+        .as_array()?
+        .iter()
+        .map(|p| {
+            let pattern = p.as_str()?.to_string();
+            builder.add_line(None, &pattern).ok()?;
+            Some(pattern)
+        })
+        .collect()
+}
+
+// -- Registry ----------------------------------------------------------------
+
+static VAULT_CONFIGS: Mutex<Option<HashMap<PathBuf, Arc<VaultConfig>>>> = Mutex::new(None);
+
+fn vault_configs<F, R>(f: F) -> R
+where
+    F: FnOnce(&mut HashMap<PathBuf, Arc<VaultConfig>>) -> R,
+{
+    let mut guard = VAULT_CONFIGS.lock().unwrap();
+    let map = guard.get_or_insert_with(HashMap::new);
+    f(map)
+}
+
+/// Configuracion del vault, releida si `.potato/config` cambio desde la
+/// ultima vez. Los avisos llegan al frontend con `get_vault_config`.
+pub fn load(vault: &Path) -> Arc<VaultConfig> {
+    let now = file_mtime_ms(&config_path(vault));
+    if let Some(config) = vault_configs(|map| map.get(vault).filter(|c| c.stamp == now).cloned()) {
+        return config;
+    }
+    let config = Arc::new(read(vault));
+    vault_configs(|map| {
+        map.insert(vault.to_path_buf(), config.clone());
+    });
+    config
+}
+
+/// True si `path` es el `.potato/config` del vault `root`.
+pub fn is_config_file(root: &Path, path: &Path) -> bool {
+    path == config_path(root)
+}
+
+// -- Valores efectivos -------------------------------------------------------
+
+pub fn templates_dir(vault: &Path) -> PathBuf {
+    let config = load(vault);
+    vault.join(config.templates_folder.as_deref().unwrap_or(templates::TEMPLATES_DIR))
+}
+
+/// Carpeta de adjuntos: la del vault, la global o None (la de por defecto).
+pub fn attachments_folder(vault: &Path) -> Option<String> {
+    load(vault)
+        .attachments_folder
+        .clone()
+        .or_else(|| settings::current().vault.attachments_folder)
+}
+
+pub fn use_gitignore(vault: &Path) -> Option<bool> {
+    load(vault).use_gitignore
+}
+
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    Default,
+    Global, // settings.json
+    Vault,  // .potato/config
+}
+
+#[derive(Serialize, Clone)]
+pub struct ConfigValue {
+    value: Value,
+    source: Source,
+}
+
+#[derive(Serialize, Clone)]
+pub struct EffectiveConfig {
+    file: Option<String>, // ruta de .potato/config si existe
+    values: BTreeMap<&'static str, ConfigValue>,
+    warnings: Vec<AppError>,
+}
+
+fn value(value: impl Serialize, source: Source) -> ConfigValue {
+    ConfigValue {
+        value: serde_json::to_value(value).unwrap_or(Value::Null),
+        source,
+    }
+}
+
+/// El del vault si lo define, si no el global y si no el de por defecto.
+fn pick<T: Serialize>(vault: Option<T>, global: Option<T>, default: T) -> ConfigValue {
+    match (vault, global) {
+        (Some(v), _) => value(v, Source::Vault),
+        (None, Some(g)) => value(g, Source::Global),
+        (None, None) => value(default, Source::Default),
+    }
+}
+
+fn effective(vault: &Path, config: &VaultConfig, global: &Settings) -> EffectiveConfig {
+    let mut values = BTreeMap::new();
+    values.insert(
+        "templates_folder",
+        pick(config.templates_folder.clone(), None, templates::TEMPLATES_DIR.to_string()),
+    );
+    values.insert(
+        "attachments_folder",
+        pick(
+            config.attachments_folder.clone(),
+            global.vault.attachments_folder.clone(),
+            attachments::DEFAULT_FOLDER.to_string(),
+        ),
+    );
+    values.insert("sync.auto_sync", pick(config.auto_sync, Some(global.sync.auto_sync), false));
+    values.insert(
+        "sync.auto_sync_minutes",
+        pick(config.auto_sync_minutes, Some(global.sync.auto_sync_minutes), 5),
+    );
+    values.insert(
+        "ignore.use_gitignore",
+        pick(config.use_gitignore, Some(global.vault.use_gitignore), false),
+    );
+    values.insert(
+        "ignore.patterns",
+        pick(Some(config.ignore_patterns.clone()).filter(|p| !p.is_empty()), None, vec![]),
+    );
+    // Sin directorio de agentes se usa el propio vault
+    values.insert(
+        "claude.agents_dir",
+        pick(
+            config.agents_dir.as_ref().map(|d| d.to_string_lossy().to_string()),
+            global.claude.workdir.clone(),
+            vault.to_string_lossy().to_string(),
+        ),
+    );
+
+    EffectiveConfig {
+        file: config.exists.then(|| config_path(vault).to_string_lossy().to_string()),
+        values,
+        warnings: config.warnings.clone(),
+    }
+}
+
+// -- Comandos ----------------------------------------------------------------
+
+/// Configuracion efectiva del vault (`.potato/config` sobre las preferencias
+/// globales) y de donde sale cada valor.
+#[tauri::command]
+pub fn get_vault_config(vault_path: String) -> Result<EffectiveConfig, AppError> {
+    let vault = PathBuf::from(&vault_path);
+    if !vault.is_dir() {
+        return Err(AppError::not_a_dir(&vault_path));
+    }
+    Ok(effective(&vault, &load(&vault), &settings::current()))
+}
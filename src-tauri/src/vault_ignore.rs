@@ -0,0 +1,159 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use walkdir::{DirEntry, WalkDir};
+
+use crate::errors::AppError;
+use crate::{file_mtime_ms, index_vault_reset, settings, vault_config};
+
+/// Reglas del vault, con sintaxis de `.gitignore`, en la raiz del vault.
+pub const IGNORE_FILE: &str = ".potatoignore";
+const GITIGNORE_FILE: &str = ".gitignore";
+
+/// Que entradas del vault no se muestran ni se indexan: las ocultas (`.x`),
+/// las que excluyen `.potatoignore` y `ignore.patterns` de `.potato/config`
+/// y, opcionalmente, `.gitignore`.
+pub struct IgnoreRules {
+    root: PathBuf,
+    matcher: Gitignore,
+    use_gitignore: bool,
+    stamp: (u128, u128, u128), // mtimes de los archivos de reglas al cargarlos
+}
+
+impl IgnoreRules {
+    pub fn load(root: &Path, use_gitignore: bool) -> Self {
+        let mut builder = GitignoreBuilder::new(root);
+        // `add` solo falla si el archivo no existe o no se puede leer: sin reglas
+        let _ = builder.add(root.join(IGNORE_FILE));
+        if use_gitignore {
+            let _ = builder.add(root.join(GITIGNORE_FILE));
+        }
+        // Ya validados al leer .potato/config
+        for pattern in &vault_config::load(root).ignore_patterns {
+            let _ = builder.add_line(None, pattern);
+        }
+        IgnoreRules {
+            root: root.to_path_buf(),
+            matcher: builder.build().unwrap_or_else(|_| Gitignore::empty()),
+            use_gitignore,
+            stamp: stamp(root),
+        }
+    }
+
+    pub fn ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let Ok(rel) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+        if rel.as_os_str().is_empty() {
+            return false;
+        }
+        if rel
+            .components()
+            .any(|c| c.as_os_str().to_string_lossy().starts_with('.'))
+        {
+            return true;
+        }
+        self.matcher.matched_path_or_any_parents(path, is_dir).is_ignore()
+    }
+}
+
+fn stamp(root: &Path) -> (u128, u128, u128) {
+    (
+        file_mtime_ms(&root.join(IGNORE_FILE)),
+        file_mtime_ms(&root.join(GITIGNORE_FILE)),
+        file_mtime_ms(&root.join(vault_config::CONFIG_FILE)),
+    )
+}
+
+/// True si `path` es uno de los archivos de reglas del vault `root`.
+pub fn is_rules_file(root: &Path, path: &Path) -> bool {
+    let in_root = path.parent() == Some(root)
+        && path
+            .file_name()
+            .is_some_and(|n| n == IGNORE_FILE || n == GITIGNORE_FILE);
+    in_root || vault_config::is_config_file(root, path)
+}
+
+// -- Registry ----------------------------------------------------------------
+
+static IGNORE_RULES: Mutex<Option<HashMap<PathBuf, Arc<IgnoreRules>>>> = Mutex::new(None);
+
+fn ignore_rules<F, R>(f: F) -> R
+where
+    F: FnOnce(&mut HashMap<PathBuf, Arc<IgnoreRules>>) -> R,
+{
+    let mut guard = IGNORE_RULES.lock().unwrap();
+    let map = guard.get_or_insert_with(HashMap::new);
+    f(map)
+}
+
+/// Reglas del vault que contiene `path`. Si no hay ningun vault cargado que
+/// lo contenga, se leen sin guardarlas tomando `path` como raiz.
+pub fn rules(path: &Path) -> Arc<IgnoreRules> {
+    let cached = ignore_rules(|map| {
+        map.iter()
+            .filter(|(root, _)| path.starts_with(root))
+            .max_by_key(|(root, _)| root.components().count())
+            .map(|(_, rules)| rules.clone())
+    });
+    cached.unwrap_or_else(|| Arc::new(IgnoreRules::load(path, false)))
+}
+
+pub fn is_ignored(path: &Path) -> bool {
+    rules(path).ignored(path, path.is_dir())
+}
+
+/// Carga las reglas del vault y las vuelve a leer si cambiaron los archivos
+/// o la opcion de `.gitignore` (la de `.potato/config` manda sobre
+/// `use_gitignore`, y este sobre la de los ajustes). Devuelve true si
+/// cambiaron respecto a las que se usaron hasta ahora; en ese caso los
+/// indices del vault se reconstruyen.
+pub fn reload(vault: &Path, use_gitignore: Option<bool>) -> bool {
+    let current = ignore_rules(|map| map.get(vault).cloned());
+    let use_gitignore = vault_config::use_gitignore(vault)
+        .or(use_gitignore)
+        .unwrap_or_else(|| settings::current().vault.use_gitignore);
+
+    let now = stamp(vault);
+    // Sin reglas cargadas se usaron las de por defecto, leidas en cada recorrido
+    let (prev_gitignore, prev_stamp) = current
+        .as_ref()
+        .map_or((false, now), |r| (r.use_gitignore, r.stamp));
+    if current.is_some() && prev_gitignore == use_gitignore && prev_stamp == now {
+        return false;
+    }
+
+    let rules = Arc::new(IgnoreRules::load(vault, use_gitignore));
+    ignore_rules(|map| {
+        map.insert(vault.to_path_buf(), rules);
+    });
+    let changed = prev_gitignore != use_gitignore || prev_stamp != now;
+    if changed {
+        index_vault_reset(vault);
+    }
+    changed
+}
+
+/// Recorre `dir` (el vault o una carpeta suya) sin entrar en lo ignorado.
+pub fn walk(dir: &Path) -> impl Iterator<Item = DirEntry> {
+    let rules = rules(dir);
+    WalkDir::new(dir)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(move |e| e.depth() == 0 || !rules.ignored(e.path(), e.file_type().is_dir()))
+        .flatten()
+}
+
+// -- Comandos ----------------------------------------------------------------
+
+/// Activa o desactiva respetar el `.gitignore` del vault ademas de `.potatoignore`.
+#[tauri::command]
+pub fn set_ignore_options(vault_path: String, use_gitignore: bool) -> Result<(), AppError> {
+    let vault = PathBuf::from(&vault_path);
+    if !vault.is_dir() {
+        return Err(AppError::not_a_dir(&vault_path));
+    }
+    reload(&vault, Some(use_gitignore));
+    Ok(())
+}
@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::vault_notes;
+
+/// Indice en memoria de un vault que se mantiene al dia nota a nota. Lo
+/// implementan el de links, el de busqueda y el de tags.
+pub trait VaultIndex: Send {
+    /// Lo que se extrae de una nota al leerla.
+    type Note: Send;
+
+    /// Indice completo del vault. Se llama fuera del lock.
+    fn build(vault: &Path) -> Self;
+    /// Lee una nota; `None` si ya no se puede leer. Se llama fuera del lock.
+    fn read_note(vault: &Path, path: &Path) -> Option<Self::Note>;
+    /// Guarda una nota ya leida, o la quita si `note` es `None`.
+    fn update_note(&mut self, vault: &Path, path: PathBuf, note: Option<Self::Note>);
+    /// Quita la nota `path` o todas las notas bajo esa carpeta.
+    fn remove_notes(&mut self, vault: &Path, path: &Path);
+}
+
+/// Indices cargados, uno por vault.
+pub struct Registry<I> {
+    vaults: Mutex<Option<HashMap<PathBuf, I>>>,
+}
+
+impl<I: VaultIndex> Registry<I> {
+    pub const fn new() -> Self {
+        Registry { vaults: Mutex::new(None) }
+    }
+
+    fn with_map<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut HashMap<PathBuf, I>) -> R,
+    {
+        let mut guard = self.vaults.lock().unwrap();
+        let map = guard.get_or_insert_with(HashMap::new);
+        f(map)
+    }
+
+    pub fn is_loaded(&self, vault: &Path) -> bool {
+        self.with_map(|map| map.contains_key(vault))
+    }
+
+    /// Ejecuta `f` sobre el indice del vault, construyendolo si aun no existe.
+    pub fn with_vault<F, R>(&self, vault: &Path, f: F) -> R
+    where
+        F: FnOnce(&mut I) -> R,
+    {
+        // Construir fuera del lock: en vaults grandes tarda
+        let built = (!self.is_loaded(vault)).then(|| I::build(vault));
+        self.with_map(|map| {
+            let entry = map.entry(vault.to_path_buf());
+            let index = match built {
+                Some(built) => entry.or_insert(built),
+                // Si se descarto entre medias (`forget`), se reconstruye aqui
+                None => entry.or_insert_with(|| I::build(vault)),
+            };
+            f(index)
+        })
+    }
+
+    /// Sustituye el indice del vault por uno ya construido.
+    pub fn replace(&self, vault: PathBuf, index: I) {
+        self.with_map(|map| {
+            map.insert(vault, index);
+        });
+    }
+
+    /// Actualiza los indices tras crear, guardar o mover a `path` (nota o
+    /// carpeta). No hace nada si el vault que la contiene aun no esta
+    /// cargado. Las notas se leen fuera del lock.
+    pub fn path_updated(&self, path: &Path) {
+        let vaults: Vec<PathBuf> = self.with_map(|map| map.keys().filter(|v| path.starts_with(v)).cloned().collect());
+        for vault in vaults {
+            let read: Vec<_> = notes_under(path)
+                .into_iter()
+                .map(|note| {
+                    let read = I::read_note(&vault, &note);
+                    (note, read)
+                })
+                .collect();
+            self.with_map(|map| {
+                let Some(index) = map.get_mut(&vault) else { return };
+                for (note, read) in read {
+                    index.update_note(&vault, note, read);
+                }
+            });
+        }
+    }
+
+    /// Quita de los indices una nota o todas las notas bajo una carpeta
+    /// eliminada.
+    pub fn path_removed(&self, path: &Path) {
+        self.with_map(|map| {
+            for (vault, index) in map.iter_mut() {
+                if path.starts_with(vault) {
+                    index.remove_notes(vault, path);
+                }
+            }
+        });
+    }
+
+    /// Descarta el indice del vault; se reconstruye en el siguiente uso.
+    pub fn forget(&self, vault: &Path) {
+        self.with_map(|map| {
+            map.remove(vault);
+        });
+    }
+}
+
+/// Notas afectadas por un cambio en `path`: las de la carpeta, la propia
+/// nota o ninguna si no es Markdown.
+fn notes_under(path: &Path) -> Vec<PathBuf> {
+    if path.is_dir() {
+        vault_notes(path)
+    } else if path.extension().is_some_and(|e| e == "md") {
+        vec![path.to_path_buf()]
+    } else {
+        vec![]
+    }
+}
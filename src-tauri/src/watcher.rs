@@ -0,0 +1,240 @@
+use notify_debouncer_full::notify::event::{ModifyKind, RenameMode};
+use notify_debouncer_full::notify::{EventKind, RecommendedWatcher, RecursiveMode};
+use notify_debouncer_full::{new_debouncer, DebounceEventResult, Debouncer, RecommendedCache};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::Emitter;
+
+use crate::attachments::attachment_kind;
+use crate::errors::{AppError, ErrorKind};
+use crate::{file_entry, index_path_removed, index_path_updated, settings, vault_ignore, FileEntry};
+
+/// Tiempo que se agrupan eventos del mismo archivo antes de emitirlos.
+const DEBOUNCE_MS: u64 = 300;
+
+#[derive(Serialize, Clone)]
+pub struct VaultChange {
+    kind: String, // "created", "modified", "removed", "renamed"
+    path: String,
+    parent: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    from: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    entry: Option<FileEntry>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct VaultChangeEvent {
+    vault: String,
+    changes: Vec<VaultChange>,
+}
+
+// -- Watcher registry --------------------------------------------------------
+
+type VaultDebouncer = Debouncer<RecommendedWatcher, RecommendedCache>;
+
+static WATCHER_REGISTRY: Mutex<Option<HashMap<String, VaultDebouncer>>> = Mutex::new(None);
+
+fn watcher_registry<F, R>(f: F) -> R
+where
+    F: FnOnce(&mut HashMap<String, VaultDebouncer>) -> R,
+{
+    let mut guard = WATCHER_REGISTRY.lock().unwrap();
+    let map = guard.get_or_insert_with(HashMap::new);
+    f(map)
+}
+
+// -- Comandos ----------------------------------------------------------------
+
+/// Empieza a observar el vault. Los cambios se emiten como `vault-changed`.
+/// Llamarlo de nuevo sobre el mismo vault reinicia el watcher.
+#[tauri::command]
+pub fn watch_vault(app: tauri::AppHandle, path: String) -> Result<(), AppError> {
+    let root = PathBuf::from(&path);
+    if !root.is_dir() {
+        return Err(AppError::not_a_dir(&path));
+    }
+
+    let vault = path.clone();
+    let handler_root = root.clone();
+    let mut debouncer = new_debouncer(
+        Duration::from_millis(DEBOUNCE_MS),
+        None,
+        move |result: DebounceEventResult| {
+            let Ok(events) = result else { return };
+            // Si cambian .potatoignore/.gitignore/.potato/config, el arbol
+            // entero puede cambiar
+            let rules_changed = events
+                .iter()
+                .any(|e| e.paths.iter().any(|p| vault_ignore::is_rules_file(&handler_root, p)))
+                && vault_ignore::reload(&handler_root, None);
+            let changes = collect_changes(&handler_root, events);
+            update_indexes(&changes);
+            if !changes.is_empty() || rules_changed {
+                let _ = app.emit("vault-changed", VaultChangeEvent {
+                    vault: vault.clone(),
+                    changes,
+                });
+            }
+        },
+    )
+    .map_err(|e| AppError::new(ErrorKind::Io, "watcher.failed").detail(e))?;
+
+    debouncer
+        .watch(&root, RecursiveMode::Recursive)
+        .map_err(|e| AppError::new(ErrorKind::Io, "watcher.failed").detail(e))?;
+
+    // Al reemplazarlo, el watcher anterior se detiene en su Drop
+    watcher_registry(|map| {
+        map.insert(path, debouncer);
+    });
+    Ok(())
+}
+
+#[tauri::command]
+pub fn unwatch_vault(path: String) {
+    let debouncer = watcher_registry(|map| map.remove(&path));
+    if let Some(d) = debouncer {
+        d.stop_nonblocking();
+    }
+}
+
+// -- Traduccion de eventos ---------------------------------------------------
+
+fn collect_changes(
+    root: &Path,
+    events: Vec<notify_debouncer_full::DebouncedEvent>,
+) -> Vec<VaultChange> {
+    let mut changes: Vec<VaultChange> = Vec::new();
+    // Mismo criterio que el arbol: los adjuntos solo si se muestran
+    let attachments = settings::current().vault.show_attachments;
+
+    for event in events {
+        let paths = &event.paths;
+        match event.kind {
+            EventKind::Create(_) => {
+                if let Some(p) = paths.first() {
+                    push_change(root, attachments, &mut changes, "created", p, None);
+                }
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if paths.len() == 2 => {
+                let (from, to) = (&paths[0], &paths[1]);
+                match (is_tracked(root, from), is_tracked(root, to)) {
+                    (true, true) => push_change(root, attachments, &mut changes, "renamed", to, Some(from)),
+                    (true, false) => push_change(root, attachments, &mut changes, "removed", from, None),
+                    (false, true) => push_change(root, attachments, &mut changes, "created", to, None),
+                    (false, false) => {}
+                }
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+                if let Some(p) = paths.first() {
+                    push_change(root, attachments, &mut changes, "removed", p, None);
+                }
+            }
+            EventKind::Modify(ModifyKind::Name(_)) => {
+                // Rename sin pareja: decidir segun exista o no en disco
+                if let Some(p) = paths.first() {
+                    let kind = if p.exists() { "created" } else { "removed" };
+                    push_change(root, attachments, &mut changes, kind, p, None);
+                }
+            }
+            EventKind::Modify(ModifyKind::Metadata(_)) => {}
+            EventKind::Modify(_) => {
+                if let Some(p) = paths.first() {
+                    if !p.is_dir() {
+                        push_change(root, attachments, &mut changes, "modified", p, None);
+                    }
+                }
+            }
+            EventKind::Remove(_) => {
+                if let Some(p) = paths.first() {
+                    push_change(root, attachments, &mut changes, "removed", p, None);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    changes
+}
+
+fn push_change(
+    root: &Path,
+    attachments: bool,
+    changes: &mut Vec<VaultChange>,
+    kind: &str,
+    path: &Path,
+    from: Option<&PathBuf>,
+) {
+    if !is_tracked(root, path) {
+        return;
+    }
+
+    let path_str = path.to_string_lossy().to_string();
+
+    // Un archivo creado y luego modificado en la misma ventana: basta con "created"
+    if kind == "modified"
+        && changes
+            .iter()
+            .any(|c| c.path == path_str && (c.kind == "created" || c.kind == "modified"))
+    {
+        return;
+    }
+
+    let entry = if kind == "removed" { None } else { file_entry(path, attachments) };
+    // Si el archivo ya no existe (creado y borrado antes del debounce) no hay nada que reportar
+    if kind != "removed" && entry.is_none() {
+        return;
+    }
+
+    changes.push(VaultChange {
+        kind: kind.to_string(),
+        path: path_str,
+        parent: path
+            .parent()
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        from: from.map(|f| f.to_string_lossy().to_string()),
+        entry,
+    });
+}
+
+/// Mantiene al dia los indices del backend ante cambios hechos fuera de la app.
+fn update_indexes(changes: &[VaultChange]) {
+    for change in changes {
+        if let Some(from) = &change.from {
+            index_path_removed(Path::new(from));
+        }
+        match change.kind.as_str() {
+            "removed" => index_path_removed(Path::new(&change.path)),
+            _ => index_path_updated(Path::new(&change.path)),
+        }
+    }
+}
+
+/// Mismas reglas que `build_tree`: sin entradas ocultas ni ignoradas, solo
+/// carpetas, `.md` y adjuntos. Las rutas eliminadas ya no se pueden consultar,
+/// asi que se aceptan si parecen una nota o adjunto o no tienen extension
+/// (posible carpeta).
+fn is_tracked(root: &Path, path: &Path) -> bool {
+    let Ok(rel) = path.strip_prefix(root) else {
+        return false;
+    };
+    if rel.as_os_str().is_empty() {
+        return false;
+    }
+    if vault_ignore::rules(root).ignored(path, path.is_dir()) {
+        return false;
+    }
+
+    if path.is_dir() {
+        return true;
+    }
+    match path.extension() {
+        Some(ext) => ext == "md" || attachment_kind(path).is_some(),
+        None => !path.exists(),
+    }
+}
@@ -0,0 +1,94 @@
+use regex::{Captures, Regex};
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// `[[target]]`, `[[target|alias]]` y `[[target#seccion|alias]]`.
+pub fn wikilink_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\[\[([^\]|]+)(?:\|([^\]]+))?\]\]").unwrap())
+}
+
+/// Igual que `wikilink_regex` pero capturando el `!` de los embeds `![[nota]]`.
+fn embed_aware_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(!?)\[\[([^\]|]+)(?:\|([^\]]+))?\]\]").unwrap())
+}
+
+/// Separa el target en (nota, seccion). `nota#titulo` -> ("nota", "#titulo").
+pub fn split_target(target: &str) -> (&str, &str) {
+    match target.find('#') {
+        Some(i) => (target[..i].trim(), &target[i..]),
+        None => (target.trim(), ""),
+    }
+}
+
+/// Identidad de una nota frente a los wikilinks: `[[nombre]]` o `[[carpeta/nombre]]`.
+pub struct LinkName {
+    pub stem: String,
+    pub rel: String, // ruta relativa al vault sin `.md`, con `/`
+}
+
+impl LinkName {
+    pub fn new(vault: &Path, note: &Path) -> Self {
+        let stem = note
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        let rel = note
+            .strip_prefix(vault)
+            .unwrap_or(note)
+            .with_extension("")
+            .to_string_lossy()
+            .replace('\\', "/");
+        LinkName { stem, rel }
+    }
+
+    /// Si el nombre de un target (sin seccion) apunta a esta nota.
+    /// Con `by_stem = false` solo se aceptan targets con ruta, util cuando
+    /// hay otra nota con el mismo nombre y `[[nombre]]` seria ambiguo.
+    pub fn matches(&self, name: &str, by_stem: bool) -> bool {
+        let name = name.strip_suffix(".md").unwrap_or(name);
+        if name.contains('/') {
+            name.trim_start_matches('/') == self.rel
+        } else {
+            by_stem && name == self.stem
+        }
+    }
+}
+
+/// Reescribe los wikilinks que apuntan a `old`. Con `new = Some(..)` se
+/// redirigen conservando seccion y alias (y la forma con ruta si la tenian);
+/// con `None` se convierten en texto plano (los embeds desaparecen).
+/// Devuelve None si no hubo cambios.
+pub fn rewrite_links(content: &str, old: &LinkName, new: Option<&LinkName>, by_stem: bool) -> Option<String> {
+    let mut changed = false;
+    let result = embed_aware_regex().replace_all(content, |cap: &Captures| {
+        let (name, section) = split_target(&cap[2]);
+        if !old.matches(name, by_stem) {
+            return cap[0].to_string();
+        }
+        changed = true;
+
+        match new {
+            Some(new) => {
+                let new_name = if name.contains('/') { &new.rel } else { &new.stem };
+                match cap.get(3) {
+                    Some(alias) => format!("{}[[{}{}|{}]]", &cap[1], new_name, section, alias.as_str()),
+                    None => format!("{}[[{}{}]]", &cap[1], new_name, section),
+                }
+            }
+            None if !cap[1].is_empty() => String::new(),
+            None => cap
+                .get(3)
+                .map(|a| a.as_str().to_string())
+                .unwrap_or_else(|| cap[2].to_string()),
+        }
+    });
+
+    if changed {
+        Some(result.into_owned())
+    } else {
+        None
+    }
+}
@@ -22,10 +22,14 @@ const App = {
     syncIndicators: true, // show unsynced file indicators
     autosave: true,       // auto-save after typing stops
     toc: false,           // show table of contents panel
+    attachments: false,   // show images/PDFs/other attachments in the sidebar
+    useGitignore: false,  // also hide/skip what the vault's .gitignore excludes
+    autoSync: false,      // commit/pull/push in background (Rust scheduler)
   },
 
   _autosaveTimeout: null,
   _lastSavedContent: null,  // track content to detect external changes
+  _lastRevision: null,      // revision en disco de la nota abierta (read_note/save_note)
   _noteHistory: [],     // stack de notas visitadas
   _noteHistoryIndex: -1, // posición actual en el stack
   _navInProgress: false, // flag para no pushear al navegar con back/forward
@@ -33,10 +37,16 @@ const App = {
   // Referencia a invoke de Tauri
   invoke: null,
 
+  // Preferencias globales (settings.json en el backend)
+  settings: null,
+
+  // Configuracion efectiva del vault abierto (.potato/config sobre settings)
+  vaultConfig: null,
+
   async init() {
     // Tauri IPC
     if (window.__TAURI__) {
-      this.invoke = window.__TAURI__.core.invoke;
+      this.invoke = CommandError.wrap(window.__TAURI__.core.invoke);
     } else {
       console.warn('Tauri API no disponible, modo standalone');
       this.invoke = async () => null;
@@ -49,6 +59,11 @@ const App = {
       (filePath, fileName, destDir) => this._onFileDrop(filePath, fileName, destDir),
     );
 
+    Editor.onPasteFile(
+      (file) => this._pasteAttachment(file),
+      () => !!(this.state.vaultPath && this.state.currentNote)
+    );
+
     Editor.init('editor', (content) => {
       this.state.dirty = true;
       Preview.update(content);
@@ -100,13 +115,22 @@ const App = {
     // Detect external changes on window focus
     window.addEventListener('focus', () => this._onWindowFocus());
 
+    // Cambios en disco reportados por el watcher del backend
+    if (window.__TAURI__ && window.__TAURI__.event) {
+      window.__TAURI__.event.listen('vault-changed', (event) => this._onVaultChanged(event.payload));
+      window.__TAURI__.event.listen('auto-sync', (event) => this._onAutoSync(event.payload));
+    }
+
+    // Preferencias: antes que cualquier componente que las lea
+    await this._initSettings();
+
     // Claude panel
     Claude.init('claude-messages');
 
     // Search palette
     this._initSearch();
 
-    // Load settings from localStorage
+    // Aplicar preferencias a los controles de Ajustes
     this._loadSettings();
 
     // Load font size preference
@@ -175,7 +199,18 @@ const App = {
   },
 
   async _loadVault(path) {
+    if (this.state.vaultPath && this.state.vaultPath !== path) {
+      this.invoke('unwatch_vault', { path: this.state.vaultPath });
+      this.invoke('stop_auto_sync', { vaultPath: this.state.vaultPath });
+    }
     this.state.vaultPath = path;
+    await this._loadVaultConfig();
+
+    try {
+      await this.invoke('watch_vault', { path });
+    } catch (err) {
+      console.warn('No se pudo observar el vault:', err);
+    }
 
     // UI updates
     const name = path.split('/').pop();
@@ -191,9 +226,13 @@ const App = {
     // Check git status and changed files BEFORE rendering
     await this._checkGitStatus();
     await this._updateChangedFiles();
+    this._updateAutoSync();
+
+    // Reglas de ignorados antes del primer listado/indexado
+    await this.invoke('set_ignore_options', { vaultPath: path, useGitignore: this.state.useGitignore }).catch(() => {});
 
     // Render sidebar with unsynced indicators already set
-    const entries = await this.invoke('list_vault', { path });
+    const entries = await this.invoke('list_vault', { path, attachments: this.state.attachments });
     Sidebar.render(entries);
 
     // Cargar archivos recientes
@@ -346,7 +385,10 @@ const App = {
   async refreshVault() {
     if (!this.state.vaultPath) return;
     await this._updateChangedFiles();
-    const entries = await this.invoke('list_vault', { path: this.state.vaultPath });
+    const entries = await this.invoke('list_vault', {
+      path: this.state.vaultPath,
+      attachments: this.state.attachments,
+    });
     Sidebar.render(entries);
     this._loadRecentFiles(this.state.vaultPath);
   },
@@ -357,8 +399,9 @@ const App = {
     // Auto-guardar nota actual
     await this.saveCurrentNote();
 
-    const content = await this.invoke('read_note', { path });
-    if (content === null) return;
+    const note = await this.invoke('read_note', { path });
+    if (note === null) return;
+    const content = note.content;
 
     // Push al historial de navegación (solo si no es nav back/forward)
     if (!this._navInProgress) {
@@ -372,6 +415,7 @@ const App = {
     this.state.currentNote = { path, title };
     this.state.dirty = false;
     this._lastSavedContent = content;
+    this._lastRevision = note.revision;
 
     Editor.setValue(content);
     this._setTitle(title);
@@ -403,6 +447,7 @@ const App = {
     this.state.currentNote = null;
     this.state.dirty = false;
     this._lastSavedContent = null;
+    this._lastRevision = null;
 
     // Ocultar controles de nota
     document.getElementById('mode-toggle').classList.add('hidden');
@@ -463,11 +508,19 @@ const App = {
     if (!this.state.currentNote || !this.state.dirty) return;
 
     const content = Editor.getValue();
-    await this.invoke('save_note', {
+    const result = await this.invoke('save_note', {
       path: this.state.currentNote.path,
       content,
+      expectedRevision: this._lastRevision,
     });
+
+    if (result && !result.saved) {
+      await this._resolveSaveConflict(result);
+      return;
+    }
+
     this._lastSavedContent = content;
+    this._lastRevision = result ? result.revision : null;
     this.state.dirty = false;
     this._setStatus('Guardado');
     setTimeout(() => {
@@ -489,10 +542,25 @@ const App = {
     const cancelBtn = document.getElementById('newnote-btn-cancel');
     const closeBtn = document.getElementById('newnote-modal-close');
 
+    const templateStep = document.getElementById('newnote-template-step');
+    const templateSelect = document.getElementById('newnote-template');
+
     modal.classList.add('open');
     input.value = '';
     input.focus();
 
+    templateStep.style.display = 'none';
+    this.invoke('list_templates', { vaultPath: this.state.vaultPath }).then((templates) => {
+      templateSelect.innerHTML = '<option value="">Sin plantilla</option>';
+      for (const t of templates || []) {
+        const opt = document.createElement('option');
+        opt.value = t.name;
+        opt.textContent = t.description ? `${t.name} — ${t.description}` : t.name;
+        templateSelect.appendChild(opt);
+      }
+      templateStep.style.display = templates && templates.length ? '' : 'none';
+    }).catch(() => {});
+
     const close = () => modal.classList.remove('open');
 
     cancelBtn.onclick = close;
@@ -515,16 +583,77 @@ const App = {
 
       close();
 
-      const path = await this.invoke('create_note', {
-        vaultPath: this.state.vaultPath,
-        name,
-      });
+      let created;
+      try {
+        created = await this.invoke('create_note', {
+          vaultPath: this.state.vaultPath,
+          name,
+          template: templateSelect.value || null,
+        });
+      } catch (err) {
+        this._setStatus(`Error: ${err}`);
+        return;
+      }
 
       await this.refreshVault();
-      await this.openNote(path, name);
+      await this.openNote(created.path, name);
+      if (created.cursor != null) Editor.setCursor(created.cursor);
     };
   },
 
+  // -- Attachments ---------------------------------------------------------
+
+  // Guarda un archivo pegado en el editor y escribe su embed en el cursor
+  async _pasteAttachment(file) {
+    if (!this.state.vaultPath || !this.state.currentNote) return;
+
+    const data = Array.from(new Uint8Array(await file.arrayBuffer()));
+    const ext = (file.type.split('/')[1] || 'png').replace('jpeg', 'jpg').replace('svg+xml', 'svg');
+    const stamp = new Date().toISOString().replace(/[-:T]/g, '').slice(0, 14);
+    const fileName = file.name && file.name !== 'image.png' ? file.name : `Pasted image ${stamp}.${ext}`;
+
+    try {
+      const saved = await this.invoke('save_attachment', {
+        vaultPath: this.state.vaultPath,
+        notePath: this.state.currentNote.path,
+        fileName,
+        data,
+      });
+      Editor.insertAtCursor(saved.embed);
+      if (this.state.attachments) this.refreshVault();
+    } catch (err) {
+      this._setStatus(`Error: ${err}`);
+    }
+  },
+
+  // -- Periodic notes ------------------------------------------------------
+
+  _periodicSettings(kind) {
+    return this.setting(`periodic.${kind}`);
+  },
+
+  // Abre (o crea) la nota diaria, semanal o mensual de `date` (hoy por defecto)
+  async openPeriodicNote(kind, date = null) {
+    if (!this.state.vaultPath) return;
+
+    let note;
+    try {
+      note = await this.invoke('open_periodic_note', {
+        vaultPath: this.state.vaultPath,
+        kind,
+        date,
+        settings: this._periodicSettings(kind),
+      });
+    } catch (err) {
+      this._setStatus(`Error: ${err}`);
+      return;
+    }
+
+    if (note.created) await this.refreshVault();
+    await this.openNote(note.path, note.name);
+    if (note.cursor != null) Editor.setCursor(note.cursor);
+  },
+
   // -- Wikilinks -----------------------------------------------------------
 
   async onWikilinkClick(target) {
@@ -538,7 +667,7 @@ const App = {
       await this.openNote(found.path, found.name);
     } else {
       // Crear nota si no existe
-      const path = await this.invoke('create_note', {
+      const { path } = await this.invoke('create_note', {
         vaultPath: this.state.vaultPath,
         name: target,
       });
@@ -672,6 +801,10 @@ const App = {
           e.preventDefault();
           this.createNote();
           break;
+        case 'd':
+          e.preventDefault();
+          this.openPeriodicNote('daily');
+          break;
         case 's':
           e.preventDefault();
           this.saveCurrentNote();
@@ -753,6 +886,11 @@ const App = {
       this.gitSync();
     });
 
+    document.getElementById('menu-branches').addEventListener('click', () => {
+      dropdown.classList.remove('open');
+      this._showBranches();
+    });
+
     document.getElementById('menu-open-folder').addEventListener('click', () => {
       dropdown.classList.remove('open');
       this._openInExplorer();
@@ -834,8 +972,19 @@ const App = {
       if (status.is_repo && status.remote) {
         this.state.gitLinked = true;
         syncBtn.classList.remove('hidden');
-        syncBtn.classList.toggle('has-changes', status.has_changes);
-        syncLabel.textContent = status.has_changes ? 'Sync*' : 'Sync';
+        syncBtn.classList.toggle('has-changes', status.has_changes || status.ahead > 0 || status.behind > 0);
+
+        let label = status.has_changes ? 'Sync*' : 'Sync';
+        if (status.ahead > 0) label += ' \u2191' + status.ahead;
+        if (status.behind > 0) label += ' \u2193' + status.behind;
+        syncLabel.textContent = label;
+
+        const parts = [];
+        if (status.ahead > 0) parts.push(status.ahead + ' para subir');
+        if (status.behind > 0) parts.push(status.behind + ' para bajar');
+        if (status.operation !== 'none') parts.push(status.operation + ' en curso');
+        const where = status.detached ? 'HEAD desacoplado' : 'Rama ' + status.branch;
+        syncBtn.title = where + (parts.length ? ': ' + parts.join(', ') : '');
       } else {
         this.state.gitLinked = false;
         syncBtn.classList.add('hidden');
@@ -845,11 +994,197 @@ const App = {
     }
   },
 
+  // -- Auto-sync -------------------------------------------------------------
+
+  _autoSyncMinutes() {
+    return this.setting('sync.auto_sync_minutes') || 5;
+  },
+
+  _updateAutoSync() {
+    if (!this.state.vaultPath) return;
+    const autoSync = this.vaultSetting('sync.auto_sync', this.state.autoSync);
+    if (autoSync && this.state.gitLinked) {
+      this.invoke('start_auto_sync', {
+        vaultPath: this.state.vaultPath,
+        intervalSecs: this.vaultSetting('sync.auto_sync_minutes', this._autoSyncMinutes()) * 60,
+      }).catch((err) => console.warn('No se pudo iniciar el auto-sync:', err));
+    } else {
+      this.invoke('stop_auto_sync', { vaultPath: this.state.vaultPath });
+    }
+  },
+
+  _onAutoSync(payload) {
+    if (!payload || payload.vault !== this.state.vaultPath) return;
+
+    switch (payload.state) {
+      case 'synced':
+        if (payload.message) this._setStatus('Auto-sync: ' + payload.message);
+        this._checkGitStatus();
+        this._updateChangedFiles();
+        break;
+      case 'paused':
+        this._setStatus('Auto-sync en pausa: hay conflictos por resolver (Sync)');
+        break;
+      case 'auth_error':
+        this._setStatus('Auto-sync: error de autenticacion, reintento en ' + Math.round(payload.next_run_secs / 60) + ' min');
+        break;
+      case 'error':
+        this._setStatus('Auto-sync: ' + (payload.message || 'error'));
+        break;
+    }
+  },
+
+  // -- Branches --------------------------------------------------------------
+
+  async _showBranches() {
+    if (!this.state.vaultPath || !this.state.gitLinked) return;
+
+    const modal = document.getElementById('branch-modal');
+    const input = document.getElementById('branch-new-input');
+    const createBtn = document.getElementById('branch-btn-create');
+    const close = () => modal.classList.remove('open');
+
+    document.getElementById('branch-btn-cancel').onclick = close;
+    document.getElementById('branch-modal-close').onclick = close;
+
+    input.value = '';
+    input.onkeydown = (e) => {
+      if (e.key === 'Enter') {
+        e.preventDefault();
+        createBtn.click();
+      }
+      if (e.key === 'Escape') {
+        e.preventDefault();
+        close();
+      }
+    };
+    createBtn.onclick = () => {
+      const name = input.value.trim();
+      if (!name) return;
+      this._branchAction('git_create_branch', { name, checkout: true }, 'crear la rama');
+    };
+
+    modal.classList.add('open');
+    await this._renderBranches();
+    input.focus();
+  },
+
+  async _renderBranches() {
+    const list = document.getElementById('branch-list');
+    let branches;
+    try {
+      branches = await this.invoke('git_branches', { vaultPath: this.state.vaultPath });
+    } catch (err) {
+      list.textContent = 'Error: ' + err;
+      return;
+    }
+
+    list.innerHTML = '';
+    const locals = new Set(branches.filter(b => !b.remote).map(b => b.name));
+    for (const branch of branches) {
+      // A remote branch already checked out locally is shown only once
+      if (branch.remote && locals.has(branch.name.slice(branch.remote.length + 1))) continue;
+
+      const item = document.createElement('div');
+      item.className = 'sync-file-item';
+
+      const name = document.createElement('span');
+      name.className = 'sync-file-name';
+      name.textContent = branch.name;
+      name.title = branch.subject;
+      item.appendChild(name);
+
+      const counts = [];
+      if (branch.ahead > 0) counts.push('\u2191' + branch.ahead);
+      if (branch.behind > 0) counts.push('\u2193' + branch.behind);
+      if (branch.current || branch.remote || counts.length) {
+        const badge = document.createElement('span');
+        badge.className = 'sync-file-status ' + (branch.current ? 'new' : branch.remote ? 'renamed' : 'modified');
+        badge.textContent = [branch.current ? 'actual' : branch.remote ? 'remota' : '', ...counts].join(' ').trim();
+        item.appendChild(badge);
+      }
+
+      if (!branch.current) {
+        const actions = [
+          ['Cambiar', () => this._branchAction('git_switch_branch', { name: branch.name }, 'cambiar de rama')],
+          ['Fusionar', () => {
+            if (confirm('Fusionar ' + branch.name + ' en la rama actual?')) {
+              this._branchAction('git_merge_branch', { name: branch.name }, 'fusionar');
+            }
+          }],
+        ];
+        if (!branch.remote) {
+          actions.push(['Eliminar', () => this._deleteBranch(branch)]);
+        }
+        for (const [label, onclick] of actions) {
+          const btn = document.createElement('button');
+          btn.className = 'sync-select-btn';
+          btn.textContent = label;
+          btn.onclick = onclick;
+          item.appendChild(btn);
+        }
+      }
+      list.appendChild(item);
+    }
+  },
+
+  async _deleteBranch(branch) {
+    if (!confirm('Eliminar la rama ' + branch.name + '?')) return;
+    const remote = !!branch.upstream && confirm('Eliminarla tambien del repositorio remoto?');
+    try {
+      await this.invoke('git_delete_branch', { vaultPath: this.state.vaultPath, name: branch.name, remote });
+    } catch (err) {
+      if (err.kind !== 'not_merged') {
+        this._showGitError(err, 'eliminar la rama');
+      } else if (confirm('La rama tiene cambios que no estan en la rama actual. Eliminarla igual?')) {
+        try {
+          await this.invoke('git_delete_branch', { vaultPath: this.state.vaultPath, name: branch.name, force: true, remote });
+        } catch (err2) {
+          this._showGitError(err2, 'eliminar la rama');
+        }
+      }
+    }
+    await this._renderBranches();
+  },
+
+  // Switch, merge or create+switch; the vault files change afterwards
+  async _branchAction(command, args, action) {
+    await this.saveCurrentNote();
+    try {
+      const result = await this.invoke(command, { vaultPath: this.state.vaultPath, ...args });
+      document.getElementById('branch-modal').classList.remove('open');
+      if (command === 'git_merge_branch') {
+        this._setStatus(result === 'up_to_date' ? 'La rama ya estaba al dia' : 'Rama ' + args.name + ' fusionada');
+      } else {
+        this._setStatus('Rama ' + (result || args.name));
+      }
+    } catch (err) {
+      if (err.kind === 'dirty') {
+        alert('Hay cambios sin subir. Sincroniza antes de ' + action + '.');
+        return;
+      }
+      if (err.kind === 'conflict') {
+        // Merge stopped (or a rebase/merge was already pending): resolve
+        // it with the sync flow
+        document.getElementById('branch-modal').classList.remove('open');
+        await this.refreshVault();
+        this.gitSync();
+        return;
+      }
+      this._showGitError(err, action);
+      return;
+    }
+    await this.refreshVault();
+    await this._reloadCurrentNoteFromDisk();
+    this._checkGitStatus();
+  },
+
   // Sync state for the modal flow
   _syncState: {
     step: 'idle',       // idle, pulling, files, commit, pushing, done, error
     changedFiles: [],
     selectedFiles: [],
+    partialFiles: new Set(),
   },
 
   async gitSync() {
@@ -859,7 +1194,7 @@ const App = {
     await this.saveCurrentNote();
 
     // Reset sync state
-    this._syncState = { step: 'pulling', changedFiles: [], selectedFiles: [] };
+    this._syncState = { step: 'pulling', changedFiles: [], selectedFiles: [], partialFiles: new Set() };
 
     // Open modal and start
     this._openSyncModal();
@@ -893,7 +1228,7 @@ const App = {
   },
 
   _syncShowStep(step) {
-    ['pull', 'files', 'commit', 'result'].forEach(s => {
+    ['pull', 'conflicts', 'files', 'commit', 'result'].forEach(s => {
       document.getElementById('sync-step-' + s).classList.toggle('hidden', s !== step);
     });
   },
@@ -917,7 +1252,7 @@ const App = {
       icon.className = 'sync-step-icon success';
       icon.innerHTML = '&#10003;';
 
-      if (result === 'already_up_to_date') {
+      if (result === 'up_to_date') {
         text.textContent = 'Todo al dia. No hay cambios nuevos en el repositorio.';
       } else if (result === 'no_remote_branch') {
         text.textContent = 'Sin rama remota (se creara al hacer push).';
@@ -925,31 +1260,19 @@ const App = {
         text.textContent = 'Cambios descargados exitosamente.';
       }
 
-      // Refresh vault (pull may have brought new files)
-      await this.refreshVault();
+      await this._syncAfterPull(text);
 
-      // Now check for local changes
-      const files = await this.invoke('git_changed_files', { path: this.state.vaultPath });
-      this._syncState.changedFiles = files;
-
-      if (files.length === 0) {
-        // No local changes, we're done
-        actionBtn.textContent = 'Cerrar';
-        actionBtn.disabled = false;
-        actionBtn.onclick = () => this._closeSyncModal();
-        text.textContent += '\nNo tienes cambios locales. Todo sincronizado.';
-      } else {
-        // Has changes, go to file selection
-        actionBtn.textContent = 'Ver cambios (' + files.length + ')';
-        actionBtn.disabled = false;
-        actionBtn.onclick = () => this._syncStepFiles();
+    } catch (err) {
+      if (err.kind === 'conflict') {
+        const state = await this.invoke('git_conflicts', { vaultPath: this.state.vaultPath });
+        this._syncStepConflicts(state);
+        return;
       }
 
-    } catch (err) {
       icon.className = 'sync-step-icon error';
       icon.innerHTML = '&#10007;';
 
-      if (err === 'auth_error') {
+      if (err.kind === 'auth') {
         text.innerHTML = 'Error de autenticacion.<br><br>Usa SSH: <code>git@github.com:user/repo.git</code>';
       } else {
         text.textContent = 'Error: ' + err;
@@ -961,6 +1284,119 @@ const App = {
     }
   },
 
+  async _syncAfterPull(text) {
+    const actionBtn = document.getElementById('sync-btn-action');
+
+    // Refresh vault (pull may have brought new files)
+    await this.refreshVault();
+
+    // Now check for local changes
+    const files = await this.invoke('git_changed_files', { path: this.state.vaultPath });
+    this._syncState.changedFiles = files;
+
+    if (files.length === 0) {
+      // No local changes, we're done
+      actionBtn.textContent = 'Cerrar';
+      actionBtn.disabled = false;
+      actionBtn.onclick = () => this._closeSyncModal();
+      text.textContent += '\nNo tienes cambios locales. Todo sincronizado.';
+    } else {
+      // Has changes, go to file selection
+      actionBtn.textContent = 'Ver cambios (' + files.length + ')';
+      actionBtn.disabled = false;
+      actionBtn.onclick = () => this._syncStepFiles();
+    }
+  },
+
+  // Step 1b: Conflictos del pull (rebase detenido)
+  _syncStepConflicts(state) {
+    const vaultPath = this.state.vaultPath;
+    const list = document.getElementById('sync-conflict-list');
+    const actionBtn = document.getElementById('sync-btn-action');
+    const cancelBtn = document.getElementById('sync-btn-cancel');
+
+    if (state.operation === 'none') {
+      cancelBtn.textContent = 'Cancelar';
+      cancelBtn.onclick = () => this._closeSyncModal();
+      this._syncShowStep('pull');
+      const icon = document.querySelector('#sync-step-pull .sync-step-icon');
+      const text = document.querySelector('#sync-step-pull .sync-step-text');
+      icon.className = 'sync-step-icon success';
+      icon.innerHTML = '&#10003;';
+      text.textContent = 'Conflictos resueltos. Cambios integrados.';
+      this._syncAfterPull(text);
+      return;
+    }
+
+    this._syncShowStep('conflicts');
+    list.innerHTML = '';
+
+    // En un rebase "ours" es lo que vino del remoto y "theirs" tu commit local
+    const rebase = state.operation === 'rebase';
+    const local = (f) => (rebase ? f.theirs : f.ours);
+    const remote = (f) => (rebase ? f.ours : f.theirs);
+
+    const resolve = async (path, content) => {
+      try {
+        const next = await this.invoke('git_resolve_conflict', { vaultPath, path, content });
+        this._syncStepConflicts(next);
+      } catch (err) {
+        this._setStatus('Error: ' + err);
+      }
+    };
+
+    for (const file of state.files) {
+      const item = document.createElement('div');
+      item.className = 'sync-file-item';
+
+      const name = document.createElement('span');
+      name.className = 'sync-file-name';
+      name.textContent = file.path;
+      name.title = file.path;
+      item.appendChild(name);
+
+      const choices = [['Local', local(file)], ['Remoto', remote(file)]];
+      if (file.merged != null) choices.unshift(['Combinar', file.merged]);
+      for (const [label, content] of choices) {
+        const btn = document.createElement('button');
+        btn.className = 'sync-select-btn';
+        btn.textContent = content == null ? label + ' (borrado)' : label;
+        btn.onclick = () => resolve(file.path, content);
+        item.appendChild(btn);
+      }
+      list.appendChild(item);
+    }
+
+    cancelBtn.textContent = 'Abortar';
+    cancelBtn.onclick = async () => {
+      await this.invoke('git_conflict_abort', { vaultPath }).catch(() => {});
+      cancelBtn.textContent = 'Cancelar';
+      this._closeSyncModal();
+    };
+
+    actionBtn.disabled = false;
+    if (state.files.length === 0) {
+      actionBtn.textContent = 'Continuar';
+      actionBtn.onclick = async () => {
+        actionBtn.disabled = true;
+        try {
+          this._syncStepConflicts(await this.invoke('git_conflict_continue', { vaultPath }));
+        } catch (err) {
+          this._setStatus('Error: ' + err);
+          actionBtn.disabled = false;
+        }
+      };
+    } else if (state.files.some((f) => f.merged != null)) {
+      actionBtn.textContent = 'Combinar automaticamente';
+      actionBtn.onclick = async () => {
+        this._syncStepConflicts(await this.invoke('git_auto_merge_conflicts', { vaultPath }));
+      };
+    } else {
+      actionBtn.textContent = 'Elige una version por archivo';
+      actionBtn.disabled = true;
+    }
+  },
+
   // Step 2: File selection
   _syncStepFiles() {
     this._syncShowStep('files');
@@ -997,6 +1433,26 @@ const App = {
       item.appendChild(name);
       item.appendChild(badge);
       list.appendChild(item);
+
+      // Modified notes can be staged or discarded hunk by hunk
+      if (statusClass === 'modified') {
+        const panel = document.createElement('div');
+        panel.className = 'sync-hunk-list';
+        panel.style.display = 'none';
+
+        const toggle = document.createElement('button');
+        toggle.className = 'sync-select-btn';
+        toggle.textContent = 'Partes';
+        toggle.title = 'Elegir que partes del archivo subir';
+        toggle.onclick = (e) => {
+          e.preventDefault();
+          const open = panel.style.display === 'none';
+          panel.style.display = open ? '' : 'none';
+          if (open) this._syncLoadHunks(file.path, panel, cb);
+        };
+        item.insertBefore(toggle, badge);
+        list.appendChild(panel);
+      }
     }
 
     // Update selection count
@@ -1005,6 +1461,82 @@ const App = {
     actionBtn.onclick = () => this._syncStepCommit();
   },
 
+  // Hunks of a modified note with buttons to stage or discard each one
+  async _syncLoadHunks(path, panel, cb) {
+    panel.innerHTML = '';
+    let file;
+    try {
+      const files = await this.invoke('git_diff_hunks', { vaultPath: this.state.vaultPath, path });
+      file = files.find(f => f.path === path);
+    } catch (err) {
+      panel.textContent = 'Error: ' + err;
+      return;
+    }
+    if (!file || file.hunks.length === 0) {
+      panel.textContent = this._syncState.partialFiles.has(path)
+        ? 'Todos los cambios ya estan en el stage.'
+        : 'Sin cambios.';
+      return;
+    }
+
+    for (const hunk of file.hunks) {
+      const block = document.createElement('div');
+      block.className = 'sync-hunk';
+
+      const header = document.createElement('div');
+      header.className = 'sync-hunk-header';
+      const title = document.createElement('span');
+      title.textContent = hunk.header;
+      header.appendChild(title);
+
+      const apply = async (action) => {
+        if (action === 'discard' && !confirm('Descartar este cambio del archivo? No se puede deshacer.')) return;
+        try {
+          await this.invoke('git_apply_hunks', {
+            vaultPath: this.state.vaultPath,
+            path,
+            diffHash: file.diff_hash,
+            hunks: [hunk.id],
+            action,
+          });
+        } catch (err) {
+          alert('Error:\n\n' + err);
+          this._syncLoadHunks(path, panel, cb);
+          return;
+        }
+        if (action === 'stage') {
+          // The rest of the file stays out: staging the whole file would
+          // add the unfinished hunks too
+          this._syncState.partialFiles.add(path);
+          cb.checked = false;
+          this._syncUpdateSelection();
+        }
+        this._syncLoadHunks(path, panel, cb);
+      };
+
+      for (const [action, label] of [['stage', 'Agregar'], ['discard', 'Descartar']]) {
+        const btn = document.createElement('button');
+        btn.className = 'sync-select-btn';
+        btn.textContent = label;
+        btn.onclick = () => apply(action);
+        header.appendChild(btn);
+      }
+      block.appendChild(header);
+
+      const pre = document.createElement('pre');
+      pre.className = 'sync-hunk-lines';
+      for (const line of hunk.lines) {
+        const row = document.createElement('div');
+        row.className = 'sync-hunk-line ' + line.kind;
+        const sign = line.kind === 'added' ? '+' : line.kind === 'removed' ? '-' : ' ';
+        row.textContent = sign + line.text;
+        pre.appendChild(row);
+      }
+      block.appendChild(pre);
+      panel.appendChild(block);
+    }
+  },
+
   _syncSelectAll(checked) {
     document.querySelectorAll('#sync-file-list input[type="checkbox"]').forEach(cb => {
       cb.checked = checked;
@@ -1019,7 +1551,12 @@ const App = {
 
     this._syncState.selectedFiles = selected.map(cb => cb.dataset.path);
 
-    if (selected.length === 0) {
+    const partial = this._syncState.partialFiles.size;
+    if (selected.length === 0 && partial > 0) {
+      actionBtn.textContent = 'Continuar (cambios parciales)';
+      actionBtn.disabled = false;
+      actionBtn.onclick = () => this._syncStepCommit();
+    } else if (selected.length === 0) {
       actionBtn.textContent = 'Omitir y cerrar';
       actionBtn.disabled = false;
       actionBtn.onclick = () => this._closeSyncModal();
@@ -1030,85 +1567,22 @@ const App = {
     }
   },
 
-  // Extract a short topic description from file content
-  _extractTopic(content) {
-    // Try frontmatter fields: description, topic, titulo, title
-    const fmMatch = content.match(/^---\s*\n([\s\S]*?)\n---/);
-    if (fmMatch) {
-      const fm = fmMatch[1];
-      for (const field of ['description', 'descripcion', 'topic', 'tema', 'title', 'titulo']) {
-        const re = new RegExp('^' + field + '\\s*:\\s*(.+)', 'mi');
-        const m = fm.match(re);
-        if (m) {
-          return m[1].trim().replace(/^["']|["']$/g, '');
-        }
-      }
-    }
-
-    // Try first heading
-    const headingMatch = content.match(/^#{1,3}\s+(.+)/m);
-    if (headingMatch) {
-      return headingMatch[1].trim();
-    }
-
-    // First non-empty line (skip frontmatter)
-    const body = fmMatch ? content.slice(fmMatch[0].length) : content;
-    const firstLine = body.split('\n').find(l => l.trim().length > 0);
-    if (firstLine) {
-      return firstLine.trim().slice(0, 60);
-    }
-
-    return null;
-  },
-
-  // Generate commit message from selected files content
+  // Commit message from the selected files (generated in the backend with
+  // the vault's .potato/commit-template)
   async _generateCommitMessage() {
-    const selected = new Set(this._syncState.selectedFiles);
-    const files = this._syncState.changedFiles.filter(f => selected.has(f.path));
-
-    const groups = { add: [], update: [], delete: [] };
-
-    for (const f of files) {
-      let topic = null;
-
-      // Read content for non-deleted files
-      if (f.status_code !== 'D') {
-        try {
-          const absPath = this.state.vaultPath + '/' + f.path;
-          const content = await this.invoke('read_note', { path: absPath });
-          if (content) topic = this._extractTopic(content);
-        } catch (_) { /* ignore */ }
-      }
-
-      // Fallback to clean filename
-      if (!topic) {
-        topic = f.path.split('/').pop().replace(/\.md$/i, '');
-      }
-
-      // Lowercase first char for natural reading
-      topic = topic.charAt(0).toLowerCase() + topic.slice(1);
-
-      if (f.status_code === '?' || f.status_code === 'A') {
-        groups.add.push(topic);
-      } else if (f.status_code === 'D') {
-        groups.delete.push(topic);
-      } else {
-        groups.update.push(topic);
-      }
+    try {
+      return await this.invoke('git_commit_message', {
+        vaultPath: this.state.vaultPath,
+        files: [...new Set([...this._syncState.selectedFiles, ...this._syncState.partialFiles])],
+      });
+    } catch (_) {
+      return '[DOC] vault sync';
     }
-
-    const parts = [];
-    if (groups.add.length) parts.push('add ' + groups.add.join(', '));
-    if (groups.update.length) parts.push('update ' + groups.update.join(', '));
-    if (groups.delete.length) parts.push('remove ' + groups.delete.join(', '));
-
-    const msg = parts.join('; ') || 'vault sync';
-    return '[DOC] ' + msg;
   },
 
   // Step 3: Commit message
   async _syncStepCommit() {
-    if (this._syncState.selectedFiles.length === 0) {
+    if (this._syncState.selectedFiles.length === 0 && this._syncState.partialFiles.size === 0) {
       this._closeSyncModal();
       return;
     }
@@ -1158,12 +1632,14 @@ const App = {
     cancelBtn.style.display = 'none';
 
     try {
-      // Stage selected files
-      text.textContent = 'Agregando archivos al stage...';
-      await this.invoke('git_stage_files', {
-        path: this.state.vaultPath,
-        files: this._syncState.selectedFiles,
-      });
+      // Stage selected files (hunks picked one by one are already staged)
+      if (this._syncState.selectedFiles.length > 0) {
+        text.textContent = 'Agregando archivos al stage...';
+        await this.invoke('git_stage_files', {
+          path: this.state.vaultPath,
+          files: this._syncState.selectedFiles,
+        });
+      }
 
       // Commit
       text.textContent = 'Creando commit...';
@@ -1194,7 +1670,7 @@ const App = {
       icon.className = 'sync-step-icon error';
       icon.innerHTML = '&#10007;';
 
-      if (err === 'auth_error') {
+      if (err.kind === 'auth') {
         text.innerHTML = 'Error de autenticacion.<br>Configura SSH keys para tu repositorio.';
       } else {
         text.textContent = 'Error: ' + err;
@@ -1295,10 +1771,95 @@ const App = {
     s.setProperty('--sync-color-bg', bg); 
   }, 
 
+  // Claves de localStorage que no son preferencias y se quedan ahi
+  _localOnlyKeys: ['potato-claude-history', 'potato-mcp-scan-results'],
+
+  // Carga settings.json; la primera vez el backend importa lo que habia en
+  // localStorage (import_local_settings no hace nada si el archivo existe).
+  async _initSettings() {
+    const values = {};
+    for (let i = 0; i < localStorage.length; i++) {
+      const key = localStorage.key(i);
+      if (key.startsWith('potato-') && !this._localOnlyKeys.includes(key)) {
+        values[key] = localStorage.getItem(key);
+      }
+    }
+
+    try {
+      this.settings = await this.invoke('import_local_settings', { values });
+    } catch (err) {
+      console.warn('No se pudieron cargar las preferencias:', err);
+    }
+    this.settings = this.settings || this._defaultSettings();
+
+    const warnings = this.settings.warnings || [];
+    warnings.forEach((w) => console.warn('settings.json:', String(CommandError.from(w))));
+    if (warnings.length) this._setStatus(String(CommandError.from(warnings[0])));
+
+    if (window.__TAURI__ && window.__TAURI__.event) {
+      window.__TAURI__.event.listen('settings-changed', (event) => {
+        this.settings = event.payload.settings;
+      });
+    }
+  },
+
+  // Valores por defecto en modo standalone (mismos que Settings::default)
+  _defaultSettings() {
+    return {
+      version: 1,
+      locale: null,
+      editor: { autosave: true, toc: false, font_size: 14 },
+      vault: { show_attachments: false, use_gitignore: false, attachments_folder: null },
+      sync: { indicators: true, color: '#10b981', auto_sync: false, auto_sync_minutes: 5 },
+      periodic: { daily: null, weekly: null, monthly: null },
+      claude: {
+        workdir: null, project_dir: null, commands_dir: null,
+        agents_only: false, context: '', mcp_selected: [],
+      },
+      updates: { whatsnew_version: null, dismissed_version: null },
+    };
+  },
+
+  // Lee una preferencia por su ruta con puntos ('editor.autosave')
+  setting(key) {
+    return key.split('.').reduce((obj, part) => (obj == null ? obj : obj[part]), this.settings);
+  },
+
+  // Guarda una preferencia; el backend valida y emite 'settings-changed'
+  async saveSetting(key, value) {
+    try {
+      this.settings = await this.invoke('set_setting', { key, value }) || this.settings;
+    } catch (err) {
+      this._setStatus(`Error: ${err}`);
+    }
+  },
+
+  // Lee la configuracion efectiva del vault; el backend valida .potato/config
+  // y devuelve como avisos las claves que ignoro.
+  async _loadVaultConfig() {
+    this.vaultConfig = null;
+    if (!this.state.vaultPath) return;
+    try {
+      this.vaultConfig = await this.invoke('get_vault_config', { vaultPath: this.state.vaultPath });
+    } catch (err) {
+      console.warn('No se pudo leer la configuracion del vault:', err);
+      return;
+    }
+    const warnings = (this.vaultConfig && this.vaultConfig.warnings) || [];
+    warnings.forEach((w) => console.warn('.potato/config:', String(CommandError.from(w))));
+    if (warnings.length) this._setStatus(String(CommandError.from(warnings[0])));
+  },
+
+  // Valor de `key` que fija el .potato/config del vault, o `fallback` (el
+  // de las preferencias globales) si el vault no lo define.
+  vaultSetting(key, fallback) {
+    const entry = this.vaultConfig && this.vaultConfig.values[key];
+    return entry && entry.source === 'vault' ? entry.value : fallback;
+  },
+
   _loadSettings() {
     // Sync indicators
-    const storedSync = localStorage.getItem('potato-sync-indicators');
-    this.state.syncIndicators = storedSync === null ? true : storedSync === 'true';
+    this.state.syncIndicators = this.setting('sync.indicators');
 
     const syncToggle = document.getElementById('setting-sync-indicators');
     const syncColorRow = document.getElementById('setting-sync-color-row');
@@ -1306,14 +1867,14 @@ const App = {
       syncToggle.checked = this.state.syncIndicators;
       syncToggle.addEventListener('change', () => {
         this.state.syncIndicators = syncToggle.checked;
-        localStorage.setItem('potato-sync-indicators', syncToggle.checked);
+        this.saveSetting('sync.indicators', syncToggle.checked);
         if (syncColorRow) syncColorRow.style.display = syncToggle.checked ? '' : 'none';
         this.refreshVault();
       });
     }
 
     // Sync color
-    const savedColor = localStorage.getItem('potato-sync-color') || '#10b981';
+    const savedColor = this.setting('sync.color');
     this._applySyncColor(savedColor);
 
     if (syncColorRow) {
@@ -1330,7 +1891,7 @@ const App = {
             container.querySelectorAll('.sync-color-swatch, .sync-color-custom').forEach(el => el.classList.remove('active'));
             sw.classList.add('active');
             this._applySyncColor(c.hex);
-            localStorage.setItem('potato-sync-color', c.hex);
+            this.saveSetting('sync.color', c.hex);
           });
           container.appendChild(sw);
         });
@@ -1353,7 +1914,7 @@ const App = {
           custom.classList.add('active');
           dot.style.background = hex;
           this._applySyncColor(hex);
-          localStorage.setItem('potato-sync-color', hex);
+          this.saveSetting('sync.color', hex);
         });
         custom.appendChild(input);
         container.appendChild(custom);
@@ -1361,31 +1922,94 @@ const App = {
     }
 
     // Auto-save
-    const storedAutosave = localStorage.getItem('potato-autosave');
-    this.state.autosave = storedAutosave === null ? true : storedAutosave === 'true';
+    this.state.autosave = this.setting('editor.autosave');
 
     const autosaveToggle = document.getElementById('setting-autosave');
     if (autosaveToggle) {
       autosaveToggle.checked = this.state.autosave;
       autosaveToggle.addEventListener('change', () => {
         this.state.autosave = autosaveToggle.checked;
-        localStorage.setItem('potato-autosave', autosaveToggle.checked);
+        this.saveSetting('editor.autosave', autosaveToggle.checked);
       });
     }
 
     // TOC (table of contents)
-    const storedToc = localStorage.getItem('potato-toc');
-    this.state.toc = storedToc === null ? false : storedToc === 'true';
+    this.state.toc = this.setting('editor.toc');
 
     const tocToggle = document.getElementById('setting-toc');
     if (tocToggle) {
       tocToggle.checked = this.state.toc;
       tocToggle.addEventListener('change', () => {
         this.state.toc = tocToggle.checked;
-        localStorage.setItem('potato-toc', tocToggle.checked);
+        this.saveSetting('editor.toc', tocToggle.checked);
         this._updateToc();
       });
     }
+
+    // Attachments in sidebar
+    this.state.attachments = this.setting('vault.show_attachments');
+
+    const attachmentsToggle = document.getElementById('setting-attachments');
+    if (attachmentsToggle) {
+      attachmentsToggle.checked = this.state.attachments;
+      attachmentsToggle.addEventListener('change', () => {
+        this.state.attachments = attachmentsToggle.checked;
+        this.saveSetting('vault.show_attachments', attachmentsToggle.checked);
+        this.refreshVault();
+      });
+    }
+
+    // Sincronizacion automatica en segundo plano
+    this.state.autoSync = this.setting('sync.auto_sync');
+
+    const autoSyncToggle = document.getElementById('setting-autosync');
+    if (autoSyncToggle) {
+      autoSyncToggle.checked = this.state.autoSync;
+      autoSyncToggle.addEventListener('change', () => {
+        this.state.autoSync = autoSyncToggle.checked;
+        this.saveSetting('sync.auto_sync', autoSyncToggle.checked);
+        this._updateAutoSync();
+      });
+    }
+
+    const autoSyncMinutes = document.getElementById('setting-autosync-minutes');
+    if (autoSyncMinutes) {
+      autoSyncMinutes.value = this._autoSyncMinutes();
+      autoSyncMinutes.addEventListener('change', async () => {
+        const minutes = parseInt(autoSyncMinutes.value, 10);
+        await this.saveSetting('sync.auto_sync_minutes', minutes > 0 ? minutes : 5);
+        autoSyncMinutes.value = this._autoSyncMinutes();
+        this._updateAutoSync();
+      });
+    }
+
+    // .gitignore del vault como reglas de ignorados
+    this.state.useGitignore = this.setting('vault.use_gitignore');
+
+    const gitignoreToggle = document.getElementById('setting-use-gitignore');
+    if (gitignoreToggle) {
+      gitignoreToggle.checked = this.state.useGitignore;
+      gitignoreToggle.addEventListener('change', async () => {
+        this.state.useGitignore = gitignoreToggle.checked;
+        this.saveSetting('vault.use_gitignore', gitignoreToggle.checked);
+        if (!this.state.vaultPath) return;
+        await this.invoke('set_ignore_options', {
+          vaultPath: this.state.vaultPath,
+          useGitignore: this.state.useGitignore,
+        }).catch(() => {});
+        this.refreshVault();
+      });
+    }
+
+    // Idioma de los mensajes del backend (vacio = el del sistema). El
+    // backend lo aplica al guardarlo.
+    const localeSelect = document.getElementById('setting-locale');
+    if (localeSelect) {
+      localeSelect.value = this.setting('locale') || '';
+      localeSelect.addEventListener('change', () => {
+        this.saveSetting('locale', localeSelect.value || null);
+      });
+    }
   },
 
   _showSettings() {
@@ -1425,14 +2049,8 @@ const App = {
   },
 
   _showGitError(err, action) {
-    const msg = typeof err === 'string' ? err : String(err);
-    const isAuth = msg.includes('Authentication') ||
-                   msg.includes('Permission denied') ||
-                   msg.includes('autenticacion') ||
-                   msg.includes('terminal prompts disabled') ||
-                   msg.includes('could not read Username');
-
-    if (isAuth) {
+    const msg = String(err);
+    if (err && err.kind === 'auth') {
       this._setStatus('Auth error');
       alert(
         'No se pudo autenticar con GitHub.\n\n' +
@@ -1547,13 +2165,51 @@ const App = {
     // Refresh sidebar (picks up new/deleted/renamed files)
     await this.refreshVault();
 
+    await this._reloadCurrentNoteFromDisk();
+  },
+
+  _vaultChangeTimeout: null,
+
+  _onVaultChanged(payload) {
+    if (!payload || payload.vault !== this.state.vaultPath) return;
+
+    // Agrupar rafagas (p.ej. git pull) en un solo refresh
+    clearTimeout(this._vaultChangeTimeout);
+    this._vaultChangeTimeout = setTimeout(() => this.refreshVault(), 200);
+
+    // Sin cambios de archivos: cambiaron las reglas o .potato/config
+    if (!payload.changes.length) {
+      this._loadVaultConfig().then(() => {
+        this._updateAutoSync();
+        Claude.onVaultChanged();
+      });
+    }
+
+    const note = this.state.currentNote;
+    if (!note) return;
+
+    // Si la nota abierta fue renombrada/movida, seguirla a su nueva ruta
+    const renamed = payload.changes.find((c) => c.kind === 'renamed' && c.from === note.path);
+    if (renamed) {
+      this.state.currentNote = { path: renamed.path, title: renamed.entry ? renamed.entry.name : note.title };
+      Sidebar.setActive(renamed.path);
+      this._saveSession();
+      return;
+    }
+
+    if (payload.changes.some((c) => c.path === note.path)) {
+      this._reloadCurrentNoteFromDisk();
+    }
+  },
+
+  async _reloadCurrentNoteFromDisk() {
     // Check if current note was changed externally
     if (!this.state.currentNote) return;
 
     try {
-      const diskContent = await this.invoke('read_note', { path: this.state.currentNote.path });
+      const diskNote = await this.invoke('read_note', { path: this.state.currentNote.path });
 
-      if (diskContent === null) {
+      if (diskNote === null) {
         // File was deleted externally
         this._setStatus('Nota eliminada externamente');
         this.state.dirty = false;
@@ -1563,11 +2219,13 @@ const App = {
       }
 
       // Compare with last saved version (not editor, to detect external edits)
+      const diskContent = diskNote.content;
       if (this._lastSavedContent !== null && diskContent !== this._lastSavedContent) {
         if (!this.state.dirty) {
           // No local changes: reload silently
           Editor.setValue(diskContent);
           this._lastSavedContent = diskContent;
+          this._lastRevision = diskNote.revision;
           Preview.update(diskContent);
           this._updateToc();
           this._setStatus('Nota recargada');
@@ -1588,13 +2246,40 @@ const App = {
     }
   },
 
+  /**
+   * La nota cambio en disco desde que se cargo (Claude, git pull, otro editor).
+   * Se pregunta si sobrescribir con la version local o recargar la de disco.
+   */
+  async _resolveSaveConflict(result) {
+    const { disk_content: diskContent } = result.conflict;
+    const overwrite = confirm(
+      'La nota cambio en disco desde que la abriste.\n\n'
+      + 'Aceptar: sobrescribir con tu version.\n'
+      + 'Cancelar: descartar tus cambios y recargar la version en disco.'
+    );
+
+    if (overwrite) {
+      // Reintentar contra la revision actual en disco
+      this._lastRevision = result.revision;
+      this.state.dirty = true;
+      await this.saveCurrentNote();
+    } else {
+      Editor.setValue(diskContent);
+      Preview.update(diskContent);
+      this._updateToc();
+      this._lastSavedContent = diskContent;
+      this._lastRevision = result.revision;
+      this.state.dirty = false;
+      this._setStatus('Nota recargada desde disco');
+    }
+  },
+
   // -- Font size ---------------------------------------------------------------
 
   _fontSize: 14,
 
   _loadFontSize() {
-    const stored = localStorage.getItem('potato-font-size');
-    this._fontSize = stored ? parseInt(stored, 10) : 14;
+    this._fontSize = this.setting('editor.font_size');
     this._applyFontSize();
   },
 
@@ -1602,7 +2287,7 @@ const App = {
     const newSize = Math.max(10, Math.min(24, this._fontSize + delta));
     if (newSize === this._fontSize) return;
     this._fontSize = newSize;
-    localStorage.setItem('potato-font-size', newSize);
+    this.saveSetting('editor.font_size', newSize);
     this._applyFontSize();
   },
 
@@ -1764,9 +2449,11 @@ const App = {
         const badgeClass = r.match_type === 'name' ? 'name' : 'content';
         const badgeText = r.match_type === 'name' ? 'nombre' : 'L' + r.line;
 
-        // Highlight query in name and preview
-        const highlightedName = this._highlightText(r.name, query);
-        const highlightedPreview = r.preview ? this._highlightText(r.preview, query) : '';
+        // Resaltar con los rangos que devuelve el backend (ignora acentos y prefijos)
+        const highlightedName = r.match_type === 'name'
+          ? this._highlightRanges(r.name, r.matches)
+          : this._highlightText(r.name, query);
+        const highlightedPreview = r.preview ? this._highlightRanges(r.preview, r.matches) : '';
 
         let html = '<div class="search-result-top">'
           + '<span class="search-result-name">' + highlightedName + '</span>'
@@ -1799,6 +2486,18 @@ const App = {
     return text.replace(/</g, '&lt;').replace(/>/g, '&gt;').replace(re, '<mark>$1</mark>');
   },
 
+  _highlightRanges(text, ranges) {
+    const esc = (t) => t.replace(/&/g, '&amp;').replace(/</g, '&lt;').replace(/>/g, '&gt;');
+    let html = '';
+    let pos = 0;
+    for (const { start, end } of ranges || []) {
+      if (start < pos) continue;
+      html += esc(text.slice(pos, start)) + '<mark>' + esc(text.slice(start, end)) + '</mark>';
+      pos = end;
+    }
+    return html + esc(text.slice(pos));
+  },
+
   _highlightSearchResult(items) {
     items.forEach((item, i) => {
       item.classList.toggle('active', i === this._searchIndex);
@@ -1844,7 +2543,7 @@ const App = {
   _whatsNewTotal: 0,
 
   _checkWhatsNew() {
-    const seen = localStorage.getItem('potato-whatsnew-version');
+    const seen = this.setting('updates.whatsnew_version');
     if (seen === this._appVersion) return;
     // No mostrar en primera instalación (sin historial previo)
     if (!seen && !localStorage.getItem('potato-claude-history')) return;
@@ -1907,7 +2606,7 @@ const App = {
   },
 
   _closeWhatsNew() {
-    localStorage.setItem('potato-whatsnew-version', this._appVersion);
+    this.saveSetting('updates.whatsnew_version', this._appVersion);
     document.getElementById('whatsnew-modal').classList.remove('open');
   },
 
@@ -1931,7 +2630,7 @@ const App = {
       if (!this._isNewer(latest, this._appVersion)) return;
 
       // No mostrar si ya dismisseó esta versión
-      const dismissed = localStorage.getItem('potato-update-dismissed');
+      const dismissed = this.setting('updates.dismissed_version');
       if (dismissed === latest) return;
 
       // Buscar asset descargable (.deb o .AppImage)
@@ -1969,7 +2668,7 @@ const App = {
 
     document.getElementById('update-close').onclick = () => {
       banner.classList.add('hidden');
-      localStorage.setItem('potato-update-dismissed', version);
+      this.saveSetting('updates.dismissed_version', version);
     };
 
     banner.classList.remove('hidden');
@@ -2001,7 +2700,7 @@ const App = {
       btn.disabled = false;
       btn.textContent = 'Actualizar';
       closeBtn.style.display = '';
-      this._setStatus(typeof err === 'string' ? err : 'Error al actualizar');
+      this._setStatus(String(err));
       setTimeout(() => {
         if (this.state.currentNote) {
           this._setStatus(this.state.currentNote.title);
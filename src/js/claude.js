@@ -39,7 +39,7 @@ const Claude = {
     this._messagesEl = document.getElementById(containerId);
     this._container = document.getElementById('claude-panel');
     this._inputEl = document.getElementById('claude-input');
-    this.invoke = window.__TAURI__ ? window.__TAURI__.core.invoke : async () => null;
+    this.invoke = window.__TAURI__ ? CommandError.wrap(window.__TAURI__.core.invoke) : async () => null;
 
     // Send button
     document.getElementById('claude-send-btn').addEventListener('click', () => this.sendMessage());
@@ -95,13 +95,13 @@ const Claude = {
     });
 
     // Working directory - load saved
-    const savedDir = localStorage.getItem('potato-claude-workdir');
+    const savedDir = App.setting('claude.workdir');
     if (savedDir) {
       this.state.workingDir = savedDir;
     }
 
     // Project directory - load saved
-    const savedProjectDir = localStorage.getItem('potato-claude-projectdir');
+    const savedProjectDir = App.setting('claude.project_dir');
     if (savedProjectDir) {
       this.state.projectDir = savedProjectDir;
     }
@@ -125,12 +125,11 @@ const Claude = {
     document.getElementById('setting-claude-projectdir-input').addEventListener('blur', () => this._setProjectDirFromInput());
 
     // Agents only toggle - load saved preference
-    const savedAgentsOnly = localStorage.getItem('potato-claude-agents-only');
-    if (savedAgentsOnly === 'true') this.state.agentsOnly = true;
+    this.state.agentsOnly = App.setting('claude.agents_only');
 
     document.getElementById('setting-claude-agents-only').addEventListener('change', (e) => {
       this.state.agentsOnly = e.target.checked;
-      localStorage.setItem('potato-claude-agents-only', e.target.checked);
+      App.saveSetting('claude.agents_only', e.target.checked);
     });
 
     // MCP servers - load saved scan results and selection
@@ -138,26 +137,24 @@ const Claude = {
       const savedMcp = localStorage.getItem('potato-mcp-scan-results');
       if (savedMcp) this.state.mcpServers = JSON.parse(savedMcp);
     } catch (_) {}
-    try {
-      const savedSel = localStorage.getItem('potato-mcp-selected');
-      if (savedSel) this.state.mcpSelected = JSON.parse(savedSel);
-    } catch (_) {}
+    this.state.mcpSelected = [...App.setting('claude.mcp_selected')];
 
     document.getElementById('setting-mcp-scan-btn').addEventListener('click', () => this._scanMcpServers());
 
     // User context - load saved
-    this._userContext = localStorage.getItem('potato-claude-context') || '';
+    this._userContext = App.setting('claude.context');
     const ctxEl = document.getElementById('setting-claude-context');
     if (ctxEl) {
       ctxEl.value = this._userContext;
       ctxEl.addEventListener('input', () => {
         this._userContext = ctxEl.value;
-        localStorage.setItem('potato-claude-context', ctxEl.value);
+        clearTimeout(this._contextSaveTimeout);
+        this._contextSaveTimeout = setTimeout(() => App.saveSetting('claude.context', ctxEl.value), 500);
       });
     }
 
     // Commands directory - load saved + listeners
-    this.state.commandsDir = localStorage.getItem('potato-claude-commandsdir') || null;
+    this.state.commandsDir = App.setting('claude.commands_dir');
 
     document.getElementById('setting-claude-commandsdir-btn').addEventListener('click', () => this._pickCommandsDir());
     document.getElementById('setting-claude-commandsdir-input').addEventListener('keydown', (e) => {
@@ -349,9 +346,10 @@ const Claude = {
     return this._getAgentsDir() || App.state.vaultPath;
   },
 
-  // Directorio para cargar agentes (independiente del modo)
+  // Directorio para cargar agentes (independiente del modo). El del
+  // .potato/config del vault manda sobre el de Ajustes.
   _getAgentsDir() {
-    return this.state.projectDir || this.state.workingDir || App.state.vaultPath;
+    return this.state.projectDir || App.vaultSetting('claude.agents_dir', this.state.workingDir) || App.state.vaultPath;
   },
 
   // Construye el system prompt del vault dinámicamente con los agentes disponibles
@@ -475,7 +473,7 @@ const Claude = {
     const path = await this.invoke('open_vault');
     if (!path) return;
     this.state.workingDir = path;
-    localStorage.setItem('potato-claude-workdir', path);
+    App.saveSetting('claude.workdir', path);
     this._updateWorkdirDisplay();
     this.newChat();
     this._loadAgents();
@@ -494,11 +492,7 @@ const Claude = {
     const path = (input.value || '').trim();
     if (path === (this.state.workingDir || '')) return;
     this.state.workingDir = path || null;
-    if (path) {
-      localStorage.setItem('potato-claude-workdir', path);
-    } else {
-      localStorage.removeItem('potato-claude-workdir');
-    }
+    App.saveSetting('claude.workdir', path || null);
     this.newChat();
     this._loadAgents();
     this._loadCommands();
@@ -508,7 +502,7 @@ const Claude = {
     const path = await this.invoke('open_vault');
     if (!path) return;
     this.state.projectDir = path;
-    localStorage.setItem('potato-claude-projectdir', path);
+    App.saveSetting('claude.project_dir', path);
     this._updateProjectDirDisplay();
     this.newChat();
     this._loadAgents();
@@ -527,22 +521,17 @@ const Claude = {
     const path = (input.value || '').trim();
     if (path === (this.state.projectDir || '')) return;
     this.state.projectDir = path || null;
-    if (path) {
-      localStorage.setItem('potato-claude-projectdir', path);
-    } else {
-      localStorage.removeItem('potato-claude-projectdir');
-    }
+    App.saveSetting('claude.project_dir', path || null);
     this.newChat();
     this._loadAgents();
     this._loadCommands();
   },
 
   onVaultChanged() {
-    // Recargar agentes si no hay workdir/projectdir custom (usa vault)
-    if (!this.state.workingDir && !this.state.projectDir) {
-      this._loadAgents();
+    // Recargar agentes siempre: sin workdir/projectdir custom salen del
+    // vault, y el .potato/config de cada vault puede fijar otro directorio
+    this._loadAgents();
     this._loadCommands();
-    }
   },
 
   updateContext() {
@@ -1485,7 +1474,7 @@ const Claude = {
     const path = await this.invoke('open_vault');
     if (!path) return;
     this.state.commandsDir = path;
-    localStorage.setItem('potato-claude-commandsdir', path);
+    App.saveSetting('claude.commands_dir', path);
     this._updateCommandsDirDisplay();
     this._loadCommands();
   },
@@ -1502,11 +1491,7 @@ const Claude = {
     const path = (input.value || '').trim();
     if (path === (this.state.commandsDir || '')) return;
     this.state.commandsDir = path || null;
-    if (path) {
-      localStorage.setItem('potato-claude-commandsdir', path);
-    } else {
-      localStorage.removeItem('potato-claude-commandsdir');
-    }
+    App.saveSetting('claude.commands_dir', path || null);
     this._loadCommands();
   },
 
@@ -1527,7 +1512,7 @@ const Claude = {
       // Limpiar selecciones de servers que ya no existen
       const validNames = new Set(this.state.mcpServers.map(s => s.name));
       this.state.mcpSelected = this.state.mcpSelected.filter(n => validNames.has(n));
-      localStorage.setItem('potato-mcp-selected', JSON.stringify(this.state.mcpSelected));
+      App.saveSetting('claude.mcp_selected', this.state.mcpSelected);
 
       this._renderMcpList();
       const count = this.state.mcpServers.length;
@@ -1602,7 +1587,7 @@ const Claude = {
           } else {
             this.state.mcpSelected = this.state.mcpSelected.filter(n => n !== server.name);
           }
-          localStorage.setItem('potato-mcp-selected', JSON.stringify(this.state.mcpSelected));
+          App.saveSetting('claude.mcp_selected', this.state.mcpSelected);
         });
         const slider = document.createElement('span');
         slider.className = 'switch-slider';
@@ -1686,6 +1671,7 @@ const Claude = {
       el.className = 'claude-ac-item' + (i === 0 ? ' active' : '');
       el.dataset.name = item.name;
       el.innerHTML = `<span class="claude-ac-name">${prefix}${this._escapeHtml(item.name)}</span>` +
+        (item.argument_hint ? ` <span class="claude-ac-desc">${this._escapeHtml(item.argument_hint)}</span>` : '') +
         (item.description ? `<span class="claude-ac-desc">${this._escapeHtml(item.description)}</span>` : '');
       el.addEventListener('mousedown', (e) => {
         e.preventDefault();
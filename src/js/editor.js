@@ -4,6 +4,8 @@
 const Editor = {
   _el: null,
   _onChange: null,
+  _onPasteFile: null,
+  _canPasteFile: null,
 
   init(elementId, onChange) {
     this._el = document.getElementById(elementId);
@@ -20,6 +22,15 @@ const Editor = {
       }, 150);
     });
 
+    // Pegar imagenes u otros archivos: se delega para guardarlos como adjunto.
+    // Si no hay donde guardarlos, el pegado sigue su curso normal
+    this._el.addEventListener('paste', (e) => {
+      const files = Array.from(e.clipboardData ? e.clipboardData.files : []);
+      if (files.length === 0 || !this._onPasteFile || !this._canPasteFile()) return;
+      e.preventDefault();
+      files.forEach((file) => this._onPasteFile(file));
+    });
+
     // Tab key inserts spaces
     this._el.addEventListener('keydown', (e) => {
       if (e.key === 'Tab') {
@@ -59,4 +70,22 @@ const Editor = {
   focus() {
     this._el.focus();
   },
+
+  onPasteFile(handler, canPaste = () => true) {
+    this._onPasteFile = handler;
+    this._canPasteFile = canPaste;
+  },
+
+  insertAtCursor(text) {
+    const start = this._el.selectionStart;
+    const end = this._el.selectionEnd;
+    this._el.value = this._el.value.substring(0, start) + text + this._el.value.substring(end);
+    this._el.selectionStart = this._el.selectionEnd = start + text.length;
+    this._el.dispatchEvent(new Event('input'));
+  },
+
+  setCursor(pos) {
+    this._el.focus();
+    this._el.selectionStart = this._el.selectionEnd = pos;
+  },
 };
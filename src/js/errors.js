@@ -0,0 +1,45 @@
+/**
+ * Errores de los comandos del backend.
+ *
+ * Rust los serializa como { kind, key, params, detail, message }. Se decide
+ * que hacer segun `kind` ('auth', 'conflict', 'dirty', 'not_merged', ...),
+ * nunca segun el texto; `String(err)` da el mensaje con el detalle.
+ */
+const CommandError = {
+  /**
+   * Normaliza cualquier valor rechazado por invoke.
+   */
+  from(err) {
+    if (err && err.kind && err.key) {
+      return this._make(err.kind, err.key, err.params, err.detail, err.message);
+    }
+    const text = err instanceof Error ? err.message : String(err);
+    return this._make('internal', 'internal', {}, null, text);
+  },
+
+  /**
+   * Envuelve el invoke de Tauri para que los errores lleguen normalizados.
+   */
+  wrap(invoke) {
+    return async (cmd, args) => {
+      try {
+        return await invoke(cmd, args);
+      } catch (err) {
+        throw CommandError.from(err);
+      }
+    };
+  },
+
+  _make(kind, key, params, detail, message) {
+    return {
+      kind,
+      key,
+      params: params || {},
+      detail: detail || null,
+      message: message || key,
+      toString() {
+        return this.detail ? this.message + ': ' + this.detail : this.message;
+      },
+    };
+  },
+};
@@ -256,14 +256,21 @@ const Sidebar = {
     // Make file draggable
     item.draggable = true;
 
+    const isAttachment = entry.kind && entry.kind !== 'note';
+    if (isAttachment) item.classList.add('attachment');
+
     const icon = document.createElement('span');
     icon.className = 'tree-icon';
-    icon.textContent = isUnsynced ? '\u25CF' : '\u25CB'; // ● vs ○
+    if (isAttachment) {
+      icon.textContent = entry.kind === 'image' ? '\u25A3' : '\u25A1'; // ▣ vs □
+    } else {
+      icon.textContent = isUnsynced ? '\u25CF' : '\u25CB'; // ● vs ○
+    }
     item.appendChild(icon);
 
     const label = document.createElement('span');
     label.className = 'tree-label';
-    label.textContent = this._cleanName(entry.name, false);
+    label.textContent = isAttachment ? entry.name : this._cleanName(entry.name, false);
     item.appendChild(label);
 
     // Sync badge for unsynced files
@@ -278,6 +285,8 @@ const Sidebar = {
     item.title = entry.name;
 
     item.addEventListener('click', () => {
+      // Los adjuntos no se abren en el editor
+      if (isAttachment) return;
       this.setActive(entry.path);
       if (this._onSelect) {
         this._onSelect(entry.path, entry.name);